@@ -1,10 +1,11 @@
-use apm::{CommandStarted, CommandResult};
+use apm::{CommandStarted, CommandResult, TopologyEvent};
 use Client;
 use error::Result;
 
 pub trait EventRunner {
     fn run_start_hooks(&self, hook: &CommandStarted) -> Result<()>;
     fn run_completion_hooks(&self, hook: &CommandResult) -> Result<()>;
+    fn run_topology_hooks(&self, event: &TopologyEvent) -> Result<()>;
 }
 
 impl EventRunner for Client {
@@ -15,4 +16,8 @@ impl EventRunner for Client {
     fn run_completion_hooks(&self, hook: &CommandResult) -> Result<()> {
         self.listener.run_completion_hooks(self.clone(), hook)
     }
+
+    fn run_topology_hooks(&self, event: &TopologyEvent) -> Result<()> {
+        self.listener.run_topology_hooks(self.clone(), event)
+    }
 }
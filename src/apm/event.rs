@@ -1,6 +1,7 @@
 use std::fmt::{Display, Error, Formatter};
 
 use bson::Document;
+use connstring::Host;
 use error::Error as MongoError;
 use separator::Separatable;
 
@@ -11,13 +12,17 @@ pub struct CommandStarted {
     pub command_name: String,
     pub request_id: i64,
     pub connection_string: String,
+    /// Whether the command retries an attempt that failed with a network or failover error.
+    pub retry: bool,
 }
 
 impl Display for CommandStarted {
     fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
-        fmt.write_fmt(format_args!("COMMAND.{} {} STARTED: {}",
+        let retry = if self.retry { " (RETRY)" } else { "" };
+        fmt.write_fmt(format_args!("COMMAND.{} {} STARTED{}: {}",
                                    self.command_name,
                                    self.connection_string,
+                                   retry,
                                    self.command))
     }
 }
@@ -68,3 +73,24 @@ impl<'a> Display for CommandResult<'a> {
         }
     }
 }
+
+/// Describes a change in the state of the monitored topology.
+pub enum TopologyEvent {
+    /// A server was removed from monitoring and its connections were closed.
+    ServerClosed { host: Host },
+    /// The topology was closed; no further operations will be executed.
+    TopologyClosed,
+}
+
+impl Display for TopologyEvent {
+    fn fmt(&self, fmt: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            TopologyEvent::ServerClosed { ref host } => {
+                fmt.write_fmt(format_args!("TOPOLOGY.SERVER_CLOSED {}:{}",
+                                           host.host_name,
+                                           host.port))
+            }
+            TopologyEvent::TopologyClosed => fmt.write_str("TOPOLOGY.CLOSED"),
+        }
+    }
+}
@@ -2,18 +2,21 @@ use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::RwLock;
 
-use apm::event::{CommandStarted, CommandResult};
+use apm::event::{CommandStarted, CommandResult, TopologyEvent};
 use Client;
 use error::{Error, Result};
 
 pub type StartHook = fn(Client, &CommandStarted);
 pub type CompletionHook = fn(Client, &CommandResult);
+pub type TopologyHook = fn(Client, &TopologyEvent);
 
 pub struct Listener {
     no_start_hooks: AtomicBool,
     no_completion_hooks: AtomicBool,
+    no_topology_hooks: AtomicBool,
     start_hooks: RwLock<Vec<StartHook>>,
     completion_hooks: RwLock<Vec<CompletionHook>>,
+    topology_hooks: RwLock<Vec<TopologyHook>>,
 }
 
 impl Listener {
@@ -21,8 +24,10 @@ impl Listener {
         Listener {
             no_start_hooks: AtomicBool::new(true),
             no_completion_hooks: AtomicBool::new(true),
+            no_topology_hooks: AtomicBool::new(true),
             start_hooks: RwLock::new(vec![]),
             completion_hooks: RwLock::new(vec![]),
+            topology_hooks: RwLock::new(vec![]),
         }
     }
 
@@ -46,6 +51,16 @@ impl Listener {
         Ok(guard.deref_mut().push(hook))
     }
 
+    pub fn add_topology_hook(&self, hook: TopologyHook) -> Result<()> {
+        let mut guard = match self.topology_hooks.write() {
+            Ok(guard) => guard,
+            Err(_) => return Err(Error::PoisonLockError),
+        };
+
+        self.no_topology_hooks.store(false, Ordering::SeqCst);
+        Ok(guard.deref_mut().push(hook))
+    }
+
     pub fn run_start_hooks(&self, client: Client, started: &CommandStarted) -> Result<()> {
         if self.no_start_hooks.load(Ordering::SeqCst) {
             return Ok(());
@@ -79,4 +94,21 @@ impl Listener {
 
         Ok(())
     }
+
+    pub fn run_topology_hooks(&self, client: Client, event: &TopologyEvent) -> Result<()> {
+        if self.no_topology_hooks.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let guard = match self.topology_hooks.read() {
+            Ok(guard) => guard,
+            Err(_) => return Err(Error::PoisonLockError),
+        };
+
+        for hook in guard.deref().iter() {
+            hook(client.clone(), event);
+        }
+
+        Ok(())
+    }
 }
@@ -9,5 +9,5 @@ mod event;
 mod listener;
 
 pub use self::client::EventRunner;
-pub use self::event::{CommandStarted, CommandResult};
+pub use self::event::{CommandStarted, CommandResult, TopologyEvent};
 pub use self::listener::Listener;
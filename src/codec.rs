@@ -0,0 +1,788 @@
+//! Conversions between BSON documents and types implementing `rustc_serialize`'s
+//! `Encodable` and `Decodable` traits.
+//!
+//! Values are converted directly to and from BSON. Integers keep their width, with 8, 16
+//! and 32-bit integers stored as 32-bit BSON integers, and `UtcDateTime` fields are stored as
+//! BSON datetimes. Likewise, `Oid` fields are stored as BSON object ids. `bson::oid::ObjectId`
+//! encodes itself as a hex string and panics when decoding a string that is not one, so typed
+//! values should use `Oid` for their object ids. An `_id` field that encodes to `null`, such
+//! as an unset `Option<Oid>`, is omitted so that the server or driver can generate an
+//! identifier.
+//!
+//! The `bson` crate's own `Encoder` and `Decoder` implement `serde`'s traits instead.
+use bson::{self, Bson, DecoderError, DecoderResult, EncoderError, EncoderResult};
+use bson::oid;
+use chrono::{DateTime, TimeZone, Timelike, UTC};
+use rustc_serialize::{self, Decodable, Encodable};
+
+use Result;
+use Error;
+
+use std::{i32, i64, result};
+
+/// Decodes a document into a value, reporting the document's `_id` on failure.
+pub fn decode_document<T: Decodable>(doc: bson::Document) -> Result<T> {
+    let id = doc.get("_id").cloned();
+    let mut decoder = Decoder::new(Bson::Document(doc));
+
+    T::decode(&mut decoder).map_err(|err| {
+        let msg = match id {
+            Some(id) => format!("Failed to decode document with _id {}: {}", id, err),
+            None => format!("Failed to decode document without an _id: {}", err),
+        };
+
+        Error::DecoderError(DecoderError::Unknown(msg))
+    })
+}
+
+/// Encodes a value as a document.
+pub fn encode_document<T: Encodable>(value: &T) -> Result<bson::Document> {
+    let mut encoder = Encoder::new();
+    try!(value.encode(&mut encoder));
+
+    match try!(encoder.bson()) {
+        Bson::Document(mut doc) => {
+            if let Some(&Bson::Null) = doc.get("_id") {
+                doc.remove("_id");
+            }
+            Ok(doc)
+        }
+        _ => {
+            Err(Error::EncoderError(EncoderError::Unknown(String::from("Value must be encoded \
+                                                                        as a document."))))
+        }
+    }
+}
+
+/// A UTC datetime that is stored as a BSON datetime, with millisecond precision.
+///
+/// Other encoders see the extended JSON form `{ "$date": { "$numberLong": <millis> } }`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UtcDateTime(pub DateTime<UTC>);
+
+impl Encodable for UtcDateTime {
+    fn encode<S: rustc_serialize::Encoder>(&self, s: &mut S) -> result::Result<(), S::Error> {
+        let millis = self.0.timestamp() * 1000 + (self.0.nanosecond() / 1000000) as i64;
+
+        s.emit_struct("UtcDateTime", 1, |s| {
+            s.emit_struct_field("$date", 0, |s| {
+                s.emit_struct("$date", 1, |s| {
+                    s.emit_struct_field("$numberLong", 0, |s| s.emit_i64(millis))
+                })
+            })
+        })
+    }
+}
+
+impl Decodable for UtcDateTime {
+    fn decode<D: rustc_serialize::Decoder>(d: &mut D) -> result::Result<Self, D::Error> {
+        let millis: i64 = try!(d.read_struct("UtcDateTime", 1, |d| {
+            d.read_struct_field("$date", 0, |d| {
+                d.read_struct("$date", 1, |d| d.read_struct_field("$numberLong", 0, D::read_i64))
+            })
+        }));
+
+        let (secs, millis) = (millis / 1000, millis % 1000);
+        let (secs, millis) = if millis < 0 {
+            (secs - 1, millis + 1000)
+        } else {
+            (secs, millis)
+        };
+
+        Ok(UtcDateTime(UTC.timestamp(secs, millis as u32 * 1000000)))
+    }
+}
+
+/// An object id that is stored as a BSON object id.
+///
+/// Other encoders see the extended JSON form `{ "$oid": <hex string> }`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Oid(pub oid::ObjectId);
+
+impl Encodable for Oid {
+    fn encode<S: rustc_serialize::Encoder>(&self, s: &mut S) -> result::Result<(), S::Error> {
+        let hex = self.0.to_string();
+        s.emit_struct("Oid", 1, |s| s.emit_struct_field("$oid", 0, |s| s.emit_str(&hex)))
+    }
+}
+
+impl Decodable for Oid {
+    fn decode<D: rustc_serialize::Decoder>(d: &mut D) -> result::Result<Self, D::Error> {
+        let hex = try!(d.read_struct("Oid", 1, |d| d.read_struct_field("$oid", 0, D::read_str)));
+
+        match oid::ObjectId::with_string(&hex) {
+            Ok(id) => Ok(Oid(id)),
+            Err(_) => Err(d.error(&format!("{} is not a valid object id.", hex))),
+        }
+    }
+}
+
+// A document or array that is being encoded.
+enum Container {
+    // A document, along with the key of the map entry whose value is being encoded.
+    Document(bson::Document, Option<String>),
+    Array(Vec<Bson>),
+}
+
+/// Encodes `Encodable` values as BSON.
+pub struct Encoder {
+    // Containers that are being encoded, innermost last.
+    containers: Vec<Container>,
+    // The most recently encoded value.
+    value: Option<Bson>,
+}
+
+impl Encoder {
+    /// Returns an encoder for a single value.
+    pub fn new() -> Encoder {
+        Encoder {
+            containers: Vec::new(),
+            value: None,
+        }
+    }
+
+    /// Returns the encoded value.
+    pub fn bson(self) -> EncoderResult<Bson> {
+        self.value.ok_or_else(|| EncoderError::Unknown(String::from("No value was encoded.")))
+    }
+
+    fn emit(&mut self, value: Bson) -> EncoderResult<()> {
+        self.value = Some(value);
+        Ok(())
+    }
+
+    fn take(&mut self) -> EncoderResult<Bson> {
+        self.value
+            .take()
+            .ok_or_else(|| EncoderError::Unknown(String::from("No value was encoded.")))
+    }
+
+    // Encodes a document, converting extended JSON documents to the types they represent.
+    fn emit_document<F>(&mut self, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.containers.push(Container::Document(bson::Document::new(), None));
+        try!(f(self));
+
+        match self.containers.pop() {
+            Some(Container::Document(doc, _)) => self.emit(Bson::from_extended_document(doc)),
+            _ => Err(EncoderError::Unknown(String::from("Expected to encode a document."))),
+        }
+    }
+
+    fn emit_array<F>(&mut self, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.containers.push(Container::Array(Vec::new()));
+        try!(f(self));
+
+        match self.containers.pop() {
+            Some(Container::Array(values)) => self.emit(Bson::Array(values)),
+            _ => Err(EncoderError::Unknown(String::from("Expected to encode an array."))),
+        }
+    }
+
+    fn emit_field<F>(&mut self, name: &str, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        try!(f(self));
+        let value = try!(self.take());
+
+        match self.containers.last_mut() {
+            Some(&mut Container::Document(ref mut doc, _)) => {
+                doc.insert(name, value);
+                Ok(())
+            }
+            _ => Err(EncoderError::Unknown(format!("Field {} is outside of a document.", name))),
+        }
+    }
+
+    fn emit_element<F>(&mut self, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        try!(f(self));
+        let value = try!(self.take());
+
+        match self.containers.last_mut() {
+            Some(&mut Container::Array(ref mut values)) => {
+                values.push(value);
+                Ok(())
+            }
+            _ => Err(EncoderError::Unknown(String::from("Element is outside of an array."))),
+        }
+    }
+
+    fn emit_variant<F>(&mut self, name: &str, len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        // Like JSON, variants without data are encoded by name.
+        if len == 0 {
+            return self.emit(Bson::String(String::from(name)));
+        }
+
+        try!(self.emit_array(f));
+        let fields = try!(self.take());
+        self.emit(Bson::Document(doc! {
+            "variant" => name,
+            "fields" => fields
+        }))
+    }
+}
+
+impl rustc_serialize::Encoder for Encoder {
+    type Error = EncoderError;
+
+    fn emit_nil(&mut self) -> EncoderResult<()> {
+        self.emit(Bson::Null)
+    }
+
+    fn emit_usize(&mut self, v: usize) -> EncoderResult<()> {
+        self.emit_u64(v as u64)
+    }
+
+    fn emit_u64(&mut self, v: u64) -> EncoderResult<()> {
+        if v > i64::MAX as u64 {
+            return Err(EncoderError::Unknown(format!("{} does not fit in a BSON integer.", v)));
+        }
+        self.emit(Bson::I64(v as i64))
+    }
+
+    fn emit_u32(&mut self, v: u32) -> EncoderResult<()> {
+        self.emit(Bson::I64(v as i64))
+    }
+
+    fn emit_u16(&mut self, v: u16) -> EncoderResult<()> {
+        self.emit(Bson::I32(v as i32))
+    }
+
+    fn emit_u8(&mut self, v: u8) -> EncoderResult<()> {
+        self.emit(Bson::I32(v as i32))
+    }
+
+    fn emit_isize(&mut self, v: isize) -> EncoderResult<()> {
+        self.emit(Bson::I64(v as i64))
+    }
+
+    fn emit_i64(&mut self, v: i64) -> EncoderResult<()> {
+        self.emit(Bson::I64(v))
+    }
+
+    fn emit_i32(&mut self, v: i32) -> EncoderResult<()> {
+        self.emit(Bson::I32(v))
+    }
+
+    fn emit_i16(&mut self, v: i16) -> EncoderResult<()> {
+        self.emit(Bson::I32(v as i32))
+    }
+
+    fn emit_i8(&mut self, v: i8) -> EncoderResult<()> {
+        self.emit(Bson::I32(v as i32))
+    }
+
+    fn emit_bool(&mut self, v: bool) -> EncoderResult<()> {
+        self.emit(Bson::Boolean(v))
+    }
+
+    fn emit_f64(&mut self, v: f64) -> EncoderResult<()> {
+        self.emit(Bson::FloatingPoint(v))
+    }
+
+    fn emit_f32(&mut self, v: f32) -> EncoderResult<()> {
+        self.emit(Bson::FloatingPoint(v as f64))
+    }
+
+    fn emit_char(&mut self, v: char) -> EncoderResult<()> {
+        self.emit(Bson::String(v.to_string()))
+    }
+
+    fn emit_str(&mut self, v: &str) -> EncoderResult<()> {
+        self.emit(Bson::String(String::from(v)))
+    }
+
+    fn emit_enum<F>(&mut self, _name: &str, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        f(self)
+    }
+
+    fn emit_enum_variant<F>(&mut self,
+                            v_name: &str,
+                            _v_id: usize,
+                            len: usize,
+                            f: F)
+                            -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_variant(v_name, len, f)
+    }
+
+    fn emit_enum_variant_arg<F>(&mut self, _a_idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_element(f)
+    }
+
+    fn emit_enum_struct_variant<F>(&mut self,
+                                   v_name: &str,
+                                   _v_id: usize,
+                                   len: usize,
+                                   f: F)
+                                   -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_variant(v_name, len, f)
+    }
+
+    fn emit_enum_struct_variant_field<F>(&mut self,
+                                         _f_name: &str,
+                                         _f_idx: usize,
+                                         f: F)
+                                         -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_element(f)
+    }
+
+    fn emit_struct<F>(&mut self, _name: &str, _len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_document(f)
+    }
+
+    fn emit_struct_field<F>(&mut self, f_name: &str, _f_idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_field(f_name, f)
+    }
+
+    fn emit_tuple<F>(&mut self, _len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_array(f)
+    }
+
+    fn emit_tuple_arg<F>(&mut self, _idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_element(f)
+    }
+
+    fn emit_tuple_struct<F>(&mut self, _name: &str, _len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_array(f)
+    }
+
+    fn emit_tuple_struct_arg<F>(&mut self, _f_idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_element(f)
+    }
+
+    fn emit_option<F>(&mut self, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        f(self)
+    }
+
+    fn emit_option_none(&mut self) -> EncoderResult<()> {
+        self.emit(Bson::Null)
+    }
+
+    fn emit_option_some<F>(&mut self, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        f(self)
+    }
+
+    fn emit_seq<F>(&mut self, _len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_array(f)
+    }
+
+    fn emit_seq_elt<F>(&mut self, _idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_element(f)
+    }
+
+    fn emit_map<F>(&mut self, _len: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        self.emit_document(f)
+    }
+
+    fn emit_map_elt_key<F>(&mut self, _idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        try!(f(self));
+
+        // Like JSON, numeric keys are converted to strings.
+        let key = match try!(self.take()) {
+            Bson::String(key) => key,
+            Bson::I32(key) => key.to_string(),
+            Bson::I64(key) => key.to_string(),
+            key => return Err(EncoderError::InvalidMapKeyType(key)),
+        };
+
+        match self.containers.last_mut() {
+            Some(&mut Container::Document(_, ref mut pending)) => {
+                *pending = Some(key);
+                Ok(())
+            }
+            _ => Err(EncoderError::Unknown(String::from("Map key is outside of a document."))),
+        }
+    }
+
+    fn emit_map_elt_val<F>(&mut self, _idx: usize, f: F) -> EncoderResult<()>
+        where F: FnOnce(&mut Encoder) -> EncoderResult<()>
+    {
+        try!(f(self));
+        let value = try!(self.take());
+
+        match self.containers.last_mut() {
+            Some(&mut Container::Document(ref mut doc, ref mut pending)) => {
+                match pending.take() {
+                    Some(key) => {
+                        doc.insert(key, value);
+                        Ok(())
+                    }
+                    None => Err(EncoderError::Unknown(String::from("Map value has no key."))),
+                }
+            }
+            _ => Err(EncoderError::Unknown(String::from("Map value is outside of a document."))),
+        }
+    }
+}
+
+/// Decodes `Decodable` values from BSON.
+pub struct Decoder {
+    // Values that remain to be decoded, next last.
+    stack: Vec<Bson>,
+}
+
+impl Decoder {
+    /// Returns a decoder for a single value.
+    pub fn new(value: Bson) -> Decoder {
+        Decoder { stack: vec![value] }
+    }
+
+    fn pop(&mut self) -> DecoderResult<Bson> {
+        self.stack.pop().ok_or(DecoderError::EndOfStream)
+    }
+
+    fn read_integer(&mut self, min: i64, max: i64) -> DecoderResult<i64> {
+        let value = match try!(self.pop()) {
+            Bson::I32(v) => v as i64,
+            Bson::I64(v) => v,
+            Bson::FloatingPoint(v) if v.fract() == 0.0 && v.abs() < i64::MAX as f64 => v as i64,
+            other => return Err(expected("an integer", &other)),
+        };
+
+        if value < min || value > max {
+            return Err(DecoderError::SyntaxError(format!("{} is out of range.", value)));
+        }
+        Ok(value)
+    }
+
+    fn read_variant<T, F>(&mut self, names: &[&str], mut f: F) -> DecoderResult<T>
+        where F: FnMut(&mut Decoder, usize) -> DecoderResult<T>
+    {
+        let name = match try!(self.pop()) {
+            Bson::String(name) => name,
+            Bson::Document(mut doc) => {
+                let name = match doc.remove("variant") {
+                    Some(Bson::String(name)) => name,
+                    _ => return Err(DecoderError::ExpectedField("variant")),
+                };
+
+                match doc.remove("fields") {
+                    Some(Bson::Array(fields)) => self.stack.extend(fields.into_iter().rev()),
+                    _ => return Err(DecoderError::ExpectedField("fields")),
+                }
+                name
+            }
+            other => return Err(expected("an enum variant", &other)),
+        };
+
+        match names.iter().position(|n| *n == name) {
+            Some(idx) => f(self, idx),
+            None => Err(DecoderError::UnknownField(name)),
+        }
+    }
+}
+
+impl rustc_serialize::Decoder for Decoder {
+    type Error = DecoderError;
+
+    fn read_nil(&mut self) -> DecoderResult<()> {
+        match try!(self.pop()) {
+            Bson::Null => Ok(()),
+            other => Err(expected("null", &other)),
+        }
+    }
+
+    fn read_usize(&mut self) -> DecoderResult<usize> {
+        self.read_integer(0, i64::MAX).map(|v| v as usize)
+    }
+
+    fn read_u64(&mut self) -> DecoderResult<u64> {
+        self.read_integer(0, i64::MAX).map(|v| v as u64)
+    }
+
+    fn read_u32(&mut self) -> DecoderResult<u32> {
+        self.read_integer(0, u32::max_value() as i64).map(|v| v as u32)
+    }
+
+    fn read_u16(&mut self) -> DecoderResult<u16> {
+        self.read_integer(0, u16::max_value() as i64).map(|v| v as u16)
+    }
+
+    fn read_u8(&mut self) -> DecoderResult<u8> {
+        self.read_integer(0, u8::max_value() as i64).map(|v| v as u8)
+    }
+
+    fn read_isize(&mut self) -> DecoderResult<isize> {
+        self.read_integer(isize::min_value() as i64, isize::max_value() as i64)
+            .map(|v| v as isize)
+    }
+
+    fn read_i64(&mut self) -> DecoderResult<i64> {
+        self.read_integer(i64::MIN, i64::MAX)
+    }
+
+    fn read_i32(&mut self) -> DecoderResult<i32> {
+        self.read_integer(i32::MIN as i64, i32::MAX as i64).map(|v| v as i32)
+    }
+
+    fn read_i16(&mut self) -> DecoderResult<i16> {
+        self.read_integer(i16::min_value() as i64, i16::max_value() as i64).map(|v| v as i16)
+    }
+
+    fn read_i8(&mut self) -> DecoderResult<i8> {
+        self.read_integer(i8::min_value() as i64, i8::max_value() as i64).map(|v| v as i8)
+    }
+
+    fn read_bool(&mut self) -> DecoderResult<bool> {
+        match try!(self.pop()) {
+            Bson::Boolean(v) => Ok(v),
+            other => Err(expected("a boolean", &other)),
+        }
+    }
+
+    fn read_f64(&mut self) -> DecoderResult<f64> {
+        match try!(self.pop()) {
+            Bson::FloatingPoint(v) => Ok(v),
+            Bson::I32(v) => Ok(v as f64),
+            Bson::I64(v) => Ok(v as f64),
+            other => Err(expected("a number", &other)),
+        }
+    }
+
+    fn read_f32(&mut self) -> DecoderResult<f32> {
+        self.read_f64().map(|v| v as f32)
+    }
+
+    fn read_char(&mut self) -> DecoderResult<char> {
+        let s = try!(self.read_str());
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(expected("a single character", &Bson::String(s.clone()))),
+        }
+    }
+
+    fn read_str(&mut self) -> DecoderResult<String> {
+        match try!(self.pop()) {
+            Bson::String(v) |
+            Bson::Symbol(v) => Ok(v),
+            other => Err(expected("a string", &other)),
+        }
+    }
+
+    fn read_enum<T, F>(&mut self, _name: &str, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_enum_variant<T, F>(&mut self, names: &[&str], f: F) -> DecoderResult<T>
+        where F: FnMut(&mut Decoder, usize) -> DecoderResult<T>
+    {
+        self.read_variant(names, f)
+    }
+
+    fn read_enum_variant_arg<T, F>(&mut self, _a_idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_enum_struct_variant<T, F>(&mut self, names: &[&str], f: F) -> DecoderResult<T>
+        where F: FnMut(&mut Decoder, usize) -> DecoderResult<T>
+    {
+        self.read_variant(names, f)
+    }
+
+    fn read_enum_struct_variant_field<T, F>(&mut self,
+                                            _f_name: &str,
+                                            _f_idx: usize,
+                                            f: F)
+                                            -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_struct<T, F>(&mut self, _s_name: &str, _len: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        // Values of other BSON types are read as their extended JSON documents.
+        let doc = match try!(self.pop()) {
+            Bson::Document(doc) => doc,
+            value @ Bson::ObjectId(_) |
+            value @ Bson::UtcDatetime(_) |
+            value @ Bson::TimeStamp(_) |
+            value @ Bson::Binary(..) |
+            value @ Bson::RegExp(..) |
+            value @ Bson::JavaScriptCode(_) |
+            value @ Bson::JavaScriptCodeWithScope(..) |
+            value @ Bson::Symbol(_) => value.to_extended_document(),
+            other => return Err(expected("a document", &other)),
+        };
+
+        self.stack.push(Bson::Document(doc));
+        let value = try!(f(self));
+        try!(self.pop());
+        Ok(value)
+    }
+
+    fn read_struct_field<T, F>(&mut self, f_name: &str, _f_idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        let mut doc = match try!(self.pop()) {
+            Bson::Document(doc) => doc,
+            other => return Err(expected("a document", &other)),
+        };
+
+        // Missing fields are read as null, so that they decode as `None`.
+        let value = match doc.remove(f_name) {
+            Some(value) => {
+                self.stack.push(value);
+                try!(f(self))
+            }
+            None => {
+                self.stack.push(Bson::Null);
+                try!(f(self).map_err(|_| {
+                    DecoderError::SyntaxError(format!("missing field `{}`", f_name))
+                }))
+            }
+        };
+
+        self.stack.push(Bson::Document(doc));
+        Ok(value)
+    }
+
+    fn read_tuple<T, F>(&mut self, len: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        self.read_seq(|d, actual| {
+            if actual == len {
+                f(d)
+            } else {
+                Err(DecoderError::SyntaxError(format!("expected a tuple of length {}, found {}",
+                                                      len,
+                                                      actual)))
+            }
+        })
+    }
+
+    fn read_tuple_arg<T, F>(&mut self, _a_idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_tuple_struct<T, F>(&mut self, _s_name: &str, len: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        self.read_tuple(len, f)
+    }
+
+    fn read_tuple_struct_arg<T, F>(&mut self, _a_idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_option<T, F>(&mut self, mut f: F) -> DecoderResult<T>
+        where F: FnMut(&mut Decoder, bool) -> DecoderResult<T>
+    {
+        match try!(self.pop()) {
+            Bson::Null => f(self, false),
+            value => {
+                self.stack.push(value);
+                f(self, true)
+            }
+        }
+    }
+
+    fn read_seq<T, F>(&mut self, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder, usize) -> DecoderResult<T>
+    {
+        let values = match try!(self.pop()) {
+            Bson::Array(values) => values,
+            other => return Err(expected("an array", &other)),
+        };
+
+        let len = values.len();
+        self.stack.extend(values.into_iter().rev());
+        f(self, len)
+    }
+
+    fn read_seq_elt<T, F>(&mut self, _idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_map<T, F>(&mut self, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder, usize) -> DecoderResult<T>
+    {
+        let doc = match try!(self.pop()) {
+            Bson::Document(doc) => doc,
+            other => return Err(expected("a document", &other)),
+        };
+
+        let entries: Vec<_> = doc.into_iter().collect();
+        let len = entries.len();
+
+        for (key, value) in entries.into_iter().rev() {
+            self.stack.push(value);
+            self.stack.push(Bson::String(key));
+        }
+        f(self, len)
+    }
+
+    fn read_map_elt_key<T, F>(&mut self, _idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn read_map_elt_val<T, F>(&mut self, _idx: usize, f: F) -> DecoderResult<T>
+        where F: FnOnce(&mut Decoder) -> DecoderResult<T>
+    {
+        f(self)
+    }
+
+    fn error(&mut self, err: &str) -> DecoderError {
+        DecoderError::Unknown(String::from(err))
+    }
+}
+
+fn expected(expected: &str, found: &Bson) -> DecoderError {
+    DecoderError::SyntaxError(format!("expected {}, found {}", expected, found))
+}
@@ -1,13 +1,24 @@
 //! Models for collection-level batch operations.
-use super::options::WriteModel;
+use super::options::{Collation, WriteModel};
 
-use bson::Document;
+use Result;
+use Error::ArgumentError;
+
+use bson::{self, Document};
+use topology::server::ServerDescription;
+
+use std::cmp;
 use std::convert::From;
 
+// The amount by which a command document may exceed the maximum document size, leaving room
+// for the fields that surround its batch of statements.
+const COMMAND_OVERHEAD_BYTES: i64 = 16 * 1024;
+
 #[derive(Debug)]
 pub struct DeleteModel {
     pub filter: Document,
     pub multi: bool,
+    pub collation: Option<Collation>,
 }
 
 #[derive(Debug)]
@@ -17,25 +28,33 @@ pub struct UpdateModel {
     pub upsert: bool,
     pub multi: bool,
     pub is_replace: bool,
+    pub collation: Option<Collation>,
 }
 
 impl UpdateModel {
-    pub fn new(filter: Document, update: Document, upsert: bool, multi: bool) -> UpdateModel {
+    pub fn new(filter: Document,
+               update: Document,
+               upsert: bool,
+               multi: bool,
+               collation: Option<Collation>)
+               -> UpdateModel {
         UpdateModel {
             filter: filter,
             update: update,
             upsert: upsert,
             multi: multi,
             is_replace: false,
+            collation: collation,
         }
     }
 }
 
 impl DeleteModel {
-    pub fn new(filter: Document, multi: bool) -> DeleteModel {
+    pub fn new(filter: Document, multi: bool, collation: Option<Collation>) -> DeleteModel {
         DeleteModel {
             filter: filter,
             multi: multi,
+            collation: collation,
         }
     }
 }
@@ -51,43 +70,48 @@ impl From<WriteModel> for Batch {
     fn from(model: WriteModel) -> Batch {
         match model {
             WriteModel::InsertOne { document } => Batch::Insert(vec![document]),
-            WriteModel::DeleteOne { filter } => {
+            WriteModel::DeleteOne { filter, collation } => {
                 Batch::Delete(vec![DeleteModel {
                                        filter: filter,
                                        multi: false,
+                                       collation: collation,
                                    }])
             }
-            WriteModel::DeleteMany { filter } => {
+            WriteModel::DeleteMany { filter, collation } => {
                 Batch::Delete(vec![DeleteModel {
                                        filter: filter,
                                        multi: true,
+                                       collation: collation,
                                    }])
             }
-            WriteModel::ReplaceOne { filter, replacement: update, upsert } => {
+            WriteModel::ReplaceOne { filter, replacement: update, upsert, collation } => {
                 Batch::Update(vec![UpdateModel {
                                        filter: filter,
                                        update: update,
                                        upsert: upsert,
                                        multi: false,
                                        is_replace: true,
+                                       collation: collation,
                                    }])
             }
-            WriteModel::UpdateOne { filter, update, upsert } => {
+            WriteModel::UpdateOne { filter, update, upsert, collation } => {
                 Batch::Update(vec![UpdateModel {
                                        filter: filter,
                                        update: update,
                                        upsert: upsert,
                                        multi: false,
                                        is_replace: false,
+                                       collation: collation,
                                    }])
             }
-            WriteModel::UpdateMany { filter, update, upsert } => {
+            WriteModel::UpdateMany { filter, update, upsert, collation } => {
                 Batch::Update(vec![UpdateModel {
                                        filter: filter,
                                        update: update,
                                        upsert: upsert,
                                        multi: true,
                                        is_replace: false,
+                                       collation: collation,
                                    }])
             }
         }
@@ -125,16 +149,18 @@ impl Batch {
             }
             Batch::Delete(ref mut models) => {
                 match model {
-                    WriteModel::DeleteOne { filter } => {
+                    WriteModel::DeleteOne { filter, collation } => {
                         models.push(DeleteModel {
                             filter: filter,
                             multi: false,
+                            collation: collation,
                         })
                     }
-                    WriteModel::DeleteMany { filter } => {
+                    WriteModel::DeleteMany { filter, collation } => {
                         models.push(DeleteModel {
                             filter: filter,
                             multi: true,
+                            collation: collation,
                         })
                     }
                     _ => return Some(model),
@@ -142,31 +168,34 @@ impl Batch {
             }
             Batch::Update(ref mut models) => {
                 match model {
-                    WriteModel::ReplaceOne { filter, replacement: update, upsert } => {
+                    WriteModel::ReplaceOne { filter, replacement: update, upsert, collation } => {
                         models.push(UpdateModel {
                             filter: filter,
                             update: update,
                             upsert: upsert,
                             multi: false,
                             is_replace: true,
+                            collation: collation,
                         })
                     }
-                    WriteModel::UpdateOne { filter, update, upsert } => {
+                    WriteModel::UpdateOne { filter, update, upsert, collation } => {
                         models.push(UpdateModel {
                             filter: filter,
                             update: update,
                             upsert: upsert,
                             multi: false,
                             is_replace: false,
+                            collation: collation,
                         })
                     }
-                    WriteModel::UpdateMany { filter, update, upsert } => {
+                    WriteModel::UpdateMany { filter, update, upsert, collation } => {
                         models.push(UpdateModel {
                             filter: filter,
                             update: update,
                             upsert: upsert,
                             multi: true,
                             is_replace: false,
+                            collation: collation,
                         })
                     }
                     _ => return Some(model),
@@ -177,3 +206,43 @@ impl Batch {
         None
     }
 }
+
+/// Returns the end of the batch of statements that starts at `start` and fits within the
+/// server's limits on the number of statements and the size of a command. The command
+/// size excludes the statements themselves.
+pub fn batch_end(statements: &[Document],
+                 start: usize,
+                 command_size: i64,
+                 description: &ServerDescription)
+                 -> Result<usize> {
+    let max_command_size = cmp::min(description.max_bson_object_size + COMMAND_OVERHEAD_BYTES,
+                                    description.max_message_size_bytes);
+    let max_count = cmp::max(description.max_write_batch_size, 1) as usize;
+
+    let mut size = command_size;
+
+    for (i, statement) in statements.iter().enumerate().skip(start) {
+        let mut buffer = Vec::new();
+        try!(bson::encode_document(&mut buffer, statement));
+        let statement_size = buffer.len() as i64;
+
+        if statement_size > description.max_bson_object_size {
+            return Err(ArgumentError(format!("The write at index {} is {} bytes, which exceeds \
+                                              the server's maximum document size of {} bytes.",
+                                             i,
+                                             statement_size,
+                                             description.max_bson_object_size)));
+        }
+
+        // Array elements are prefixed by a type byte and their null-terminated index.
+        let element_size = statement_size + (i - start).to_string().len() as i64 + 2;
+
+        if i > start && (i - start == max_count || size + element_size > max_command_size) {
+            return Ok(i);
+        }
+
+        size += element_size;
+    }
+
+    Ok(statements.len())
+}
@@ -0,0 +1,162 @@
+//! Fluent builders for bulk write operations.
+use bson::Document;
+
+use Result;
+use Error::ArgumentError;
+
+use common::WriteConcern;
+
+use super::Collection;
+use super::options::{Collation, WriteModel};
+use super::results::BulkWriteResult;
+
+/// A bulk write operation built up one request at a time.
+///
+/// Ordered operations execute their requests in the order they were added and stop at the
+/// first error, while unordered operations attempt every request in any order.
+pub struct BulkOperation<'a> {
+    coll: &'a Collection,
+    ordered: bool,
+    requests: Vec<WriteModel>,
+}
+
+/// Requests within a bulk operation that apply to the documents matching a filter.
+pub struct BulkFindOperation<'a: 'b, 'b> {
+    bulk: &'b mut BulkOperation<'a>,
+    filter: Document,
+    upsert: bool,
+    collation: Option<Collation>,
+}
+
+impl<'a> BulkOperation<'a> {
+    /// Returns an empty bulk operation on the collection.
+    pub fn new(coll: &'a Collection, ordered: bool) -> BulkOperation<'a> {
+        BulkOperation {
+            coll: coll,
+            ordered: ordered,
+            requests: Vec::new(),
+        }
+    }
+
+    /// Returns true if requests are executed in order.
+    pub fn is_ordered(&self) -> bool {
+        self.ordered
+    }
+
+    /// Returns the number of requests added so far.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if no requests have been added.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Adds a request to insert a document.
+    pub fn insert(&mut self, document: Document) {
+        self.requests.push(WriteModel::InsertOne { document: document });
+    }
+
+    /// Begins a request on the documents matching the filter.
+    pub fn find<'b>(&'b mut self, filter: Document) -> BulkFindOperation<'a, 'b> {
+        BulkFindOperation {
+            bulk: self,
+            filter: filter,
+            upsert: false,
+            collation: None,
+        }
+    }
+
+    /// Executes the requests with the given write concern, or the collection's if none is
+    /// provided. Write errors are reported through the result's bulk write exception, while
+    /// the error of the first batch that could not be sent is returned.
+    pub fn execute(self, write_concern: Option<WriteConcern>) -> Result<BulkWriteResult> {
+        if self.requests.is_empty() {
+            return Err(ArgumentError(String::from("A bulk operation requires at least one \
+                                                   request.")));
+        }
+
+        if let Some(ref write_concern) = write_concern {
+            try!(write_concern.validate());
+        }
+
+        for request in &self.requests {
+            match *request {
+                WriteModel::ReplaceOne { ref replacement, .. } => {
+                    try!(Collection::validate_replace(replacement))
+                }
+                WriteModel::UpdateOne { ref update, .. } |
+                WriteModel::UpdateMany { ref update, .. } => {
+                    try!(Collection::validate_update(update))
+                }
+                _ => (),
+            }
+        }
+
+        match self.coll.bulk_write_with_write_concern(self.requests, self.ordered, write_concern) {
+            (result, None) => Ok(result),
+            (_, Some(err)) => Err(err),
+        }
+    }
+}
+
+impl<'a, 'b> BulkFindOperation<'a, 'b> {
+    /// Inserts a document if an update or replacement matches none.
+    pub fn upsert(mut self) -> BulkFindOperation<'a, 'b> {
+        self.upsert = true;
+        self
+    }
+
+    /// Sets the collation used to match documents.
+    pub fn collation(mut self, collation: Collation) -> BulkFindOperation<'a, 'b> {
+        self.collation = Some(collation);
+        self
+    }
+
+    /// Adds a request to update a single matching document.
+    pub fn update_one(self, update: Document) {
+        self.bulk.requests.push(WriteModel::UpdateOne {
+            filter: self.filter,
+            update: update,
+            upsert: self.upsert,
+            collation: self.collation,
+        });
+    }
+
+    /// Adds a request to update every matching document.
+    pub fn update_many(self, update: Document) {
+        self.bulk.requests.push(WriteModel::UpdateMany {
+            filter: self.filter,
+            update: update,
+            upsert: self.upsert,
+            collation: self.collation,
+        });
+    }
+
+    /// Adds a request to replace a single matching document.
+    pub fn replace_one(self, replacement: Document) {
+        self.bulk.requests.push(WriteModel::ReplaceOne {
+            filter: self.filter,
+            replacement: replacement,
+            upsert: self.upsert,
+            collation: self.collation,
+        });
+    }
+
+    /// Adds a request to delete a single matching document.
+    pub fn delete_one(self) {
+        self.bulk.requests.push(WriteModel::DeleteOne {
+            filter: self.filter,
+            collation: self.collation,
+        });
+    }
+
+    /// Adds a request to delete every matching document.
+    pub fn delete_many(self) {
+        self.bulk.requests.push(WriteModel::DeleteMany {
+            filter: self.filter,
+            collation: self.collation,
+        });
+    }
+}
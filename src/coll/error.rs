@@ -322,6 +322,11 @@ impl BulkWriteException {
                                       write_concern: WriteConcern)
                                       -> Result<()> {
 
+        // Unacknowledged writes do not report errors.
+        if !write_concern.is_acknowledged() {
+            return Ok(());
+        }
+
         // Parse out any write concern errors.
         let wc_err = if let Some(&Bson::Document(ref error)) = result.get("writeConcernError") {
             Some(try!(WriteConcernError::parse(error.clone(), write_concern)))
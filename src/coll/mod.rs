@@ -1,28 +1,63 @@
 //! Interface for collection-level operations.
 mod batch;
+pub mod bulk;
 pub mod error;
 pub mod options;
+pub mod pipeline;
 pub mod results;
+mod sync;
+pub mod typed;
 
 use bson::{self, Bson, oid};
 use command_type::CommandType;
 
 use self::batch::{Batch, DeleteModel, UpdateModel};
+use self::bulk::BulkOperation;
 use self::error::{BulkWriteException, WriteException};
 use self::options::*;
+use self::pipeline::{Accumulator, Pipeline};
 use self::results::*;
+use self::typed::TypedCollection;
 
-use ThreadedClient;
-use common::{ReadPreference, WriteConcern};
-use cursor::Cursor;
+use {Client, ErrorCode, ThreadedClient};
+use apm::{CommandStarted, CommandResult, EventRunner};
+use codec;
+use common::{ReadConcern, ReadMode, ReadPreference, WriteConcern};
+use cursor::{Cursor, TypedCursor};
 use db::{Database, ThreadedDatabase};
+use pool::PooledStream;
+use session::DEFAULT_SESSION_TIMEOUT_MINUTES;
 
 use Result;
-use Error::{ArgumentError, ResponseError, OperationError, BulkWriteError};
+use Error::{self, ArgumentError, ResponseError, OperationError, BulkWriteError,
+            EventListenerError, IoError};
 
-use wire_protocol::flags::OpQueryFlags;
+use rustc_serialize::{Decodable, Encodable};
+use wire_protocol::flags::{OpDeleteFlags, OpInsertFlags, OpMsgFlags, OpQueryFlags, OpUpdateFlags};
+use wire_protocol::operations::Message;
 use std::collections::{BTreeMap, VecDeque};
 use std::iter::FromIterator;
+use std::ops::Range;
+
+use time;
+
+// Wire versions of the first servers to support explaining each kind of operation
+// with the explain command.
+const EXPLAIN_COMMAND_WIRE_VERSION: i64 = 3;
+const EXPLAIN_FIND_WIRE_VERSION: i64 = 4;
+const EXPLAIN_AGGREGATE_WIRE_VERSION: i64 = 5;
+
+// Wire version of the first servers to accept a write concern for aggregations with
+// `$out` and map-reduce operations with an output collection, and of the first to require
+// aggregations to return a cursor.
+const COMMAND_WRITE_CONCERN_WIRE_VERSION: i64 = 5;
+const AGGREGATE_CURSOR_REQUIRED_WIRE_VERSION: i64 = 6;
+
+// Wire version of the first servers to accept a commit quorum for index builds.
+const COMMIT_QUORUM_WIRE_VERSION: i64 = 9;
+
+// Wire version of the first servers to accept OP_MSG.
+const OP_MSG_WIRE_VERSION: i64 = 6;
 
 /// Interfaces with a MongoDB collection.
 pub struct Collection {
@@ -32,6 +67,7 @@ pub struct Collection {
     pub namespace: String,
     read_preference: ReadPreference,
     write_concern: WriteConcern,
+    read_concern: ReadConcern,
 }
 
 impl Collection {
@@ -58,9 +94,21 @@ impl Collection {
             namespace: format!("{}.{}", db.name, name),
             read_preference: rp,
             write_concern: wc,
+            read_concern: db.read_concern.to_owned(),
         }
     }
 
+    /// Replaces the read concern inherited from the database, returning the collection.
+    pub fn with_read_concern(mut self, read_concern: ReadConcern) -> Collection {
+        self.read_concern = read_concern;
+        self
+    }
+
+    /// Returns the read concern used by read operations that do not specify one.
+    pub fn read_concern(&self) -> &ReadConcern {
+        &self.read_concern
+    }
+
     /// Returns a unique operational request id.
     pub fn get_req_id(&self) -> i32 {
         self.db.client.get_req_id()
@@ -91,29 +139,179 @@ impl Collection {
         self.db.drop_collection(&self.name()[..])
     }
 
+    /// Renames the collection, replacing any existing collection with the new name if
+    /// `drop_target` is true.
+    pub fn rename(&self, new_name: &str, drop_target: bool) -> Result<()> {
+        let mut spec = bson::Document::new();
+        spec.insert("renameCollection", Bson::String(self.namespace.to_owned()));
+        spec.insert("to", Bson::String(format!("{}.{}", self.db.name, new_name)));
+        spec.insert("dropTarget", Bson::Boolean(drop_target));
+
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        let (selected, wire_version) = try!(self.select_server(&CommandType::RenameCollection,
+                                                               &read_pref));
+        if wire_version >= COMMAND_WRITE_CONCERN_WIRE_VERSION {
+            spec.insert("writeConcern", Bson::Document(self.write_concern.to_bson()));
+        }
+
+        let admin = self.db.client.db("admin");
+        try!(admin.command_selected(selected, spec, CommandType::RenameCollection, read_pref));
+        Ok(())
+    }
+
+    /// Returns storage statistics for the collection.
+    pub fn stats(&self) -> Result<CollectionStats> {
+        let mut spec = bson::Document::new();
+        spec.insert("collStats", Bson::String(self.name()));
+
+        let result = try!(self.db.command(spec,
+                                          CommandType::CollStats,
+                                          Some(self.read_preference.to_owned())));
+        Ok(CollectionStats::new(result))
+    }
+
+    /// Checks the collection's data and indexes for correctness. A full validation is
+    /// more thorough but slower.
+    pub fn validate(&self, full: bool) -> Result<ValidateResult> {
+        let mut spec = bson::Document::new();
+        spec.insert("validate", Bson::String(self.name()));
+        spec.insert("full", Bson::Boolean(full));
+
+        let result = try!(self.db.command(spec, CommandType::Validate, None));
+        Ok(ValidateResult::new(result))
+    }
+
+    /// Changes the collection's options, such as its validator, the expiry of a TTL
+    /// index, or the size of a capped collection.
+    pub fn modify(&self, options: CollModOptions) -> Result<()> {
+        if let Some(ref write_concern) = options.write_concern {
+            try!(write_concern.validate());
+        }
+
+        let spec = try!(options.to_command(&self.name()));
+        try!(self.db.command(spec, CommandType::CollMod, None));
+        Ok(())
+    }
+
     /// Runs an aggregation framework pipeline.
+    ///
+    /// Pipelines that end in a `$out` or `$merge` stage are sent to a writable server with
+    /// the write concern, and return an empty cursor.
     pub fn aggregate(&self,
                      pipeline: Vec<bson::Document>,
                      options: Option<AggregateOptions>)
                      -> Result<Cursor> {
         let opts = options.unwrap_or_else(AggregateOptions::new);
+        let writes = Collection::pipeline_writes(&pipeline);
+
+        let (read_pref, cmd_type) = if writes {
+            (ReadPreference::new(ReadMode::Primary, None), CommandType::AggregateWrite)
+        } else {
+            let read_pref = opts.read_preference.clone()
+                .unwrap_or(self.read_preference.to_owned());
+            (read_pref, CommandType::Aggregate)
+        };
+
+        let (selected, wire_version) = try!(self.select_server(&cmd_type, &read_pref));
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+        let read_concern = try!(self.read_concern_for(opts.read_concern.clone(), wire_version));
+
+        let mut spec = self.aggregate_command(pipeline, &opts);
+        if let Some(read_concern) = read_concern {
+            spec.insert("readConcern", Bson::Document(read_concern));
+        }
+
+        if writes && wire_version >= COMMAND_WRITE_CONCERN_WIRE_VERSION {
+            let wc = opts.write_concern.clone().unwrap_or(self.write_concern.clone());
+            try!(wc.validate());
+            spec.insert("writeConcern", Bson::Document(wc.to_bson()));
+        }
+
+        // Servers before 3.6 can return the results inline instead of through a cursor.
+        if !opts.use_cursor && wire_version < AGGREGATE_CURSOR_REQUIRED_WIRE_VERSION {
+            spec.remove("cursor");
+            let result = try!(self.db.command_selected(selected,
+                                                       spec,
+                                                       cmd_type.clone(),
+                                                       read_pref.clone()));
+            let docs = match result.get("result") {
+                Some(&Bson::Array(ref docs)) => {
+                    docs.iter()
+                        .filter_map(|bdoc| match *bdoc {
+                            Bson::Document(ref doc) => Some(doc.clone()),
+                            _ => None,
+                        })
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+
+            return Ok(Cursor::with_documents(self.db.client.clone(),
+                                             self.namespace.to_owned(),
+                                             docs,
+                                             cmd_type,
+                                             read_pref));
+        }
 
+        self.db.command_cursor_selected(selected, spec, cmd_type, read_pref)
+    }
+
+    // Returns true if the pipeline ends in a stage that writes its results to a collection.
+    fn pipeline_writes(pipeline: &[bson::Document]) -> bool {
+        match pipeline.last() {
+            Some(stage) => stage.contains_key("$out") || stage.contains_key("$merge"),
+            None => false,
+        }
+    }
+
+    // Builds an aggregate command for the pipeline.
+    fn aggregate_command(&self,
+                         pipeline: Vec<bson::Document>,
+                         opts: &AggregateOptions)
+                         -> bson::Document {
+        let writes = Collection::pipeline_writes(&pipeline);
         let pipeline_map = pipeline.iter()
             .map(|bdoc| Bson::Document(bdoc.to_owned()))
             .collect();
 
         let mut spec = bson::Document::new();
         let mut cursor = bson::Document::new();
-        cursor.insert("batchSize", Bson::I32(opts.batch_size));
+
+        // Writing pipelines return no documents, so the batch size is left to the server.
+        if !writes {
+            cursor.insert("batchSize", Bson::I32(opts.batch_size));
+        }
+
         spec.insert("aggregate", Bson::String(self.name()));
         spec.insert("pipeline", Bson::Array(pipeline_map));
         spec.insert("cursor", Bson::Document(cursor));
         if opts.allow_disk_use {
             spec.insert("allowDiskUse", Bson::Boolean(opts.allow_disk_use));
         }
+        if let Some(bypass) = opts.bypass_document_validation {
+            if writes {
+                spec.insert("bypassDocumentValidation", Bson::Boolean(bypass));
+            }
+        }
+        if let Some(max_time_ms) = opts.max_time_ms {
+            spec.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(ref comment) = opts.comment {
+            spec.insert("comment", Bson::String(comment.to_owned()));
+        }
+        if let Some(ref hint) = opts.hint {
+            spec.insert("hint", Bson::String(hint.to_owned()));
+        } else if let Some(ref hint_doc) = opts.hint_doc {
+            spec.insert("hint", Bson::Document(hint_doc.to_owned()));
+        }
+        if let Some(ref let_vars) = opts.let_vars {
+            spec.insert("let", Bson::Document(let_vars.to_owned()));
+        }
+        if let Some(ref collation) = opts.collation {
+            spec.insert("collation", Bson::Document(collation.to_bson()));
+        }
 
-        let read_pref = opts.read_preference.unwrap_or(self.read_preference.to_owned());
-        self.db.command_cursor(spec, CommandType::Aggregate, read_pref)
+        spec
     }
 
     /// Gets the number of documents matching the filter.
@@ -122,7 +320,25 @@ impl Collection {
                  options: Option<CountOptions>)
                  -> Result<i64> {
         let opts = options.unwrap_or_else(CountOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Count, &read_pref));
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+        let read_concern = try!(self.read_concern_for(opts.read_concern.clone(), wire_version));
+        let mut spec = self.count_command(filter, opts);
+        if let Some(read_concern) = read_concern {
+            spec.insert("readConcern", Bson::Document(read_concern));
+        }
 
+        let result = try!(self.db.command_selected(selected, spec, CommandType::Count, read_pref));
+        match result.get("n") {
+            Some(&Bson::I32(ref n)) => Ok(*n as i64),
+            Some(&Bson::I64(ref n)) => Ok(*n),
+            _ => Err(ResponseError(String::from("No count received from server."))),
+        }
+    }
+
+    // Builds a count command for the filter.
+    fn count_command(&self, filter: Option<bson::Document>, opts: CountOptions) -> bson::Document {
         let mut spec = bson::Document::new();
         spec.insert("count", Bson::String(self.name()));
         spec.insert("skip", Bson::I64(opts.skip as i64));
@@ -138,8 +354,78 @@ impl Collection {
             spec.insert("hint", Bson::String(opts.hint.unwrap()));
         }
 
-        let read_pref = opts.read_preference.unwrap_or(self.read_preference.to_owned());
-        let result = try!(self.db.command(spec, CommandType::Count, Some(read_pref)));
+        if let Some(collation) = opts.collation {
+            spec.insert("collation", Bson::Document(collation.to_bson()));
+        }
+
+        spec
+    }
+
+    /// Gets the number of documents matching the filter by running an aggregation, which
+    /// unlike `count` is accurate on sharded clusters.
+    pub fn count_documents(&self,
+                           filter: Option<bson::Document>,
+                           options: Option<CountOptions>)
+                           -> Result<i64> {
+        let opts = options.unwrap_or_else(CountOptions::new);
+
+        let mut pipeline = Pipeline::new().match_(filter.unwrap_or_else(bson::Document::new));
+        if opts.skip > 0 {
+            pipeline = pipeline.skip(opts.skip as i64);
+        }
+        if opts.limit > 0 {
+            pipeline = pipeline.limit(opts.limit);
+        }
+        let n = Accumulator::Sum(Bson::I32(1));
+        let pipeline = try!(pipeline.group(Bson::I32(1), vec![(String::from("n"), n)]).build());
+
+        let mut agg_opts = AggregateOptions::new();
+        agg_opts.max_time_ms = opts.max_time_ms;
+        agg_opts.read_preference = opts.read_preference;
+        agg_opts.read_concern = opts.read_concern;
+        agg_opts.collation = opts.collation;
+
+        // Favor specified hint document over string
+        if opts.hint_doc.is_some() {
+            agg_opts.hint_doc = opts.hint_doc;
+        } else {
+            agg_opts.hint = opts.hint;
+        }
+
+        let mut cursor = try!(self.aggregate(pipeline, Some(agg_opts)));
+        match cursor.next() {
+            Some(Ok(doc)) => {
+                match doc.get("n") {
+                    Some(&Bson::I32(n)) => Ok(n as i64),
+                    Some(&Bson::I64(n)) => Ok(n),
+                    _ => Err(ResponseError(String::from("No count received from server."))),
+                }
+            }
+            Some(Err(err)) => Err(err),
+            // No documents matched, so there was nothing to group.
+            None => Ok(0),
+        }
+    }
+
+    /// Gets an estimate of the number of documents in the collection from its metadata,
+    /// without scanning it. Only the time limit, read preference and read concern of the
+    /// options are used.
+    pub fn estimated_document_count(&self, options: Option<CountOptions>) -> Result<i64> {
+        let opts = options.unwrap_or_else(CountOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Count, &read_pref));
+        let read_concern = try!(self.read_concern_for(opts.read_concern.clone(), wire_version));
+
+        let mut spec = bson::Document::new();
+        spec.insert("count", Bson::String(self.name()));
+        if let Some(max_time_ms) = opts.max_time_ms {
+            spec.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(read_concern) = read_concern {
+            spec.insert("readConcern", Bson::Document(read_concern));
+        }
+
+        let result = try!(self.db.command_selected(selected, spec, CommandType::Count, read_pref));
         match result.get("n") {
             Some(&Bson::I32(ref n)) => Ok(*n as i64),
             Some(&Bson::I64(ref n)) => Ok(*n),
@@ -155,20 +441,439 @@ impl Collection {
                     -> Result<Vec<Bson>> {
 
         let opts = options.unwrap_or_else(DistinctOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Distinct,
+                                                               &read_pref));
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+        let read_concern = try!(self.read_concern_for(opts.read_concern.clone(), wire_version));
+        let mut spec = self.distinct_command(field_name, filter, &opts);
+        if let Some(read_concern) = read_concern {
+            spec.insert("readConcern", Bson::Document(read_concern));
+        }
+
+        let result = try!(self.db.command_selected(selected,
+                                                   spec,
+                                                   CommandType::Distinct,
+                                                   read_pref));
+        match result.get("values") {
+            Some(&Bson::Array(ref vals)) => Ok(vals.to_owned()),
+            _ => Err(ResponseError(String::from("No values received from server."))),
+        }
+    }
 
+    // Builds a distinct command for the field and filter.
+    fn distinct_command(&self,
+                        field_name: &str,
+                        filter: Option<bson::Document>,
+                        opts: &DistinctOptions)
+                        -> bson::Document {
         let mut spec = bson::Document::new();
         spec.insert("distinct", Bson::String(self.name()));
         spec.insert("key", Bson::String(String::from(field_name)));
         if filter.is_some() {
             spec.insert("query", Bson::Document(filter.unwrap()));
         }
+        if let Some(ref collation) = opts.collation {
+            spec.insert("collation", Bson::Document(collation.to_bson()));
+        }
 
-        let read_pref = opts.read_preference.unwrap_or(self.read_preference.to_owned());
-        let result = try!(self.db.command(spec, CommandType::Distinct, Some(read_pref)));
-        match result.get("values") {
-            Some(&Bson::Array(ref vals)) => Ok(vals.to_owned()),
-            _ => Err(ResponseError(String::from("No values received from server."))),
+        spec
+    }
+
+    /// Runs a map-reduce operation with the JavaScript `map` and `reduce` functions, given as
+    /// `Bson::JavaScriptCode` or `Bson::JavaScriptCodeWithScope`.
+    ///
+    /// Inline output is returned through a cursor; otherwise the results are written to the
+    /// output collection on the primary and only statistics are returned.
+    pub fn map_reduce(&self,
+                      map: Bson,
+                      reduce: Bson,
+                      options: Option<MapReduceOptions>)
+                      -> Result<MapReduceResult> {
+        let opts = options.unwrap_or_else(MapReduceOptions::new);
+        try!(Collection::validate_javascript("map", &map));
+        try!(Collection::validate_javascript("reduce", &reduce));
+        if let Some(ref finalize) = opts.finalize {
+            try!(Collection::validate_javascript("finalize", finalize));
+        }
+
+        let inline = opts.out == MapReduceOutput::Inline;
+        let (read_pref, cmd_type) = if inline {
+            let read_pref = opts.read_preference.clone()
+                .unwrap_or(self.read_preference.to_owned());
+            (read_pref, CommandType::MapReduce)
+        } else {
+            (ReadPreference::new(ReadMode::Primary, None), CommandType::MapReduceWrite)
+        };
+
+        let (selected, wire_version) = try!(self.select_server(&cmd_type, &read_pref));
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+
+        let mut spec = bson::Document::new();
+        spec.insert("mapReduce", Bson::String(self.name()));
+        spec.insert("map", map);
+        spec.insert("reduce", reduce);
+        spec.insert("out", Bson::Document(opts.out.to_bson()));
+        if let Some(ref query) = opts.query {
+            spec.insert("query", Bson::Document(query.to_owned()));
+        }
+        if let Some(ref sort) = opts.sort {
+            spec.insert("sort", Bson::Document(sort.to_owned()));
+        }
+        if let Some(limit) = opts.limit {
+            spec.insert("limit", Bson::I64(limit));
+        }
+        if let Some(ref finalize) = opts.finalize {
+            spec.insert("finalize", finalize.to_owned());
+        }
+        if let Some(ref scope) = opts.scope {
+            spec.insert("scope", Bson::Document(scope.to_owned()));
+        }
+        if let Some(js_mode) = opts.js_mode {
+            spec.insert("jsMode", Bson::Boolean(js_mode));
+        }
+        if let Some(verbose) = opts.verbose {
+            spec.insert("verbose", Bson::Boolean(verbose));
         }
+        if let Some(max_time_ms) = opts.max_time_ms {
+            spec.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(ref collation) = opts.collation {
+            spec.insert("collation", Bson::Document(collation.to_bson()));
+        }
+
+        if inline {
+            let read_concern = try!(self.read_concern_for(opts.read_concern.clone(),
+                                                          wire_version));
+            if let Some(read_concern) = read_concern {
+                spec.insert("readConcern", Bson::Document(read_concern));
+            }
+        } else {
+            if let Some(bypass) = opts.bypass_document_validation {
+                spec.insert("bypassDocumentValidation", Bson::Boolean(bypass));
+            }
+
+            if wire_version >= COMMAND_WRITE_CONCERN_WIRE_VERSION {
+                let wc = opts.write_concern.clone().unwrap_or(self.write_concern.clone());
+                try!(wc.validate());
+                spec.insert("writeConcern", Bson::Document(wc.to_bson()));
+            }
+        }
+
+        let mut result = try!(self.db.command_selected(selected,
+                                                       spec,
+                                                       cmd_type.clone(),
+                                                       read_pref.clone()));
+
+        if !inline {
+            return Ok(MapReduceResult::Collection(MapReduceStats::new(result)));
+        }
+
+        let docs = match result.remove("results") {
+            Some(Bson::Array(docs)) => {
+                docs.into_iter()
+                    .filter_map(|bdoc| match bdoc {
+                        Bson::Document(doc) => Some(doc),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let cursor = Cursor::with_documents(self.db.client.clone(),
+                                            self.namespace.to_owned(),
+                                            docs,
+                                            cmd_type,
+                                            read_pref);
+
+        Ok(MapReduceResult::Inline(cursor, MapReduceStats::new(result)))
+    }
+
+    // Fails unless the value holds JavaScript code.
+    fn validate_javascript(name: &str, code: &Bson) -> Result<()> {
+        match *code {
+            Bson::JavaScriptCode(_) |
+            Bson::JavaScriptCodeWithScope(..) => Ok(()),
+            _ => Err(ArgumentError(format!("The {} function must be JavaScript code.", name))),
+        }
+    }
+
+    /// Explains how the server would execute a find operation.
+    ///
+    /// Servers before 3.2 explain the query through the `$explain` modifier, which
+    /// ignores the requested verbosity.
+    pub fn explain_find(&self,
+                        filter: Option<bson::Document>,
+                        options: Option<FindOptions>,
+                        verbosity: ExplainVerbosity)
+                        -> Result<ExplainResult> {
+        let mut options = options.unwrap_or_else(FindOptions::new);
+        let read_pref = options.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        let filter = filter.unwrap_or_else(bson::Document::new);
+
+        if wire_version < EXPLAIN_FIND_WIRE_VERSION {
+            let mut query = doc! { "$query" => filter, "$explain" => true };
+            if let Some(sort) = options.sort.take() {
+                query.insert("$orderby", Bson::Document(sort));
+            }
+
+            let mut cursor = try!(self.find_selected(selected,
+                                                     wire_version,
+                                                     Some(query),
+                                                     options,
+                                                     CommandType::Find));
+            return match cursor.next() {
+                Some(Ok(doc)) => Ok(ExplainResult::new(doc)),
+                Some(Err(err)) => Err(err),
+                None => Err(ResponseError(String::from("No explain output received from server."))),
+            };
+        }
+
+        let cmd = self.find_command(filter, options);
+        self.explain_command(selected, cmd, verbosity, read_pref)
+    }
+
+    /// Explains how the server would execute an aggregation pipeline.
+    ///
+    /// Servers before 3.4 explain the pipeline through the aggregate command's `explain`
+    /// option, which ignores the requested verbosity.
+    pub fn explain_aggregate(&self,
+                             pipeline: Vec<bson::Document>,
+                             options: Option<AggregateOptions>,
+                             verbosity: ExplainVerbosity)
+                             -> Result<ExplainResult> {
+        let opts = options.unwrap_or_else(AggregateOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        let mut spec = self.aggregate_command(pipeline, &opts);
+
+        if wire_version < EXPLAIN_AGGREGATE_WIRE_VERSION {
+            spec.remove("cursor");
+            spec.insert("explain", Bson::Boolean(true));
+            let result = try!(self.db.command_selected(selected,
+                                                       spec,
+                                                       CommandType::Aggregate,
+                                                       read_pref));
+            return Ok(ExplainResult::new(result));
+        }
+
+        self.explain_command(selected, spec, verbosity, read_pref)
+    }
+
+    /// Explains how the server would execute a count operation.
+    pub fn explain_count(&self,
+                         filter: Option<bson::Document>,
+                         options: Option<CountOptions>,
+                         verbosity: ExplainVerbosity)
+                         -> Result<ExplainResult> {
+        let opts = options.unwrap_or_else(CountOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        try!(Collection::require_wire_version(wire_version,
+                                              EXPLAIN_COMMAND_WIRE_VERSION,
+                                              "explaining count operations"));
+        let spec = self.count_command(filter, opts);
+        self.explain_command(selected, spec, verbosity, read_pref)
+    }
+
+    /// Explains how the server would execute a distinct operation.
+    pub fn explain_distinct(&self,
+                            field_name: &str,
+                            filter: Option<bson::Document>,
+                            options: Option<DistinctOptions>,
+                            verbosity: ExplainVerbosity)
+                            -> Result<ExplainResult> {
+        let opts = options.unwrap_or_else(DistinctOptions::new);
+        let read_pref = opts.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        try!(Collection::require_wire_version(wire_version,
+                                              EXPLAIN_FIND_WIRE_VERSION,
+                                              "explaining distinct operations"));
+        let spec = self.distinct_command(field_name, filter, &opts);
+        self.explain_command(selected, spec, verbosity, read_pref)
+    }
+
+    /// Explains how the server would execute an update of a single document.
+    /// The update is not applied.
+    pub fn explain_update_one(&self,
+                              filter: bson::Document,
+                              update: bson::Document,
+                              options: Option<UpdateOptions>,
+                              verbosity: ExplainVerbosity)
+                              -> Result<ExplainResult> {
+        try!(Collection::validate_update(&update));
+        self.explain_update(filter, update, options, false, verbosity)
+    }
+
+    /// Explains how the server would execute an update of multiple documents.
+    /// The update is not applied.
+    pub fn explain_update_many(&self,
+                               filter: bson::Document,
+                               update: bson::Document,
+                               options: Option<UpdateOptions>,
+                               verbosity: ExplainVerbosity)
+                               -> Result<ExplainResult> {
+        try!(Collection::validate_update(&update));
+        self.explain_update(filter, update, options, true, verbosity)
+    }
+
+    /// Explains how the server would execute a deletion of a single document.
+    /// No documents are deleted.
+    pub fn explain_delete_one(&self,
+                              filter: bson::Document,
+                              verbosity: ExplainVerbosity)
+                              -> Result<ExplainResult> {
+        self.explain_delete(filter, false, verbosity)
+    }
+
+    /// Explains how the server would execute a deletion of multiple documents.
+    /// No documents are deleted.
+    pub fn explain_delete_many(&self,
+                               filter: bson::Document,
+                               verbosity: ExplainVerbosity)
+                               -> Result<ExplainResult> {
+        self.explain_delete(filter, true, verbosity)
+    }
+
+    // Internal update explain helper.
+    fn explain_update(&self,
+                      filter: bson::Document,
+                      update: bson::Document,
+                      options: Option<UpdateOptions>,
+                      multi: bool,
+                      verbosity: ExplainVerbosity)
+                      -> Result<ExplainResult> {
+        let options = options.unwrap_or_else(|| UpdateOptions::new(false, None));
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        try!(Collection::require_wire_version(wire_version,
+                                              EXPLAIN_COMMAND_WIRE_VERSION,
+                                              "explaining update operations"));
+
+        let mut model = bson::Document::new();
+        model.insert("q", Bson::Document(filter));
+        model.insert("u", Bson::Document(update));
+        model.insert("upsert", Bson::Boolean(options.upsert));
+        model.insert("multi", Bson::Boolean(multi));
+        if let Some(collation) = options.collation {
+            model.insert("collation", Bson::Document(collation.to_bson()));
+        }
+
+        let mut cmd = bson::Document::new();
+        cmd.insert("update", Bson::String(self.name()));
+        cmd.insert("updates", Bson::Array(vec![Bson::Document(model)]));
+
+        self.explain_command(selected, cmd, verbosity, read_pref)
+    }
+
+    // Internal delete explain helper.
+    fn explain_delete(&self,
+                      filter: bson::Document,
+                      multi: bool,
+                      verbosity: ExplainVerbosity)
+                      -> Result<ExplainResult> {
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        let (selected, wire_version) = try!(self.select_server(&CommandType::Explain,
+                                                               &read_pref));
+        try!(Collection::require_wire_version(wire_version,
+                                              EXPLAIN_COMMAND_WIRE_VERSION,
+                                              "explaining delete operations"));
+
+        let mut model = bson::Document::new();
+        model.insert("q", Bson::Document(filter));
+        model.insert("limit", Bson::I32(if multi { 0 } else { 1 }));
+
+        let mut cmd = bson::Document::new();
+        cmd.insert("delete", Bson::String(self.name()));
+        cmd.insert("deletes", Bson::Array(vec![Bson::Document(model)]));
+
+        self.explain_command(selected, cmd, verbosity, read_pref)
+    }
+
+    // Selects a server for a command of the given type, returning the stream to it along
+    // with the server's wire version, so that the command can be built for the features of
+    // the server that runs it.
+    fn select_server(&self,
+                     cmd_type: &CommandType,
+                     read_pref: &ReadPreference)
+                     -> Result<((PooledStream, bool, bool), i64)> {
+        let client = &self.db.client;
+        let selected = try!(Cursor::select_stream(client, cmd_type, read_pref));
+        let wire_version = try!(client.topology.stream_wire_version(&selected.0));
+        Ok((selected, wire_version))
+    }
+
+    // Selects a writable server, returning the stream to it along with its wire version.
+    fn select_write_server(&self) -> Result<(PooledStream, i64)> {
+        let client = &self.db.client;
+        let stream = try!(client.acquire_write_stream());
+        let wire_version = try!(client.topology.stream_wire_version(&stream));
+        Ok((stream, wire_version))
+    }
+
+    // Fails if a server with the given wire version does not support a feature.
+    fn require_wire_version(server_version: i64, wire_version: i64, feature: &str) -> Result<()> {
+        if server_version < wire_version {
+            return Err(OperationError(format!("The server does not support {}.", feature)));
+        }
+
+        Ok(())
+    }
+
+    // Returns the read concern to send with a read operation, preferring the operation's
+    // read concern over the collection's. Fails if a server with the given wire version
+    // does not support the read concern's level.
+    fn read_concern_for(&self,
+                        read_concern: Option<ReadConcern>,
+                        wire_version: i64)
+                        -> Result<Option<bson::Document>> {
+        let read_concern = read_concern.unwrap_or_else(|| self.read_concern.to_owned());
+        match read_concern.level {
+            Some(level) => {
+                let feature = format!("the {} read concern", level.as_str());
+                try!(Collection::require_wire_version(wire_version,
+                                                      level.min_wire_version(),
+                                                      &feature));
+                Ok(Some(read_concern.to_bson()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Fails if a collation is provided and a server with the given wire version does not
+    // support collations.
+    fn check_collation(collation: Option<&Collation>, wire_version: i64) -> Result<()> {
+        match collation {
+            Some(_) => {
+                Collection::require_wire_version(wire_version, COLLATION_WIRE_VERSION, "collations")
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Runs the explain command on the provided command over the selected server.
+    fn explain_command(&self,
+                       selected: (PooledStream, bool, bool),
+                       cmd: bson::Document,
+                       verbosity: ExplainVerbosity,
+                       read_pref: ReadPreference)
+                       -> Result<ExplainResult> {
+        let mut spec = bson::Document::new();
+        spec.insert("explain", Bson::Document(cmd));
+        spec.insert("verbosity", Bson::String(String::from(verbosity.to_str())));
+
+        let result = try!(self.db.command_selected(selected,
+                                                   spec,
+                                                   CommandType::Explain,
+                                                   read_pref));
+        Ok(ExplainResult::new(result))
     }
 
     /// Returns a list of documents within the collection that match the filter.
@@ -185,6 +890,41 @@ impl Collection {
                               cmd_type: CommandType)
                               -> Result<Cursor> {
         let options = options.unwrap_or_else(FindOptions::new);
+        let read_pref = options.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+        let (selected, wire_version) = try!(self.select_server(&cmd_type, &read_pref));
+        self.find_selected(selected, wire_version, filter, options, cmd_type)
+    }
+
+    // Runs a find operation over the selected server, which supports the given wire version.
+    fn find_selected(&self,
+                     selected: (PooledStream, bool, bool),
+                     wire_version: i64,
+                     filter: Option<bson::Document>,
+                     options: FindOptions,
+                     cmd_type: CommandType)
+                     -> Result<Cursor> {
+        let read_pref = options.read_preference.clone().unwrap_or(self.read_preference.to_owned());
+
+        // Collations and read concerns can only be sent with the find command.
+        let read_concern = try!(self.read_concern_for(options.read_concern.clone(), wire_version));
+        if options.collation.is_some() || read_concern.is_some() {
+            try!(Collection::check_collation(options.collation.as_ref(), wire_version));
+            let cursor_type = options.cursor_type.clone();
+            let max_await_time_ms = options.max_await_time_ms;
+            let mut spec = self.find_command(filter.unwrap_or_else(bson::Document::new), options);
+            if let Some(read_concern) = read_concern {
+                spec.insert("readConcern", Bson::Document(read_concern));
+            }
+
+            let mut cursor = try!(self.db.command_cursor_selected(selected,
+                                                                  spec,
+                                                                  cmd_type,
+                                                                  read_pref));
+            cursor.set_cursor_type(cursor_type);
+            cursor.set_max_await_time_ms(max_await_time_ms);
+            return Ok(cursor);
+        }
+
         let flags = OpQueryFlags::with_find_options(&options);
 
         let doc = if options.sort.is_some() {
@@ -200,19 +940,71 @@ impl Collection {
             filter.unwrap_or_else(bson::Document::new)
         };
 
-        let read_pref = options.read_preference.unwrap_or(self.read_preference.to_owned());
+        let mut cursor = try!(Cursor::query_selected(selected,
+                                                     self.db.client.clone(),
+                                                     self.namespace.to_owned(),
+                                                     options.batch_size,
+                                                     flags,
+                                                     options.skip as i32,
+                                                     options.limit,
+                                                     doc,
+                                                     options.projection.clone(),
+                                                     cmd_type,
+                                                     false,
+                                                     read_pref));
+
+        cursor.set_max_await_time_ms(options.max_await_time_ms);
+        Ok(cursor)
+    }
 
-        Cursor::query(self.db.client.clone(),
-                      self.namespace.to_owned(),
-                      options.batch_size,
-                      flags,
-                      options.skip as i32,
-                      options.limit,
-                      doc,
-                      options.projection.clone(),
-                      cmd_type,
-                      false,
-                      read_pref)
+    // Builds a find command for the filter and options.
+    fn find_command(&self, filter: bson::Document, options: FindOptions) -> bson::Document {
+        let mut cmd = bson::Document::new();
+        cmd.insert("find", Bson::String(self.name()));
+        cmd.insert("filter", Bson::Document(filter));
+        if let Some(sort) = options.sort {
+            cmd.insert("sort", Bson::Document(sort));
+        }
+        if let Some(projection) = options.projection {
+            cmd.insert("projection", Bson::Document(projection));
+        }
+        if options.skip != 0 {
+            cmd.insert("skip", Bson::I64(options.skip as i64));
+        }
+        if options.limit < 0 {
+            cmd.insert("limit", Bson::I64(-options.limit as i64));
+            cmd.insert("singleBatch", Bson::Boolean(true));
+        } else if options.limit != 0 {
+            cmd.insert("limit", Bson::I64(options.limit as i64));
+        }
+        if options.batch_size != 0 {
+            cmd.insert("batchSize", Bson::I32(options.batch_size));
+        }
+        if let Some(comment) = options.comment {
+            cmd.insert("comment", Bson::String(comment));
+        }
+        if let Some(max_time_ms) = options.max_time_ms {
+            cmd.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(collation) = options.collation {
+            cmd.insert("collation", Bson::Document(collation.to_bson()));
+        }
+        if options.cursor_type != CursorType::NonTailable {
+            cmd.insert("tailable", Bson::Boolean(true));
+        }
+        if options.cursor_type == CursorType::TailableAwait {
+            cmd.insert("awaitData", Bson::Boolean(true));
+        }
+        if options.no_cursor_timeout {
+            cmd.insert("noCursorTimeout", Bson::Boolean(true));
+        }
+        if options.allow_partial_results {
+            cmd.insert("allowPartialResults", Bson::Boolean(true));
+        }
+        if options.op_log_replay {
+            cmd.insert("oplogReplay", Bson::Boolean(true));
+        }
+        cmd
     }
 
     /// Returns the first document within the collection that matches the filter, or None.
@@ -238,89 +1030,139 @@ impl Collection {
         }
     }
 
+    /// Converts the collection into one that encodes and decodes values of type `T`.
+    pub fn with_type<T: Encodable + Decodable>(self) -> TypedCollection<T> {
+        TypedCollection::new(self)
+    }
+
+    /// Returns a cursor that decodes each matching document into a value of type `T`.
+    pub fn find_as<T: Decodable>(&self,
+                                 filter: Option<bson::Document>,
+                                 options: Option<FindOptions>)
+                                 -> Result<TypedCursor<T>> {
+        self.find(filter, options).map(Cursor::with_type)
+    }
+
+    /// Returns the first document within the collection that matches the filter,
+    /// decoded into a value of type `T`, or None.
+    pub fn find_one_as<T: Decodable>(&self,
+                                     filter: Option<bson::Document>,
+                                     options: Option<FindOptions>)
+                                     -> Result<Option<T>> {
+        match try!(self.find_one(filter, options)) {
+            Some(doc) => codec::decode_document(doc).map(Some),
+            None => Ok(None),
+        }
+    }
+
     // Helper method for all findAndModify commands.
     fn find_and_modify(&self,
-                       cmd: &mut bson::Document,
+                       stream: PooledStream,
+                       cmd: bson::Document,
                        filter: bson::Document,
-                       _max_time_ms: Option<i64>,
-                       projection: Option<bson::Document>,
-                       sort: Option<bson::Document>,
                        write_concern: Option<WriteConcern>,
                        cmd_type: CommandType)
-                       -> Result<Option<bson::Document>> {
+                       -> Result<FindAndModifyResult> {
 
         let wc = write_concern.unwrap_or(self.write_concern.clone());
+        try!(wc.validate());
 
         let mut new_cmd = bson::Document::new();
         new_cmd.insert("findAndModify", Bson::String(self.name()));
         new_cmd.insert("query", Bson::Document(filter));
         new_cmd.insert("writeConcern", Bson::Document(wc.to_bson()));
-        if sort.is_some() {
-            new_cmd.insert("sort", Bson::Document(sort.unwrap()));
-        }
-        if projection.is_some() {
-            new_cmd.insert("fields", Bson::Document(projection.unwrap()));
-        }
 
-        for (key, val) in cmd.iter() {
-            new_cmd.insert(key.to_owned(), val.to_owned());
+        for (key, val) in cmd.into_iter() {
+            new_cmd.insert(key, val);
         }
 
-        let res = try!(self.db.command(new_cmd, cmd_type, None));
+        let res = try!(self.write_command(stream, new_cmd, cmd_type, wc.is_acknowledged()));
         try!(WriteException::validate_write_result(res.clone(), wc));
-        let doc = match res.get("value") {
-            Some(&Bson::Document(ref nested_doc)) => Some(nested_doc.to_owned()),
-            _ => None,
-        };
+        Ok(FindAndModifyResult::new(&res))
+    }
 
-        Ok(doc)
+    // Builds the options shared by all findAndModify commands.
+    fn find_and_modify_options(max_time_ms: Option<i64>,
+                               projection: Option<bson::Document>,
+                               sort: Option<bson::Document>,
+                               collation: Option<Collation>,
+                               hint: Option<String>,
+                               hint_doc: Option<bson::Document>)
+                               -> bson::Document {
+        let mut cmd = bson::Document::new();
+        if let Some(sort) = sort {
+            cmd.insert("sort", Bson::Document(sort));
+        }
+        if let Some(projection) = projection {
+            cmd.insert("fields", Bson::Document(projection));
+        }
+        if let Some(max_time_ms) = max_time_ms {
+            cmd.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(collation) = collation {
+            cmd.insert("collation", Bson::Document(collation.to_bson()));
+        }
+        if let Some(hint_doc) = hint_doc {
+            cmd.insert("hint", Bson::Document(hint_doc));
+        } else if let Some(hint) = hint {
+            cmd.insert("hint", Bson::String(hint));
+        }
+        cmd
     }
 
     // Helper method for validated replace and update commands.
     fn find_one_and_replace_or_update(&self,
                                       filter: bson::Document,
                                       update: bson::Document,
-                                      after: bool,
-                                      max_time_ms: Option<i64>,
-                                      projection: Option<bson::Document>,
-                                      sort: Option<bson::Document>,
-                                      upsert: bool,
-                                      write_concern: Option<WriteConcern>,
+                                      opts: FindOneAndUpdateOptions,
                                       cmd_type: CommandType)
-                                      -> Result<Option<bson::Document>> {
-
-        let mut cmd = bson::Document::new();
+                                      -> Result<FindAndModifyResult> {
+
+        let (stream, wire_version) = try!(self.select_write_server());
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+        let mut cmd = Collection::find_and_modify_options(opts.max_time_ms,
+                                                          opts.projection,
+                                                          opts.sort,
+                                                          opts.collation,
+                                                          opts.hint,
+                                                          opts.hint_doc);
         cmd.insert("update", Bson::Document(update));
-        if after {
+        if opts.return_document.to_bool() {
             cmd.insert("new", Bson::Boolean(true));
         }
-        if upsert {
+        if opts.upsert {
             cmd.insert("upsert", Bson::Boolean(true));
         }
+        if let Some(array_filters) = opts.array_filters {
+            let filters = array_filters.into_iter().map(Bson::Document).collect();
+            cmd.insert("arrayFilters", Bson::Array(filters));
+        }
+        if let Some(bypass) = opts.bypass_document_validation {
+            cmd.insert("bypassDocumentValidation", Bson::Boolean(bypass));
+        }
 
-        self.find_and_modify(&mut cmd,
-                             filter,
-                             max_time_ms,
-                             projection,
-                             sort,
-                             write_concern,
-                             cmd_type)
+        self.find_and_modify(stream, cmd, filter, opts.write_concern, cmd_type)
     }
 
     /// Finds a single document and deletes it, returning the original.
     pub fn find_one_and_delete(&self,
                                filter: bson::Document,
                                options: Option<FindOneAndDeleteOptions>)
-                               -> Result<Option<bson::Document>> {
+                               -> Result<FindAndModifyResult> {
 
         let opts = options.unwrap_or_else(FindOneAndDeleteOptions::new);
-        let mut cmd = bson::Document::new();
+        let (stream, wire_version) = try!(self.select_write_server());
+        try!(Collection::check_collation(opts.collation.as_ref(), wire_version));
+        let mut cmd = Collection::find_and_modify_options(opts.max_time_ms,
+                                                          opts.projection,
+                                                          opts.sort,
+                                                          opts.collation,
+                                                          opts.hint,
+                                                          opts.hint_doc);
         cmd.insert("remove", Bson::Boolean(true));
-        self.find_and_modify(&mut cmd,
+        self.find_and_modify(stream,
+                             cmd,
                              filter,
-                             opts.max_time_ms,
-                             opts.projection,
-                             opts.sort,
                              opts.write_concern,
                              CommandType::FindOneAndDelete)
     }
@@ -331,17 +1173,12 @@ impl Collection {
                                 filter: bson::Document,
                                 replacement: bson::Document,
                                 options: Option<FindOneAndUpdateOptions>)
-                                -> Result<Option<bson::Document>> {
+                                -> Result<FindAndModifyResult> {
         let opts = options.unwrap_or_else(FindOneAndUpdateOptions::new);
         try!(Collection::validate_replace(&replacement));
         self.find_one_and_replace_or_update(filter,
                                             replacement,
-                                            opts.return_document.to_bool(),
-                                            opts.max_time_ms,
-                                            opts.projection,
-                                            opts.sort,
-                                            opts.upsert,
-                                            opts.write_concern,
+                                            opts,
                                             CommandType::FindOneAndReplace)
     }
 
@@ -351,74 +1188,84 @@ impl Collection {
                                filter: bson::Document,
                                update: bson::Document,
                                options: Option<FindOneAndUpdateOptions>)
-                               -> Result<Option<bson::Document>> {
+                               -> Result<FindAndModifyResult> {
         let opts = options.unwrap_or_else(FindOneAndUpdateOptions::new);
         try!(Collection::validate_update(&update));
-        self.find_one_and_replace_or_update(filter,
-                                            update,
-                                            opts.return_document.to_bool(),
-                                            opts.max_time_ms,
-                                            opts.projection,
-                                            opts.sort,
-                                            opts.upsert,
-                                            opts.write_concern,
-                                            CommandType::FindOneAndUpdate)
+        self.find_one_and_replace_or_update(filter, update, opts, CommandType::FindOneAndUpdate)
     }
 
-    fn get_unordered_batches(requests: Vec<WriteModel>) -> Vec<Batch> {
+    // Groups the requests into a batch of each kind, along with the original index of each
+    // request in its batch.
+    fn get_unordered_batches(requests: Vec<WriteModel>) -> Vec<(Batch, Vec<i64>)> {
         let mut inserts = vec![];
         let mut deletes = vec![];
         let mut updates = vec![];
+        let mut insert_indexes = vec![];
+        let mut delete_indexes = vec![];
+        let mut update_indexes = vec![];
 
-        for req in requests {
+        for (i, req) in requests.into_iter().enumerate() {
             match req {
-                WriteModel::InsertOne { document } => inserts.push(document),
-                WriteModel::DeleteOne { filter } => {
+                WriteModel::InsertOne { document } => {
+                    insert_indexes.push(i as i64);
+                    inserts.push(document)
+                }
+                WriteModel::DeleteOne { filter, collation } => {
+                    delete_indexes.push(i as i64);
                     deletes.push(DeleteModel {
                         filter: filter,
                         multi: false,
+                        collation: collation,
                     })
                 }
-                WriteModel::DeleteMany { filter } => {
+                WriteModel::DeleteMany { filter, collation } => {
+                    delete_indexes.push(i as i64);
                     deletes.push(DeleteModel {
                         filter: filter,
                         multi: true,
+                        collation: collation,
                     })
                 }
-                WriteModel::ReplaceOne { filter, replacement, upsert } => {
+                WriteModel::ReplaceOne { filter, replacement, upsert, collation } => {
+                    update_indexes.push(i as i64);
                     updates.push(UpdateModel {
                         filter: filter,
                         update: replacement,
                         upsert: upsert,
                         multi: false,
                         is_replace: true,
+                        collation: collation,
                     })
                 }
-                WriteModel::UpdateOne { filter, update, upsert } => {
+                WriteModel::UpdateOne { filter, update, upsert, collation } => {
+                    update_indexes.push(i as i64);
                     updates.push(UpdateModel {
                         filter: filter,
                         update: update,
                         upsert: upsert,
                         multi: false,
                         is_replace: false,
+                        collation: collation,
                     })
                 }
-                WriteModel::UpdateMany { filter, update, upsert } => {
+                WriteModel::UpdateMany { filter, update, upsert, collation } => {
+                    update_indexes.push(i as i64);
                     updates.push(UpdateModel {
                         filter: filter,
                         update: update,
                         upsert: upsert,
                         multi: true,
                         is_replace: false,
+                        collation: collation,
                     })
                 }
             }
         }
 
         vec![
-            Batch::Insert(inserts),
-            Batch::Delete(deletes),
-            Batch::Update(updates),
+            (Batch::Insert(inserts), insert_indexes),
+            (Batch::Delete(deletes), delete_indexes),
+            (Batch::Update(updates), update_indexes),
         ]
     }
 
@@ -443,145 +1290,484 @@ impl Collection {
 
     fn execute_insert_batch(&self,
                             documents: Vec<bson::Document>,
-                            start_index: i64,
+                            models: Vec<WriteModel>,
+                            indexes: &[i64],
                             ordered: bool,
+                            write_concern: Option<WriteConcern>,
                             result: &mut BulkWriteResult,
                             exception: &mut BulkWriteException)
-                            -> bool {
-        let models = documents.iter()
-            .map(|doc| WriteModel::InsertOne { document: doc.clone() })
-            .collect();
-
-        let options = Some(InsertManyOptions::new(ordered, None));
+                            -> Result<bool> {
+        let options = Some(InsertManyOptions::new(ordered, write_concern));
 
         match self.insert_many(documents, options) {
             Ok(insert_result) => {
-                result.process_insert_many_result(insert_result, models, start_index, exception)
+                Ok(result.process_insert_many_result(insert_result, models, indexes, exception))
             }
-            Err(_) => {
+            Err(err) => {
                 exception.add_unproccessed_models(models);
-                false
+                Err(err)
             }
         }
     }
 
     fn execute_delete_batch(&self,
-                            models: Vec<DeleteModel>,
+                            delete_models: Vec<DeleteModel>,
+                            models: Vec<WriteModel>,
+                            indexes: &[i64],
                             ordered: bool,
+                            write_concern: Option<WriteConcern>,
                             result: &mut BulkWriteResult,
                             exception: &mut BulkWriteException)
-                            -> bool {
-        let original_models = models.iter()
-            .map(|model| {
-                if model.multi {
-                    WriteModel::DeleteMany { filter: model.filter.clone() }
-                } else {
-                    WriteModel::DeleteOne { filter: model.filter.clone() }
-                }
-            })
-            .collect();
-
-        match self.bulk_delete(models, ordered, None, CommandType::DeleteMany) {
+                            -> Result<bool> {
+        match self.bulk_delete(delete_models, ordered, write_concern, CommandType::DeleteMany) {
             Ok(bulk_delete_result) => {
-                result.process_bulk_delete_result(bulk_delete_result, original_models, exception)
+                Ok(result.process_bulk_delete_result(bulk_delete_result, models, indexes, exception))
             }
-            Err(_) => {
-                exception.add_unproccessed_models(original_models);
-                false
+            Err(err) => {
+                exception.add_unproccessed_models(models);
+                Err(err)
             }
         }
     }
 
     fn execute_update_batch(&self,
-                            models: Vec<UpdateModel>,
-                            start_index: i64,
+                            update_models: Vec<UpdateModel>,
+                            models: Vec<WriteModel>,
+                            indexes: &[i64],
                             ordered: bool,
+                            write_concern: Option<WriteConcern>,
                             result: &mut BulkWriteResult,
                             exception: &mut BulkWriteException)
-                            -> bool {
-        let original_models = models.iter()
-            .map(|model| {
-                if model.multi {
-                    WriteModel::DeleteMany { filter: model.filter.clone() }
-                } else {
-                    WriteModel::DeleteOne { filter: model.filter.clone() }
-                }
-            })
-            .collect();
-
-        match self.bulk_update(models, ordered, None, CommandType::UpdateMany) {
+                            -> Result<bool> {
+        match self.bulk_update(update_models, ordered, write_concern, CommandType::UpdateMany) {
             Ok(bulk_update_result) => {
-                result.process_bulk_update_result(bulk_update_result,
-                                                  original_models,
-                                                  start_index,
-                                                  exception)
+                Ok(result.process_bulk_update_result(bulk_update_result, models, indexes, exception))
             }
-            Err(_) => {
-                exception.add_unproccessed_models(original_models);
-                false
+            Err(err) => {
+                exception.add_unproccessed_models(models);
+                Err(err)
             }
         }
     }
 
+    // Executes a batch, given the original requests it was built from and their indexes
+    // in the bulk write. Returns whether the batch completed without write errors, or the
+    // error that prevented it from being sent.
     fn execute_batch(&self,
                      batch: Batch,
-                     start_index: i64,
+                     models: Vec<WriteModel>,
+                     indexes: &[i64],
                      ordered: bool,
+                     write_concern: Option<WriteConcern>,
                      result: &mut BulkWriteResult,
                      exception: &mut BulkWriteException)
-                     -> bool {
+                     -> Result<bool> {
         match batch {
             Batch::Insert(docs) => {
-                self.execute_insert_batch(docs, start_index, ordered, result, exception)
+                self.execute_insert_batch(docs,
+                                          models,
+                                          indexes,
+                                          ordered,
+                                          write_concern,
+                                          result,
+                                          exception)
+            }
+            Batch::Delete(deletes) => {
+                self.execute_delete_batch(deletes,
+                                          models,
+                                          indexes,
+                                          ordered,
+                                          write_concern,
+                                          result,
+                                          exception)
             }
-            Batch::Delete(models) => self.execute_delete_batch(models, ordered, result, exception),
-            Batch::Update(models) => {
-                self.execute_update_batch(models, start_index, ordered, result, exception)
+            Batch::Update(updates) => {
+                self.execute_update_batch(updates,
+                                          models,
+                                          indexes,
+                                          ordered,
+                                          write_concern,
+                                          result,
+                                          exception)
             }
         }
     }
 
     /// Sends a batch of writes to the server at the same time.
     pub fn bulk_write(&self, requests: Vec<WriteModel>, ordered: bool) -> BulkWriteResult {
+        self.bulk_write_with_write_concern(requests, ordered, None).0
+    }
+
+    /// Returns a builder for a bulk operation whose requests are executed in order,
+    /// stopping at the first error.
+    pub fn initialize_ordered_bulk<'a>(&'a self) -> BulkOperation<'a> {
+        BulkOperation::new(self, true)
+    }
+
+    /// Returns a builder for a bulk operation whose requests may be executed in any order,
+    /// continuing past errors.
+    pub fn initialize_unordered_bulk<'a>(&'a self) -> BulkOperation<'a> {
+        BulkOperation::new(self, false)
+    }
+
+    // Sends a batch of writes with the given write concern, or the collection's if none
+    // is provided. Along with the result, returns the error of the first batch that could
+    // not be sent; its requests are reported as unprocessed.
+    fn bulk_write_with_write_concern(&self,
+                                     requests: Vec<WriteModel>,
+                                     ordered: bool,
+                                     write_concern: Option<WriteConcern>)
+                                     -> (BulkWriteResult, Option<Error>) {
+        let originals = requests.clone();
+
         let batches = if ordered {
-            Collection::get_ordered_batches(VecDeque::from_iter(requests.into_iter()))
+            let batches =
+                Collection::get_ordered_batches(VecDeque::from_iter(requests.into_iter()));
+
+            let mut start_index = 0;
+            batches.into_iter()
+                .map(|batch| {
+                    let indexes = (start_index..start_index + batch.len()).collect();
+                    start_index += batch.len();
+                    (batch, indexes)
+                })
+                .collect()
         } else {
             Collection::get_unordered_batches(requests)
         };
 
         let mut result = BulkWriteResult::new();
+        result.acknowledged = write_concern.as_ref()
+            .unwrap_or(&self.write_concern)
+            .is_acknowledged();
         let mut exception = BulkWriteException::new(vec![], vec![], vec![], None);
+        let mut error = None;
 
-        let mut start_index = 0;
+        for (batch, indexes) in batches {
+            if indexes.is_empty() {
+                continue;
+            }
 
-        for batch in batches {
-            let length = batch.len();
-            let success =
-                self.execute_batch(batch, start_index, ordered, &mut result, &mut exception);
+            let models = indexes.iter().map(|&i| originals[i as usize].clone()).collect();
+            let success = match self.execute_batch(batch,
+                                                   models,
+                                                   &indexes,
+                                                   ordered,
+                                                   write_concern.clone(),
+                                                   &mut result,
+                                                   &mut exception) {
+                Ok(success) => success,
+                Err(err) => {
+                    if error.is_none() {
+                        error = Some(err);
+                    }
+                    false
+                }
+            };
 
             if !success && ordered {
                 break;
             }
-
-            start_index += length;
         }
 
-        if exception.unprocessed_requests.is_empty() {
+        if !exception.write_errors.is_empty() || exception.write_concern_error.is_some() ||
+           !exception.unprocessed_requests.is_empty() {
             result.bulk_write_exception = Some(exception);
         }
 
+        (result, error)
+    }
+
+    // Collations can't be sent with legacy write opcodes, and without a reply the driver can't
+    // tell whether the server applied them, so they require an acknowledged write concern.
+    fn check_acknowledged_collation(write_concern: &WriteConcern) -> Result<()> {
+        if write_concern.is_acknowledged() {
+            Ok(())
+        } else {
+            Err(ArgumentError(String::from("Collations are not supported with unacknowledged \
+                                            write concerns.")))
+        }
+    }
+
+    // Sends a write command with an unacknowledged write concern without waiting for a reply.
+    // Servers that accept OP_MSG receive the command itself with the moreToCome flag set, while
+    // older servers receive the equivalent legacy write messages produced by `legacy_messages`.
+    fn send_unacknowledged<F>(&self,
+                              mut stream: PooledStream,
+                              cmd: bson::Document,
+                              cmd_type: CommandType,
+                              legacy_messages: F)
+                              -> Result<()>
+        where F: FnOnce(&Client) -> Result<Vec<Message>>
+    {
+        let client = self.db.client.clone();
+        let wire_version = try!(client.topology.stream_wire_version(&stream));
+
+        let messages = if wire_version >= OP_MSG_WIRE_VERSION {
+            let mut body = cmd.clone();
+            body.insert("$db", Bson::String(self.db.name.to_owned()));
+            let flags = OpMsgFlags { more_to_come: true };
+            vec![try!(Message::new_msg(client.get_req_id(), flags, body))]
+        } else {
+            try!(legacy_messages(&client))
+        };
+
+        let socket = stream.get_socket();
+        let req_id = messages.first().map_or(0, |message| message.header().request_id) as i64;
+        let cmd_name = cmd_type.to_str();
+        let connstring = format!("{}", try!(socket.get_ref().peer_addr()));
+
+        let hook_result = client.run_start_hooks(&CommandStarted {
+            command: cmd,
+            database_name: self.db.name.to_owned(),
+            command_name: String::from(cmd_name),
+            request_id: req_id,
+            connection_string: connstring.clone(),
+            retry: false,
+        });
+
+        if hook_result.is_err() {
+            return Err(EventListenerError(None));
+        }
+
+        let init_time = time::precise_time_ns();
+
+        for message in &messages {
+            if let Err(e) = message.write(socket) {
+                let hook_result = client.run_completion_hooks(&CommandResult::Failure {
+                    duration: time::precise_time_ns() - init_time,
+                    command_name: String::from(cmd_name),
+                    failure: &e,
+                    request_id: req_id,
+                    connection_string: connstring,
+                });
+
+                if hook_result.is_err() {
+                    return Err(EventListenerError(Some(Box::new(e))));
+                }
+
+                return Err(e);
+            }
+        }
+
+        // No reply is sent for unacknowledged writes, so listeners are given a successful one.
+        let _ = client.run_completion_hooks(&CommandResult::Success {
+            duration: time::precise_time_ns() - init_time,
+            reply: doc! { "ok" => 1 },
+            command_name: String::from(cmd_name),
+            request_id: req_id,
+            connection_string: connstring,
+        });
+
+        Ok(())
+    }
+
+    // Runs a write command. Retryable commands sent to a server that supports retryable writes
+    // are given a session and transaction number, so that the server can recognize a retry,
+    // and are retried once on a newly selected server after a network or failover error.
+    fn write_command(&self,
+                     stream: PooledStream,
+                     mut cmd: bson::Document,
+                     cmd_type: CommandType,
+                     retryable: bool)
+                     -> Result<bson::Document> {
+        let client = self.db.client.clone();
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        if !retryable || !client.retry_writes {
+            return self.db.command_selected((stream, false, false), cmd, cmd_type, read_pref);
+        }
+
+        let description = try!(client.topology.stream_server_description(&stream));
+        if !description.supports_retryable_writes() {
+            return self.db.command_selected((stream, false, false), cmd, cmd_type, read_pref);
+        }
+
+        let wire_version = try!(client.topology.stream_wire_version(&stream));
+        let timeout = description.logical_session_timeout_minutes
+            .unwrap_or(DEFAULT_SESSION_TIMEOUT_MINUTES);
+        let mut session = try!(client.session_pool.checkout(timeout));
+        cmd.insert("lsid", Bson::Document(session.id.clone()));
+        cmd.insert("txnNumber", Bson::I64(session.next_txn_number()));
+
+        let mut result = self.db.command_selected((stream, false, false),
+                                                  cmd.clone(),
+                                                  cmd_type.clone(),
+                                                  read_pref.clone());
+
+        if let Err(IoError(_)) = result {
+            session.mark_dirty();
+        }
+
+        let retry = match result {
+            Ok(ref reply) => Collection::has_retryable_write_concern_error(reply),
+            Err(ref err) => err.is_retryable(),
+        };
+
+        if retry {
+            // The retry is only sent to a server that supports retryable writes and at least
+            // the features of the server that ran the first attempt.
+            if let Ok(stream) = client.acquire_write_stream() {
+                let supports_retry = client.topology
+                    .stream_server_description(&stream)
+                    .map(|retry_description| retry_description.supports_retryable_writes())
+                    .unwrap_or(false);
+                let supports_features = client.topology
+                    .stream_wire_version(&stream)
+                    .map(|retry_version| retry_version >= wire_version)
+                    .unwrap_or(false);
+
+                if supports_retry && supports_features {
+                    result = self.db.retry_command_selected((stream, false, false),
+                                                            cmd,
+                                                            cmd_type,
+                                                            read_pref);
+
+                    if let Err(IoError(_)) = result {
+                        session.mark_dirty();
+                    }
+                }
+            }
+        }
+
+        let _ = client.session_pool.checkin(session, timeout);
         result
     }
 
-    // Internal insertion helper function. Returns a vec of collected ids and a possible exception.
+    // Returns true if a reply reports a write concern error that a retry may overcome.
+    fn has_retryable_write_concern_error(reply: &bson::Document) -> bool {
+        match reply.get("writeConcernError") {
+            Some(&Bson::Document(ref error)) => {
+                match error.get("code") {
+                    Some(&Bson::I32(code)) => {
+                        ErrorCode::from_i32(code).map_or(false, |code| code.is_retryable_error())
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    // Sends the statements of a write command in as many commands as the limits of the servers
+    // they are sent to require. The replies are merged as though a single command had been
+    // sent, with the indexes of upserts and write errors relative to the full list of
+    // statements, and ordered writes stop after the first command that reports a write error.
+    // Returns None if the write concern is unacknowledged, in which case `legacy_messages`
+    // produces the legacy write messages for a range of statements. Statements with
+    // collations are only sent to servers that support them.
+    fn write_in_batches<F>(&self,
+                           cmd: bson::Document,
+                           key: &str,
+                           statements: &[bson::Document],
+                           ordered: bool,
+                           write_concern: &WriteConcern,
+                           cmd_type: CommandType,
+                           retryable: bool,
+                           collation: bool,
+                           legacy_messages: F)
+                           -> Result<Option<bson::Document>>
+        where F: Fn(&Client, Range<usize>) -> Result<Vec<Message>>
+    {
+        if statements.is_empty() {
+            return Err(ArgumentError(String::from("Write commands require at least one \
+                                                   statement.")));
+        }
+
+        // The statements are sent as an array, which adds a type byte, the key, a length
+        // and a terminating null byte.
+        let mut buffer = Vec::new();
+        try!(bson::encode_document(&mut buffer, &cmd));
+        let command_size = buffer.len() as i64 + key.len() as i64 + 7;
+
+        let mut n = 0;
+        let mut n_modified = 0;
+        let mut upserted = Vec::new();
+        let mut write_errors = Vec::new();
+        let mut write_concern_error = None;
+
+        let mut start = 0;
+        while start < statements.len() {
+            // Each batch is sized for the limits of the server it is sent to.
+            let (stream, wire_version) = try!(self.select_write_server());
+            if collation {
+                try!(Collection::require_wire_version(wire_version,
+                                                      COLLATION_WIRE_VERSION,
+                                                      "collations"));
+            }
+
+            let description = try!(self.db.client.topology.stream_server_description(&stream));
+            let end = try!(batch::batch_end(statements, start, command_size, &description));
+            let range = start..end;
+            start = end;
+
+            let mut batch_cmd = cmd.clone();
+            let batch = statements[range.clone()].iter().cloned().map(Bson::Document).collect();
+            batch_cmd.insert(key, Bson::Array(batch));
+
+            if !write_concern.is_acknowledged() {
+                try!(self.send_unacknowledged(stream,
+                                              batch_cmd,
+                                              cmd_type.clone(),
+                                              |client| legacy_messages(client, range.clone())));
+                continue;
+            }
+
+            let result = try!(self.write_command(stream, batch_cmd, cmd_type.clone(), retryable));
+
+            if let Some(&Bson::I32(count)) = result.get("n") {
+                n += count;
+            }
+            if let Some(&Bson::I32(count)) = result.get("nModified") {
+                n_modified += count;
+            }
+            if let Some(&Bson::Array(ref docs)) = result.get("upserted") {
+                upserted.extend(docs.iter().map(|doc| offset_index(doc, range.start)));
+            }
+            if let Some(&Bson::Array(ref errors)) = result.get("writeErrors") {
+                write_errors.extend(errors.iter().map(|error| offset_index(error, range.start)));
+            }
+            if let Some(error) = result.get("writeConcernError") {
+                write_concern_error = Some(error.clone());
+            }
+
+            if ordered && !write_errors.is_empty() {
+                break;
+            }
+        }
+
+        if !write_concern.is_acknowledged() {
+            return Ok(None);
+        }
+
+        let mut reply = doc! {
+            "ok" => 1,
+            "n" => n,
+            "nModified" => n_modified
+        };
+        if !upserted.is_empty() {
+            reply.insert("upserted", Bson::Array(upserted));
+        }
+        if !write_errors.is_empty() {
+            reply.insert("writeErrors", Bson::Array(write_errors));
+        }
+        if let Some(error) = write_concern_error {
+            reply.insert("writeConcernError", error);
+        }
+
+        Ok(Some(reply))
+    }
+
+    // Internal insertion helper function. Returns a vec of collected ids, a possible exception,
+    // and whether the server acknowledged the write.
     fn insert(&self,
               docs: Vec<bson::Document>,
               ordered: bool,
               write_concern: Option<WriteConcern>,
               cmd_type: CommandType)
-              -> Result<(Vec<Bson>, Option<BulkWriteException>)> {
+              -> Result<(Vec<Bson>, Option<BulkWriteException>, bool)> {
 
         let wc = write_concern.unwrap_or(self.write_concern.clone());
+        try!(wc.validate());
 
         let mut converted_docs = Vec::new();
         let mut ids = Vec::new();
@@ -596,16 +1782,36 @@ impl Collection {
                     ids.push(id);
                 }
             }
-            converted_docs.push(Bson::Document(cdoc));
+            converted_docs.push(cdoc);
         }
 
         let mut cmd = bson::Document::new();
         cmd.insert("insert", Bson::String(self.name()));
-        cmd.insert("documents", Bson::Array(converted_docs));
         cmd.insert("ordered", Bson::Boolean(ordered));
         cmd.insert("writeConcern", Bson::Document(wc.to_bson()));
 
-        let result = try!(self.db.command(cmd, cmd_type, None));
+        let namespace = self.namespace.to_owned();
+        let reply = try!(self.write_in_batches(cmd,
+                                               "documents",
+                                               &converted_docs,
+                                               ordered,
+                                               &wc,
+                                               cmd_type,
+                                               true,
+                                               false,
+                                               |client, range| {
+            let flags = OpInsertFlags { continue_on_error: !ordered };
+            let message = try!(Message::new_insert(client.get_req_id(),
+                                                   flags,
+                                                   namespace.to_owned(),
+                                                   converted_docs[range].to_vec()));
+            Ok(vec![message])
+        }));
+
+        let result = match reply {
+            Some(result) => result,
+            None => return Ok((ids, None, false)),
+        };
 
         // Intercept bulk write exceptions and insert into the result
         let exception_res = BulkWriteException::validate_bulk_write_result(result.clone(), wc);
@@ -615,7 +1821,7 @@ impl Collection {
             Err(e) => return Err(e),
         };
 
-        Ok((ids, exception))
+        Ok((ids, exception, true))
     }
 
     /// Inserts the provided document. If the document is missing an identifier,
@@ -624,10 +1830,10 @@ impl Collection {
                       doc: bson::Document,
                       write_concern: Option<WriteConcern>)
                       -> Result<InsertOneResult> {
-        let (ids, bulk_exception) = try!(self.insert(vec![doc],
-                                                     true,
-                                                     write_concern.clone(),
-                                                     CommandType::InsertOne));
+        let (ids, bulk_exception, acknowledged) = try!(self.insert(vec![doc],
+                                                                   true,
+                                                                   write_concern.clone(),
+                                                                   CommandType::InsertOne));
 
         if ids.is_empty() {
             return Err(OperationError(String::from("No ids returned for insert_one.")));
@@ -649,7 +1855,19 @@ impl Collection {
             None => Some(ids[0].to_owned()),
         };
 
-        Ok(InsertOneResult::new(id, exception))
+        let mut result = InsertOneResult::new(id, exception);
+        result.acknowledged = acknowledged;
+        Ok(result)
+    }
+
+    /// Encodes the provided value as a document and inserts it. If the encoded document
+    /// is missing an identifier, the driver should generate one.
+    pub fn insert_one_from<T: Encodable>(&self,
+                                         value: &T,
+                                         write_concern: Option<WriteConcern>)
+                                         -> Result<InsertOneResult> {
+        let doc = try!(codec::encode_document(value));
+        self.insert_one(doc, write_concern)
     }
 
     /// Inserts the provided documents. If any documents are missing an identifier,
@@ -659,10 +1877,10 @@ impl Collection {
                        options: Option<InsertManyOptions>)
                        -> Result<InsertManyResult> {
         let options = options.unwrap_or_else(|| InsertManyOptions::new(false, None));
-        let (ids, exception) = try!(self.insert(docs,
-                                                options.ordered,
-                                                options.write_concern,
-                                                CommandType::InsertMany));
+        let (ids, exception, acknowledged) = try!(self.insert(docs,
+                                                              options.ordered,
+                                                              options.write_concern,
+                                                              CommandType::InsertMany));
 
         let mut map = BTreeMap::new();
         for i in 0..ids.len() {
@@ -673,9 +1891,18 @@ impl Collection {
             for error in &exc.write_errors {
                 map.remove(&(error.index as i64));
             }
+
+            // Ordered inserts stop at the first error, so later documents weren't inserted.
+            if options.ordered {
+                if let Some(first) = exc.write_errors.iter().map(|error| error.index).min() {
+                    let _ = map.split_off(&(first as i64));
+                }
+            }
         }
 
-        Ok(InsertManyResult::new(Some(map), exception))
+        let mut result = InsertManyResult::new(Some(map), exception);
+        result.acknowledged = acknowledged;
+        Ok(result)
     }
 
     // Sends a batch of delete ops to the server at once.
@@ -687,25 +1914,60 @@ impl Collection {
                    -> Result<BulkDeleteResult> {
 
         let wc = write_concern.unwrap_or(self.write_concern.clone());
+        try!(wc.validate());
+
+        let collation = models.iter().any(|model| model.collation.is_some());
+        if collation {
+            try!(Collection::check_acknowledged_collation(&wc));
+        }
 
         let mut deletes = Vec::new();
-        for model in models {
+        for model in &models {
             let mut delete = bson::Document::new();
-            delete.insert("q", Bson::Document(model.filter));
+            delete.insert("q", Bson::Document(model.filter.clone()));
             let limit = if model.multi { 0 } else { 1 };
             delete.insert("limit", Bson::I64(limit));
-            deletes.push(Bson::Document(delete));
+            if let Some(ref collation) = model.collation {
+                delete.insert("collation", Bson::Document(collation.to_bson()));
+            }
+            deletes.push(delete);
         }
 
         let mut cmd = bson::Document::new();
         cmd.insert("delete", Bson::String(self.name()));
-        cmd.insert("deletes", Bson::Array(deletes));
         if !ordered {
             cmd.insert("ordered", Bson::Boolean(ordered));
         }
         cmd.insert("writeConcern", Bson::Document(wc.to_bson()));
 
-        let result = try!(self.db.command(cmd, cmd_type, None));
+        // Deletes of every matching document can't be retried safely.
+        let retryable = models.iter().all(|model| !model.multi);
+
+        let namespace = self.namespace.to_owned();
+        let reply = try!(self.write_in_batches(cmd,
+                                               "deletes",
+                                               &deletes,
+                                               ordered,
+                                               &wc,
+                                               cmd_type,
+                                               retryable,
+                                               collation,
+                                               |client, range| {
+            let mut messages = Vec::new();
+            for model in &models[range] {
+                let flags = OpDeleteFlags { single_remove: !model.multi };
+                messages.push(try!(Message::new_delete(client.get_req_id(),
+                                                       namespace.to_owned(),
+                                                       flags,
+                                                       model.filter.clone())));
+            }
+            Ok(messages)
+        }));
+
+        let result = match reply {
+            Some(result) => result,
+            None => return Ok(BulkDeleteResult::unacknowledged()),
+        };
 
         // Intercept write exceptions and insert into the result
         let exception_res = BulkWriteException::validate_bulk_write_result(result.clone(), wc);
@@ -722,17 +1984,18 @@ impl Collection {
     fn delete(&self,
               filter: bson::Document,
               multi: bool,
-              write_concern: Option<WriteConcern>)
+              options: Option<DeleteOptions>)
               -> Result<DeleteResult> {
+        let options = options.unwrap_or_else(DeleteOptions::new);
         let cmd_type = if multi {
             CommandType::DeleteMany
         } else {
             CommandType::DeleteOne
         };
 
-        let result = try!(self.bulk_delete(vec![DeleteModel::new(filter, multi)],
+        let result = try!(self.bulk_delete(vec![DeleteModel::new(filter, multi, options.collation)],
                                            true,
-                                           write_concern,
+                                           options.write_concern,
                                            cmd_type));
 
         Ok(DeleteResult::with_bulk_result(result))
@@ -741,17 +2004,17 @@ impl Collection {
     /// Deletes a single document.
     pub fn delete_one(&self,
                       filter: bson::Document,
-                      write_concern: Option<WriteConcern>)
+                      options: Option<DeleteOptions>)
                       -> Result<DeleteResult> {
-        self.delete(filter, false, write_concern)
+        self.delete(filter, false, options)
     }
 
     /// Deletes multiple documents.
     pub fn delete_many(&self,
                        filter: bson::Document,
-                       write_concern: Option<WriteConcern>)
+                       options: Option<DeleteOptions>)
                        -> Result<DeleteResult> {
-        self.delete(filter, true, write_concern)
+        self.delete(filter, true, options)
     }
 
     // Sends a batch of replace and update ops to the server at once.
@@ -762,12 +2025,18 @@ impl Collection {
                    cmd_type: CommandType)
                    -> Result<BulkUpdateResult> {
         let wc = write_concern.unwrap_or(self.write_concern.clone());
+        try!(wc.validate());
+
+        let collation = models.iter().any(|model| model.collation.is_some());
+        if collation {
+            try!(Collection::check_acknowledged_collation(&wc));
+        }
 
         let mut updates = Vec::new();
-        for model in models {
+        for model in &models {
             let mut update = bson::Document::new();
-            update.insert("q", Bson::Document(model.filter));
-            update.insert("u", Bson::Document(model.update));
+            update.insert("q", Bson::Document(model.filter.clone()));
+            update.insert("u", Bson::Document(model.update.clone()));
             update.insert("upsert", Bson::Boolean(model.upsert));
             if !ordered {
                 update.insert("ordered", Bson::Boolean(ordered));
@@ -775,15 +2044,48 @@ impl Collection {
             if model.multi {
                 update.insert("multi", Bson::Boolean(model.multi));
             }
-            updates.push(Bson::Document(update));
+            if let Some(ref collation) = model.collation {
+                update.insert("collation", Bson::Document(collation.to_bson()));
+            }
+            updates.push(update);
         }
 
         let mut cmd = bson::Document::new();
         cmd.insert("update", Bson::String(self.name()));
-        cmd.insert("updates", Bson::Array(updates));
         cmd.insert("writeConcern", Bson::Document(wc.to_bson()));
 
-        let result = try!(self.db.command(cmd, cmd_type, None));
+        // Updates of every matching document can't be retried safely.
+        let retryable = models.iter().all(|model| !model.multi);
+
+        let namespace = self.namespace.to_owned();
+        let reply = try!(self.write_in_batches(cmd,
+                                               "updates",
+                                               &updates,
+                                               ordered,
+                                               &wc,
+                                               cmd_type,
+                                               retryable,
+                                               collation,
+                                               |client, range| {
+            let mut messages = Vec::new();
+            for model in &models[range] {
+                let flags = OpUpdateFlags {
+                    upsert: model.upsert,
+                    multi_update: model.multi,
+                };
+                messages.push(try!(Message::new_update(client.get_req_id(),
+                                                       namespace.to_owned(),
+                                                       flags,
+                                                       model.filter.clone(),
+                                                       model.update.clone())));
+            }
+            Ok(messages)
+        }));
+
+        let result = match reply {
+            Some(result) => result,
+            None => return Ok(BulkUpdateResult::unacknowledged()),
+        };
 
         // Intercept write exceptions and insert into the result
         let exception_res = BulkWriteException::validate_bulk_write_result(result.clone(), wc);
@@ -802,6 +2104,7 @@ impl Collection {
               update: bson::Document,
               upsert: bool,
               multi: bool,
+              collation: Option<Collation>,
               write_concern: Option<WriteConcern>)
               -> Result<UpdateResult> {
 
@@ -811,10 +2114,8 @@ impl Collection {
             CommandType::UpdateOne
         };
 
-        let result = try!(self.bulk_update(vec![UpdateModel::new(filter, update, upsert, multi)],
-                                           true,
-                                           write_concern,
-                                           cmd_type));
+        let model = UpdateModel::new(filter, update, upsert, multi, collation);
+        let result = try!(self.bulk_update(vec![model], true, write_concern, cmd_type));
 
         Ok(UpdateResult::with_bulk_result(result))
     }
@@ -831,6 +2132,7 @@ impl Collection {
                     replacement,
                     options.upsert,
                     false,
+                    options.collation,
                     options.write_concern)
     }
 
@@ -842,7 +2144,12 @@ impl Collection {
                       -> Result<UpdateResult> {
         let options = options.unwrap_or_else(|| UpdateOptions::new(false, None));
         try!(Collection::validate_update(&update));
-        self.update(filter, update, options.upsert, false, options.write_concern)
+        self.update(filter,
+                    update,
+                    options.upsert,
+                    false,
+                    options.collation,
+                    options.write_concern)
     }
 
     /// Updates multiple documents.
@@ -853,7 +2160,12 @@ impl Collection {
                        -> Result<UpdateResult> {
         let options = options.unwrap_or_else(|| UpdateOptions::new(false, None));
         try!(Collection::validate_update(&update));
-        self.update(filter, update, options.upsert, true, options.write_concern)
+        self.update(filter,
+                    update,
+                    options.upsert,
+                    true,
+                    options.collation,
+                    options.write_concern)
     }
 
     fn validate_replace(replacement: &bson::Document) -> Result<()> {
@@ -891,9 +2203,27 @@ impl Collection {
 
     /// Create multiple indexes.
     pub fn create_indexes(&self, models: Vec<IndexModel>) -> Result<Vec<String>> {
+        self.create_indexes_with_options(models, None)
+    }
+
+    /// Create multiple indexes, with options for the createIndexes command.
+    pub fn create_indexes_with_options(&self,
+                                       models: Vec<IndexModel>,
+                                       options: Option<CreateIndexesOptions>)
+                                       -> Result<Vec<String>> {
+        let opts = options.unwrap_or_else(CreateIndexesOptions::new);
         let mut names = Vec::with_capacity(models.len());
         let mut indexes = Vec::with_capacity(models.len());
 
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        let (selected, wire_version) = try!(self.select_server(&CommandType::CreateIndexes,
+                                                               &read_pref));
+        if models.iter().any(|model| model.options.collation.is_some()) {
+            try!(Collection::require_wire_version(wire_version,
+                                                  COLLATION_WIRE_VERSION,
+                                                  "collations"));
+        }
+
         for model in models {
             names.push(try!(model.name()));
             indexes.push(Bson::Document(try!(model.to_bson())));
@@ -902,7 +2232,25 @@ impl Collection {
         let mut cmd = bson::Document::new();
         cmd.insert("createIndexes", Bson::String(self.name()));
         cmd.insert("indexes", Bson::Array(indexes));
-        let result = try!(self.db.command(cmd, CommandType::CreateIndexes, None));
+
+        if let Some(ref commit_quorum) = opts.commit_quorum {
+            try!(Collection::require_wire_version(wire_version,
+                                                  COMMIT_QUORUM_WIRE_VERSION,
+                                                  "commitQuorum"));
+            cmd.insert("commitQuorum", commit_quorum.to_bson());
+        }
+        if let Some(max_time_ms) = opts.max_time_ms {
+            cmd.insert("maxTimeMS", Bson::I64(max_time_ms));
+        }
+        if let Some(write_concern) = opts.write_concern {
+            try!(write_concern.validate());
+            cmd.insert("writeConcern", Bson::Document(write_concern.to_bson()));
+        }
+
+        let result = try!(self.db.command_selected(selected,
+                                                   cmd,
+                                                   CommandType::CreateIndexes,
+                                                   read_pref));
 
         match result.get("errmsg") {
             Some(&Bson::String(ref msg)) => Err(OperationError(msg.to_owned())),
@@ -955,4 +2303,128 @@ impl Collection {
                                CommandType::ListIndexes,
                                self.read_preference.to_owned())
     }
+
+    /// List all indexes in the collection as index models.
+    pub fn list_index_models(&self) -> Result<Vec<IndexModel>> {
+        let mut models = Vec::new();
+        for index in try!(self.list_indexes()) {
+            models.push(try!(IndexModel::from_bson(&try!(index))));
+        }
+        Ok(models)
+    }
+
+    /// Makes the collection's indexes match the models, creating missing indexes and,
+    /// depending on the options, recreating changed indexes and dropping unmanaged ones.
+    /// Existing indexes are matched to models by their key patterns.
+    pub fn sync_indexes(&self,
+                        models: Vec<IndexModel>,
+                        options: Option<SyncOptions>)
+                        -> Result<SyncIndexesResult> {
+        let opts = options.unwrap_or_else(SyncOptions::new);
+
+        let mut existing = Vec::new();
+        for index in try!(self.list_indexes()) {
+            existing.push(try!(index));
+        }
+
+        let mut result = SyncIndexesResult::default();
+        result.dry_run = opts.dry_run;
+
+        let mut managed = vec![false; existing.len()];
+        let mut to_create = Vec::new();
+        let mut to_drop = Vec::new();
+
+        for model in models {
+            let name = try!(model.name());
+            let spec = try!(model.to_bson());
+
+            let position = existing.iter().position(|index| match index.get("key") {
+                Some(&Bson::Document(ref keys)) => sync::documents_match(&model.keys, keys),
+                _ => false,
+            });
+
+            match position {
+                Some(i) => {
+                    managed[i] = true;
+                    let explicit_name = model.options.name.is_some();
+                    if sync::options_differ(&spec, &existing[i], explicit_name) {
+                        let existing_name = match existing[i].get("name") {
+                            Some(&Bson::String(ref name)) => name.to_owned(),
+                            _ => name.to_owned(),
+                        };
+                        result.changed.push(existing_name.to_owned());
+                        if opts.recreate_changed {
+                            to_drop.push(existing_name);
+                            result.recreated.push(name);
+                            to_create.push(model);
+                        }
+                    } else {
+                        result.unchanged.push(name);
+                    }
+                }
+                None => {
+                    result.created.push(name);
+                    to_create.push(model);
+                }
+            }
+        }
+
+        // A dry run reports the changed indexes, leaving the caller to decide whether
+        // they are an error.
+        if !result.changed.is_empty() && !opts.recreate_changed && !opts.dry_run {
+            return Err(OperationError(format!("The options of indexes {} have changed; \
+                                               set recreate_changed to replace them.",
+                                              result.changed.join(", "))));
+        }
+
+        for (index, managed) in existing.iter().zip(managed) {
+            let name = match index.get("name") {
+                Some(&Bson::String(ref name)) => name.to_owned(),
+                _ => continue,
+            };
+
+            if managed || name == "_id_" {
+                continue;
+            }
+
+            if opts.drop_unmanaged {
+                to_drop.push(name.to_owned());
+                result.dropped.push(name);
+            } else {
+                result.unmanaged.push(name);
+            }
+        }
+
+        if opts.dry_run {
+            return Ok(result);
+        }
+
+        for name in to_drop {
+            try!(self.drop_index_string(name));
+        }
+
+        if !to_create.is_empty() {
+            try!(self.create_indexes(to_create));
+        }
+
+        Ok(result)
+    }
+}
+
+// Shifts the index reported in an upsert or write error document by the position of the
+// batch it was sent in.
+fn offset_index(doc: &Bson, offset: usize) -> Bson {
+    let mut doc = match *doc {
+        Bson::Document(ref doc) => doc.clone(),
+        ref other => return other.clone(),
+    };
+
+    let index = match doc.get("index") {
+        Some(&Bson::I32(index)) => Bson::I32(index + offset as i32),
+        Some(&Bson::I64(index)) => Bson::I64(index + offset as i64),
+        _ => return Bson::Document(doc),
+    };
+
+    doc.insert("index", index);
+    Bson::Document(doc)
 }
@@ -1,7 +1,7 @@
 //! Options for collection-level operations.
 use bson::{self, Bson};
 use cursor;
-use common::{ReadPreference, WriteConcern};
+use common::{ReadConcern, ReadPreference, WriteConcern};
 use Error::ArgumentError;
 use Result;
 
@@ -20,26 +20,111 @@ pub enum ReturnDocument {
     After,
 }
 
+/// Describes how much information an explain operation should return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplainVerbosity {
+    /// The winning plan chosen by the query optimizer.
+    QueryPlanner,
+    /// The winning plan, along with statistics from executing it.
+    ExecutionStats,
+    /// Execution statistics for the winning plan and all rejected candidate plans.
+    AllPlansExecution,
+}
+
+/// Describes where the output of a map-reduce operation is written. Output collections
+/// are in the same database as the input collection unless `db` is given.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapReduceOutput {
+    /// Returns the results in memory instead of writing them to a collection.
+    Inline,
+    /// Replaces the contents of the collection with the results.
+    Replace { collection: String, db: Option<String> },
+    /// Merges the results into the collection, overwriting documents with the same key.
+    Merge { collection: String, db: Option<String> },
+    /// Merges the results into the collection, running the reduce function on documents
+    /// with the same key.
+    Reduce { collection: String, db: Option<String> },
+}
+
+/// Determines which documents a collection's validator checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationLevel {
+    /// No documents are validated.
+    Off,
+    /// All inserts and updates are validated.
+    Strict,
+    /// Updates to existing documents that are already invalid are not validated.
+    Moderate,
+}
+
+/// Determines what happens when a document fails validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationAction {
+    /// The write is rejected.
+    Error,
+    /// The write is allowed, and a warning is logged by the server.
+    Warn,
+}
+
+/// The first wire version supporting collations, corresponding to MongoDB 3.4.
+pub const COLLATION_WIRE_VERSION: i64 = 5;
+
+/// Language-specific rules for comparing strings, such as rules for letter case and
+/// accent marks. Collations require MongoDB 3.4 or later.
+///
+/// A collation with a `strength` of 1 or 2 compares strings case-insensitively.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Collation {
+    /// The ICU locale, such as `en` or `fr_CA`, or `simple` for binary comparison.
+    pub locale: String,
+    /// Whether to include case comparison at strength levels 1 and 2.
+    pub case_level: Option<bool>,
+    /// The sort order of case differences: `upper`, `lower` or `off`.
+    pub case_first: Option<String>,
+    /// The level of comparison to perform, from 1 (base characters) to 5 (identical).
+    pub strength: Option<i32>,
+    /// Whether to compare numeric strings as numbers.
+    pub numeric_ordering: Option<bool>,
+    /// Whether whitespace and punctuation are considered base characters:
+    /// `non-ignorable` or `shifted`.
+    pub alternate: Option<String>,
+    /// The characters ignored when `alternate` is `shifted`: `punct` or `space`.
+    pub max_variable: Option<String>,
+    /// Whether to check if text requires normalization.
+    pub normalization: Option<bool>,
+    /// Whether strings with diacritics sort from the back of the string.
+    pub backwards: Option<bool>,
+}
+
 /// Marker interface for writes that can be batched together.
 #[derive(Debug, Clone)]
 pub enum WriteModel {
     InsertOne { document: bson::Document },
-    DeleteOne { filter: bson::Document },
-    DeleteMany { filter: bson::Document },
+    DeleteOne {
+        filter: bson::Document,
+        collation: Option<Collation>,
+    },
+    DeleteMany {
+        filter: bson::Document,
+        collation: Option<Collation>,
+    },
     ReplaceOne {
         filter: bson::Document,
         replacement: bson::Document,
         upsert: bool,
+        collation: Option<Collation>,
     },
     UpdateOne {
         filter: bson::Document,
         update: bson::Document,
         upsert: bool,
+        collation: Option<Collation>,
     },
     UpdateMany {
         filter: bson::Document,
         update: bson::Document,
         upsert: bool,
+        collation: Option<Collation>,
     },
 }
 
@@ -51,6 +136,16 @@ pub struct AggregateOptions {
     pub batch_size: i32,
     pub max_time_ms: Option<i64>,
     pub read_preference: Option<ReadPreference>,
+    pub read_concern: Option<ReadConcern>,
+    pub collation: Option<Collation>,
+    pub bypass_document_validation: Option<bool>,
+    pub comment: Option<String>,
+    pub hint: Option<String>,
+    pub hint_doc: Option<bson::Document>,
+    /// Variables that can be referenced as `$$name` within the pipeline.
+    pub let_vars: Option<bson::Document>,
+    /// The write concern for pipelines ending in `$out` or `$merge`.
+    pub write_concern: Option<WriteConcern>,
 }
 
 /// Options for count queries.
@@ -62,6 +157,31 @@ pub struct CountOptions {
     pub hint_doc: Option<bson::Document>,
     pub max_time_ms: Option<i64>,
     pub read_preference: Option<ReadPreference>,
+    pub read_concern: Option<ReadConcern>,
+    pub collation: Option<Collation>,
+}
+
+/// Changes to make to a collection with the collMod command.
+#[derive(Clone, Default)]
+pub struct CollModOptions {
+    /// A new document validator, replacing the current one.
+    pub validator: Option<bson::Document>,
+    pub validation_level: Option<ValidationLevel>,
+    pub validation_action: Option<ValidationAction>,
+    /// Changes to an existing index.
+    pub index: Option<IndexModification>,
+    /// The new maximum size in bytes of a capped collection.
+    pub capped_size: Option<i64>,
+    /// The new maximum number of documents in a capped collection.
+    pub capped_max: Option<i64>,
+    pub write_concern: Option<WriteConcern>,
+}
+
+/// Options for delete operations.
+#[derive(Clone, Default)]
+pub struct DeleteOptions {
+    pub collation: Option<Collation>,
+    pub write_concern: Option<WriteConcern>,
 }
 
 /// Options for distinct queries.
@@ -69,6 +189,8 @@ pub struct CountOptions {
 pub struct DistinctOptions {
     pub max_time_ms: Option<i64>,
     pub read_preference: Option<ReadPreference>,
+    pub read_concern: Option<ReadConcern>,
+    pub collation: Option<Collation>,
 }
 
 /// Options for collection queries.
@@ -83,10 +205,16 @@ pub struct FindOptions {
     pub batch_size: i32,
     pub comment: Option<String>,
     pub max_time_ms: Option<i64>,
+    /// For `TailableAwait` cursors, the maximum amount of time to wait for new documents
+    /// before `Cursor::try_next` returns `Ok(None)`.
+    pub max_await_time_ms: Option<i64>,
     pub modifiers: Option<bson::Document>,
     pub projection: Option<bson::Document>,
     pub sort: Option<bson::Document>,
     pub read_preference: Option<ReadPreference>,
+    pub read_concern: Option<ReadConcern>,
+    /// Queries with a collation are sent as `find` commands.
+    pub collation: Option<Collation>,
 }
 
 /// Options for `findOneAndDelete` operations.
@@ -96,6 +224,9 @@ pub struct FindOneAndDeleteOptions {
     pub projection: Option<bson::Document>,
     pub sort: Option<bson::Document>,
     pub write_concern: Option<WriteConcern>,
+    pub collation: Option<Collation>,
+    pub hint: Option<String>,
+    pub hint_doc: Option<bson::Document>,
 }
 
 /// Options for `findOneAndUpdate` operations.
@@ -107,6 +238,12 @@ pub struct FindOneAndUpdateOptions {
     pub sort: Option<bson::Document>,
     pub upsert: bool,
     pub write_concern: Option<WriteConcern>,
+    pub collation: Option<Collation>,
+    /// Filters determining which array elements an update applies to.
+    pub array_filters: Option<Vec<bson::Document>>,
+    pub bypass_document_validation: Option<bool>,
+    pub hint: Option<String>,
+    pub hint_doc: Option<bson::Document>,
 }
 
 /// Options for index operations.
@@ -132,6 +269,34 @@ pub struct IndexOptions {
     pub min: Option<f64>,
     // Options for geoHaystack indexes
     pub bucket_size: Option<i32>,
+    pub collation: Option<Collation>,
+    /// Only index documents matching this filter.
+    pub partial_filter_expression: Option<bson::Document>,
+    /// The fields to include in or exclude from a wildcard index.
+    pub wildcard_projection: Option<bson::Document>,
+    /// Whether the index is hidden from the query planner.
+    pub hidden: Option<bool>,
+}
+
+/// The number of data-bearing replica set members that must finish building an index
+/// before it is ready.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommitQuorum {
+    Nodes(i32),
+    Majority,
+    /// All data-bearing voting members, the server's default.
+    VotingMembers,
+    /// The members matching a replica set tag set.
+    Tag(String),
+}
+
+/// Options for createIndexes operations.
+#[derive(Clone, Default)]
+pub struct CreateIndexesOptions {
+    /// Requires MongoDB 4.4 or later.
+    pub commit_quorum: Option<CommitQuorum>,
+    pub max_time_ms: Option<i64>,
+    pub write_concern: Option<WriteConcern>,
 }
 
 /// A single index model.
@@ -141,6 +306,17 @@ pub struct IndexModel {
     pub options: IndexOptions,
 }
 
+/// Changes to an existing index, identified by either its keys or its name.
+#[derive(Clone, Default)]
+pub struct IndexModification {
+    pub keys: Option<bson::Document>,
+    pub name: Option<String>,
+    /// The new time to live of documents in a TTL index.
+    pub expire_after_seconds: Option<i32>,
+    /// Whether to hide the index from the query planner.
+    pub hidden: Option<bool>,
+}
+
 /// Options for insertMany operations.
 #[derive(Clone, Default)]
 pub struct InsertManyOptions {
@@ -148,11 +324,47 @@ pub struct InsertManyOptions {
     pub write_concern: Option<WriteConcern>,
 }
 
+/// Options for mapReduce operations.
+#[derive(Clone, Default)]
+pub struct MapReduceOptions {
+    pub out: MapReduceOutput,
+    pub query: Option<bson::Document>,
+    pub sort: Option<bson::Document>,
+    pub limit: Option<i64>,
+    /// A JavaScript function applied to the output of the reduce function.
+    pub finalize: Option<Bson>,
+    /// Global variables accessible in the map, reduce and finalize functions.
+    pub scope: Option<bson::Document>,
+    pub js_mode: Option<bool>,
+    pub verbose: Option<bool>,
+    pub bypass_document_validation: Option<bool>,
+    pub max_time_ms: Option<i64>,
+    pub collation: Option<Collation>,
+    pub read_preference: Option<ReadPreference>,
+    pub read_concern: Option<ReadConcern>,
+    pub write_concern: Option<WriteConcern>,
+}
+
+/// Options for synchronizing a collection's indexes with a set of index models.
+#[derive(Clone, Default)]
+pub struct SyncOptions {
+    /// Whether to drop indexes that aren't in the desired set. The `_id_` index is never
+    /// dropped.
+    pub drop_unmanaged: bool,
+    /// Whether to drop and recreate indexes whose options have changed. If false, changed
+    /// indexes cause the synchronization to fail before any index is modified, unless
+    /// it is a dry run, which reports them in the result's `changed` indexes.
+    pub recreate_changed: bool,
+    /// Whether to only report what would change, without modifying any indexes.
+    pub dry_run: bool,
+}
+
 /// Options for update operations.
 #[derive(Clone, Default)]
 pub struct UpdateOptions {
     pub upsert: bool,
     pub write_concern: Option<WriteConcern>,
+    pub collation: Option<Collation>,
 }
 
 pub type ReplaceOptions = UpdateOptions;
@@ -165,6 +377,121 @@ impl AggregateOptions {
             batch_size: cursor::DEFAULT_BATCH_SIZE,
             max_time_ms: None,
             read_preference: None,
+            read_concern: None,
+            collation: None,
+            bypass_document_validation: None,
+            comment: None,
+            hint: None,
+            hint_doc: None,
+            let_vars: None,
+            write_concern: None,
+        }
+    }
+}
+
+impl Collation {
+    /// Creates a collation for the locale with the locale's default rules.
+    pub fn new(locale: &str) -> Collation {
+        Collation { locale: String::from(locale), ..Default::default() }
+    }
+
+    /// Converts the collation to its BSON document representation.
+    pub fn to_bson(&self) -> bson::Document {
+        let mut doc = bson::Document::new();
+        doc.insert("locale", Bson::String(self.locale.to_owned()));
+
+        if let Some(val) = self.case_level {
+            doc.insert("caseLevel", Bson::Boolean(val));
+        }
+        if let Some(ref val) = self.case_first {
+            doc.insert("caseFirst", Bson::String(val.to_owned()));
+        }
+        if let Some(val) = self.strength {
+            doc.insert("strength", Bson::I32(val));
+        }
+        if let Some(val) = self.numeric_ordering {
+            doc.insert("numericOrdering", Bson::Boolean(val));
+        }
+        if let Some(ref val) = self.alternate {
+            doc.insert("alternate", Bson::String(val.to_owned()));
+        }
+        if let Some(ref val) = self.max_variable {
+            doc.insert("maxVariable", Bson::String(val.to_owned()));
+        }
+        if let Some(val) = self.normalization {
+            doc.insert("normalization", Bson::Boolean(val));
+        }
+        if let Some(val) = self.backwards {
+            doc.insert("backwards", Bson::Boolean(val));
+        }
+
+        doc
+    }
+
+    /// Parses a collation from its BSON document representation.
+    pub fn from_bson(doc: &bson::Document) -> Result<Collation> {
+        let locale = match get_string(doc, "locale") {
+            Some(locale) => locale,
+            None => return Err(ArgumentError(String::from("Collations must have a locale."))),
+        };
+
+        Ok(Collation {
+            locale: locale,
+            case_level: get_bool(doc, "caseLevel"),
+            case_first: get_string(doc, "caseFirst"),
+            strength: get_i32(doc, "strength"),
+            numeric_ordering: get_bool(doc, "numericOrdering"),
+            alternate: get_string(doc, "alternate"),
+            max_variable: get_string(doc, "maxVariable"),
+            normalization: get_bool(doc, "normalization"),
+            backwards: get_bool(doc, "backwards"),
+        })
+    }
+}
+
+impl CollModOptions {
+    pub fn new() -> CollModOptions {
+        Default::default()
+    }
+
+    /// Returns the collMod command for the collection with these changes.
+    pub fn to_command(&self, coll_name: &str) -> Result<bson::Document> {
+        let mut doc = doc! { "collMod" => coll_name };
+
+        if let Some(ref validator) = self.validator {
+            doc.insert("validator", Bson::Document(validator.to_owned()));
+        }
+        if let Some(level) = self.validation_level {
+            doc.insert("validationLevel", Bson::String(String::from(level.to_str())));
+        }
+        if let Some(action) = self.validation_action {
+            doc.insert("validationAction", Bson::String(String::from(action.to_str())));
+        }
+        if let Some(ref index) = self.index {
+            doc.insert("index", Bson::Document(try!(index.to_bson())));
+        }
+        if let Some(size) = self.capped_size {
+            doc.insert("cappedSize", Bson::I64(size));
+        }
+        if let Some(max) = self.capped_max {
+            doc.insert("cappedMax", Bson::I64(max));
+        }
+        if let Some(ref write_concern) = self.write_concern {
+            doc.insert("writeConcern", Bson::Document(write_concern.to_bson()));
+        }
+
+        Ok(doc)
+    }
+}
+
+impl CommitQuorum {
+    /// Returns the commit quorum as it is sent to the server.
+    pub fn to_bson(&self) -> Bson {
+        match *self {
+            CommitQuorum::Nodes(n) => Bson::I32(n),
+            CommitQuorum::Majority => Bson::String(String::from("majority")),
+            CommitQuorum::VotingMembers => Bson::String(String::from("votingMembers")),
+            CommitQuorum::Tag(ref tag) => Bson::String(tag.to_owned()),
         }
     }
 }
@@ -178,6 +505,23 @@ impl CountOptions {
             hint_doc: None,
             max_time_ms: None,
             read_preference: None,
+            read_concern: None,
+            collation: None,
+        }
+    }
+}
+
+impl CreateIndexesOptions {
+    pub fn new() -> CreateIndexesOptions {
+        Default::default()
+    }
+}
+
+impl DeleteOptions {
+    pub fn new() -> DeleteOptions {
+        DeleteOptions {
+            collation: None,
+            write_concern: None,
         }
     }
 }
@@ -187,6 +531,8 @@ impl DistinctOptions {
         DistinctOptions {
             max_time_ms: None,
             read_preference: None,
+            read_concern: None,
+            collation: None,
         }
     }
 }
@@ -210,10 +556,13 @@ impl FindOptions {
             batch_size: cursor::DEFAULT_BATCH_SIZE,
             comment: None,
             max_time_ms: None,
+            max_await_time_ms: None,
             modifiers: None,
             projection: None,
             sort: None,
             read_preference: None,
+            read_concern: None,
+            collation: None,
         }
     }
 
@@ -232,6 +581,9 @@ impl FindOneAndDeleteOptions {
             projection: None,
             sort: None,
             write_concern: None,
+            collation: None,
+            hint: None,
+            hint_doc: None,
         }
     }
 }
@@ -251,6 +603,11 @@ impl FindOneAndUpdateOptions {
             sort: None,
             upsert: false,
             write_concern: None,
+            collation: None,
+            array_filters: None,
+            bypass_document_validation: None,
+            hint: None,
+            hint_doc: None,
         }
     }
 }
@@ -274,6 +631,10 @@ impl IndexOptions {
             max: None,
             min: None,
             bucket_size: None,
+            collation: None,
+            partial_filter_expression: None,
+            wildcard_projection: None,
+            hidden: None,
         }
     }
 }
@@ -369,6 +730,84 @@ impl IndexModel {
         if let Some(ref val) = self.options.bucket_size {
             doc.insert("bucketSize", Bson::I32(*val));
         }
+        if let Some(ref val) = self.options.collation {
+            doc.insert("collation", Bson::Document(val.to_bson()));
+        }
+        if let Some(ref val) = self.options.partial_filter_expression {
+            doc.insert("partialFilterExpression", Bson::Document(val.clone()));
+        }
+        if let Some(ref val) = self.options.wildcard_projection {
+            doc.insert("wildcardProjection", Bson::Document(val.clone()));
+        }
+        if let Some(ref val) = self.options.hidden {
+            doc.insert("hidden", Bson::Boolean(*val));
+        }
+
+        Ok(doc)
+    }
+
+    /// Parses an index specification, as returned by `listIndexes`, into a model.
+    pub fn from_bson(doc: &bson::Document) -> Result<IndexModel> {
+        let keys = match doc.get("key") {
+            Some(&Bson::Document(ref keys)) => keys.clone(),
+            _ => {
+                return Err(ArgumentError(String::from("Index specifications must contain a \
+                                                       key document.")))
+            }
+        };
+
+        let mut options = IndexOptions::new();
+        options.background = get_bool(doc, "background");
+        options.expire_after_seconds = get_i32(doc, "expireAfterSeconds");
+        options.name = get_string(doc, "name");
+        options.sparse = get_bool(doc, "sparse");
+        options.storage_engine = get_string(doc, "storageEngine");
+        options.unique = get_bool(doc, "unique");
+        options.version = get_i32(doc, "v");
+        options.default_language = get_string(doc, "default_language");
+        options.language_override = get_string(doc, "language_override");
+        options.text_version = get_i32(doc, "textIndexVersion");
+        options.weights = get_document(doc, "weights");
+        options.sphere_version = get_i32(doc, "2dsphereIndexVersion");
+        options.bits = get_i32(doc, "bits");
+        options.max = get_f64(doc, "max");
+        options.min = get_f64(doc, "min");
+        options.bucket_size = get_i32(doc, "bucketSize");
+        options.collation = match get_document(doc, "collation") {
+            Some(collation) => Some(try!(Collation::from_bson(&collation))),
+            None => None,
+        };
+        options.partial_filter_expression = get_document(doc, "partialFilterExpression");
+        options.wildcard_projection = get_document(doc, "wildcardProjection");
+        options.hidden = get_bool(doc, "hidden");
+
+        Ok(IndexModel::new(keys, Some(options)))
+    }
+}
+
+impl IndexModification {
+    pub fn new() -> IndexModification {
+        Default::default()
+    }
+
+    /// Returns the `index` document for the collMod command.
+    pub fn to_bson(&self) -> Result<bson::Document> {
+        let mut doc = match (&self.keys, &self.name) {
+            (&Some(ref keys), &None) => doc! { "keyPattern" => (keys.to_owned()) },
+            (&None, &Some(ref name)) => doc! { "name" => (name.to_owned()) },
+            _ => {
+                return Err(ArgumentError(String::from("An index modification must identify \
+                                                       the index by either its keys or its \
+                                                       name.")))
+            }
+        };
+
+        if let Some(expire) = self.expire_after_seconds {
+            doc.insert("expireAfterSeconds", Bson::I32(expire));
+        }
+        if let Some(hidden) = self.hidden {
+            doc.insert("hidden", Bson::Boolean(hidden));
+        }
 
         Ok(doc)
     }
@@ -383,6 +822,66 @@ impl InsertManyOptions {
     }
 }
 
+impl Default for MapReduceOutput {
+    fn default() -> Self {
+        MapReduceOutput::Inline
+    }
+}
+
+impl MapReduceOutput {
+    /// Returns the `out` document for the mapReduce command.
+    pub fn to_bson(&self) -> bson::Document {
+        let (action, collection, db) = match *self {
+            MapReduceOutput::Inline => return doc! { "inline" => 1 },
+            MapReduceOutput::Replace { ref collection, ref db } => ("replace", collection, db),
+            MapReduceOutput::Merge { ref collection, ref db } => ("merge", collection, db),
+            MapReduceOutput::Reduce { ref collection, ref db } => ("reduce", collection, db),
+        };
+
+        let mut doc = bson::Document::new();
+        doc.insert(action, Bson::String(collection.to_owned()));
+        if let Some(ref db) = *db {
+            doc.insert("db", Bson::String(db.to_owned()));
+        }
+        doc
+    }
+}
+
+impl MapReduceOptions {
+    pub fn new() -> MapReduceOptions {
+        Default::default()
+    }
+}
+
+impl ExplainVerbosity {
+    pub fn to_str(&self) -> &'static str {
+        match *self {
+            ExplainVerbosity::QueryPlanner => "queryPlanner",
+            ExplainVerbosity::ExecutionStats => "executionStats",
+            ExplainVerbosity::AllPlansExecution => "allPlansExecution",
+        }
+    }
+}
+
+impl ValidationLevel {
+    pub fn to_str(&self) -> &'static str {
+        match *self {
+            ValidationLevel::Off => "off",
+            ValidationLevel::Strict => "strict",
+            ValidationLevel::Moderate => "moderate",
+        }
+    }
+}
+
+impl ValidationAction {
+    pub fn to_str(&self) -> &'static str {
+        match *self {
+            ValidationAction::Error => "error",
+            ValidationAction::Warn => "warn",
+        }
+    }
+}
+
 impl ReturnDocument {
     pub fn to_bool(&self) -> bool {
         match *self {
@@ -392,11 +891,57 @@ impl ReturnDocument {
     }
 }
 
+impl SyncOptions {
+    pub fn new() -> SyncOptions {
+        Default::default()
+    }
+}
+
 impl UpdateOptions {
     pub fn new(upsert: bool, write_concern: Option<WriteConcern>) -> UpdateOptions {
         UpdateOptions {
             upsert: upsert,
             write_concern: write_concern,
+            collation: None,
         }
     }
 }
+
+fn get_bool(doc: &bson::Document, key: &str) -> Option<bool> {
+    match doc.get(key) {
+        Some(&Bson::Boolean(val)) => Some(val),
+        _ => None,
+    }
+}
+
+fn get_i32(doc: &bson::Document, key: &str) -> Option<i32> {
+    match doc.get(key) {
+        Some(&Bson::I32(val)) => Some(val),
+        Some(&Bson::I64(val)) => Some(val as i32),
+        Some(&Bson::FloatingPoint(val)) => Some(val as i32),
+        _ => None,
+    }
+}
+
+fn get_f64(doc: &bson::Document, key: &str) -> Option<f64> {
+    match doc.get(key) {
+        Some(&Bson::I32(val)) => Some(val as f64),
+        Some(&Bson::I64(val)) => Some(val as f64),
+        Some(&Bson::FloatingPoint(val)) => Some(val),
+        _ => None,
+    }
+}
+
+fn get_string(doc: &bson::Document, key: &str) -> Option<String> {
+    match doc.get(key) {
+        Some(&Bson::String(ref val)) => Some(val.to_owned()),
+        _ => None,
+    }
+}
+
+fn get_document(doc: &bson::Document, key: &str) -> Option<bson::Document> {
+    match doc.get(key) {
+        Some(&Bson::Document(ref val)) => Some(val.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,452 @@
+//! A builder for aggregation pipelines with typed stages.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate bson;
+//! # extern crate mongodb;
+//! #
+//! # use bson::Bson;
+//! # use mongodb::{Client, ThreadedClient};
+//! # use mongodb::coll::pipeline::{Accumulator, Pipeline};
+//! # use mongodb::db::ThreadedDatabase;
+//! #
+//! # fn main() {
+//! # let client = Client::connect("localhost", 27017).unwrap();
+//! let total = Accumulator::Sum(Bson::String(String::from("$price")));
+//!
+//! let pipeline = Pipeline::new()
+//!     .match_(doc! { "status" => "shipped" })
+//!     .group(Bson::String(String::from("$customer")), vec![(String::from("total"), total)])
+//!     .sort(doc! { "total" => (-1) })
+//!     .limit(10)
+//!     .build()
+//!     .unwrap();
+//!
+//! let cursor = client.db("shop").collection("orders").aggregate(pipeline, None).unwrap();
+//! # }
+//! ```
+use bson::{self, Bson};
+use Error::ArgumentError;
+use Result;
+
+/// An accumulator expression used to compute a field in a `$group` or `$bucket` stage.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Accumulator {
+    Sum(Bson),
+    Avg(Bson),
+    First(Bson),
+    Last(Bson),
+    Max(Bson),
+    Min(Bson),
+    Push(Bson),
+    AddToSet(Bson),
+    StdDevPop(Bson),
+    StdDevSamp(Bson),
+}
+
+/// Options for an `$unwind` stage.
+#[derive(Clone, Debug, Default)]
+pub struct UnwindOptions {
+    /// The name of a new field to hold the array index of each element.
+    pub include_array_index: Option<String>,
+    /// Whether to output documents whose array is null, missing or empty.
+    pub preserve_null_and_empty_arrays: Option<bool>,
+}
+
+/// Options for a `$bucket` stage.
+#[derive(Clone, Debug, Default)]
+pub struct BucketOptions {
+    /// The bucket for documents that fall outside of the boundaries.
+    pub default: Option<Bson>,
+    /// The fields to compute for each bucket, in addition to `_id`.
+    pub output: Vec<(String, Accumulator)>,
+}
+
+#[derive(Clone, Debug)]
+enum Stage {
+    Match(bson::Document),
+    Project(bson::Document),
+    Group {
+        id: Bson,
+        fields: Vec<(String, Accumulator)>,
+    },
+    Sort(bson::Document),
+    Limit(i64),
+    Skip(i64),
+    Unwind {
+        path: String,
+        options: UnwindOptions,
+    },
+    Lookup {
+        from: String,
+        local_field: String,
+        foreign_field: String,
+        as_field: String,
+    },
+    Facet(Vec<(String, Pipeline)>),
+    Bucket {
+        group_by: Bson,
+        boundaries: Vec<Bson>,
+        options: BucketOptions,
+    },
+    AddFields(bson::Document),
+    Count(String),
+    Out(String),
+}
+
+/// Builds an aggregation pipeline one stage at a time.
+///
+/// Stages are validated when the pipeline is built, so mistakes such as a `$out` stage
+/// that is not last are reported before anything is sent to the server.
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+}
+
+impl Accumulator {
+    /// Returns the accumulator expression as a document, such as `{ $sum: 1 }`.
+    pub fn to_bson(&self) -> bson::Document {
+        let (operator, expr) = match *self {
+            Accumulator::Sum(ref expr) => ("$sum", expr),
+            Accumulator::Avg(ref expr) => ("$avg", expr),
+            Accumulator::First(ref expr) => ("$first", expr),
+            Accumulator::Last(ref expr) => ("$last", expr),
+            Accumulator::Max(ref expr) => ("$max", expr),
+            Accumulator::Min(ref expr) => ("$min", expr),
+            Accumulator::Push(ref expr) => ("$push", expr),
+            Accumulator::AddToSet(ref expr) => ("$addToSet", expr),
+            Accumulator::StdDevPop(ref expr) => ("$stdDevPop", expr),
+            Accumulator::StdDevSamp(ref expr) => ("$stdDevSamp", expr),
+        };
+
+        let mut doc = bson::Document::new();
+        doc.insert(operator, expr.clone());
+        doc
+    }
+}
+
+impl UnwindOptions {
+    pub fn new() -> UnwindOptions {
+        Default::default()
+    }
+}
+
+impl BucketOptions {
+    pub fn new() -> BucketOptions {
+        Default::default()
+    }
+}
+
+impl Stage {
+    fn name(&self) -> &'static str {
+        match *self {
+            Stage::Match(_) => "$match",
+            Stage::Project(_) => "$project",
+            Stage::Group { .. } => "$group",
+            Stage::Sort(_) => "$sort",
+            Stage::Limit(_) => "$limit",
+            Stage::Skip(_) => "$skip",
+            Stage::Unwind { .. } => "$unwind",
+            Stage::Lookup { .. } => "$lookup",
+            Stage::Facet(_) => "$facet",
+            Stage::Bucket { .. } => "$bucket",
+            Stage::AddFields(_) => "$addFields",
+            Stage::Count(_) => "$count",
+            Stage::Out(_) => "$out",
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match *self {
+            Stage::Group { ref fields, .. } => {
+                for &(ref name, _) in fields {
+                    try!(Stage::validate_field_name("$group", name));
+                    if name == "_id" {
+                        return Err(ArgumentError(String::from("The $group _id must be given as \
+                                                               the group key, not as an \
+                                                               accumulated field.")));
+                    }
+                }
+                Ok(())
+            }
+            Stage::Sort(ref sort) => {
+                if sort.is_empty() {
+                    return Err(ArgumentError(String::from("A $sort stage requires at least one \
+                                                           field.")));
+                }
+                for (field, order) in sort.iter() {
+                    match *order {
+                        Bson::I32(1) | Bson::I32(-1) | Bson::I64(1) | Bson::I64(-1) |
+                        Bson::Document(_) => (),
+                        _ => {
+                            return Err(ArgumentError(format!("The $sort order for field '{}' \
+                                                              must be 1, -1 or a $meta \
+                                                              expression.",
+                                                             field)))
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Stage::Limit(limit) if limit <= 0 => {
+                Err(ArgumentError(String::from("The $limit value must be positive.")))
+            }
+            Stage::Skip(skip) if skip < 0 => {
+                Err(ArgumentError(String::from("The $skip value must not be negative.")))
+            }
+            Stage::Unwind { ref path, .. } if !path.starts_with('$') => {
+                Err(ArgumentError(format!("The $unwind path '{}' must be prefixed with '$'.",
+                                          path)))
+            }
+            Stage::Lookup { ref as_field, .. } => Stage::validate_field_name("$lookup", as_field),
+            Stage::Facet(ref facets) => {
+                if facets.is_empty() {
+                    return Err(ArgumentError(String::from("A $facet stage requires at least one \
+                                                           sub-pipeline.")));
+                }
+                for &(ref name, ref pipeline) in facets {
+                    try!(Stage::validate_field_name("$facet", name));
+                    for stage in &pipeline.stages {
+                        match *stage {
+                            Stage::Facet(_) | Stage::Out(_) => {
+                                return Err(ArgumentError(format!("A {} stage can't be used \
+                                                                  inside $facet.",
+                                                                 stage.name())))
+                            }
+                            _ => try!(stage.validate()),
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Stage::Bucket { ref boundaries, ref options, .. } => {
+                if boundaries.len() < 2 {
+                    return Err(ArgumentError(String::from("A $bucket stage requires at least \
+                                                           two boundaries.")));
+                }
+                for &(ref name, _) in &options.output {
+                    try!(Stage::validate_field_name("$bucket", name));
+                }
+                Ok(())
+            }
+            Stage::Count(ref name) => Stage::validate_field_name("$count", name),
+            Stage::Out(ref coll) if coll.is_empty() => {
+                Err(ArgumentError(String::from("The $out stage requires a collection name.")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Output field names can't be empty, start with '$' or contain '.'.
+    fn validate_field_name(stage: &str, name: &str) -> Result<()> {
+        if name.is_empty() || name.starts_with('$') || name.contains('.') {
+            Err(ArgumentError(format!("'{}' is not a valid output field name for {}.",
+                                      name,
+                                      stage)))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn accumulated_fields(id: Bson, fields: Vec<(String, Accumulator)>) -> bson::Document {
+        let mut doc = bson::Document::new();
+        doc.insert("_id", id);
+        for (name, accumulator) in fields {
+            doc.insert(name, Bson::Document(accumulator.to_bson()));
+        }
+        doc
+    }
+
+    fn to_bson(self) -> bson::Document {
+        let name = self.name();
+        let value = match self {
+            Stage::Match(filter) => Bson::Document(filter),
+            Stage::Project(projection) => Bson::Document(projection),
+            Stage::Group { id, fields } => {
+                Bson::Document(Stage::accumulated_fields(id, fields))
+            }
+            Stage::Sort(sort) => Bson::Document(sort),
+            Stage::Limit(limit) => Bson::I64(limit),
+            Stage::Skip(skip) => Bson::I64(skip),
+            Stage::Unwind { path, options } => {
+                if options.include_array_index.is_none() &&
+                   options.preserve_null_and_empty_arrays.is_none() {
+                    Bson::String(path)
+                } else {
+                    let mut doc = doc! { "path" => path };
+                    if let Some(index) = options.include_array_index {
+                        doc.insert("includeArrayIndex", Bson::String(index));
+                    }
+                    if let Some(preserve) = options.preserve_null_and_empty_arrays {
+                        doc.insert("preserveNullAndEmptyArrays", Bson::Boolean(preserve));
+                    }
+                    Bson::Document(doc)
+                }
+            }
+            Stage::Lookup { from, local_field, foreign_field, as_field } => {
+                Bson::Document(doc! {
+                    "from" => from,
+                    "localField" => local_field,
+                    "foreignField" => foreign_field,
+                    "as" => as_field
+                })
+            }
+            Stage::Facet(facets) => {
+                let mut doc = bson::Document::new();
+                for (name, pipeline) in facets {
+                    let stages = pipeline.stages
+                        .into_iter()
+                        .map(|stage| Bson::Document(stage.to_bson()))
+                        .collect();
+                    doc.insert(name, Bson::Array(stages));
+                }
+                Bson::Document(doc)
+            }
+            Stage::Bucket { group_by, boundaries, options } => {
+                let mut doc = bson::Document::new();
+                doc.insert("groupBy", group_by);
+                doc.insert("boundaries", Bson::Array(boundaries));
+                if let Some(default) = options.default {
+                    doc.insert("default", default);
+                }
+                if !options.output.is_empty() {
+                    let mut output = bson::Document::new();
+                    for (name, accumulator) in options.output {
+                        output.insert(name, Bson::Document(accumulator.to_bson()));
+                    }
+                    doc.insert("output", Bson::Document(output));
+                }
+                Bson::Document(doc)
+            }
+            Stage::AddFields(fields) => Bson::Document(fields),
+            Stage::Count(field) => Bson::String(field),
+            Stage::Out(coll) => Bson::String(coll),
+        };
+
+        let mut doc = bson::Document::new();
+        doc.insert(name, value);
+        doc
+    }
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline.
+    pub fn new() -> Pipeline {
+        Default::default()
+    }
+
+    fn push(mut self, stage: Stage) -> Pipeline {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Adds a `$match` stage that filters documents with a query.
+    pub fn match_(self, filter: bson::Document) -> Pipeline {
+        self.push(Stage::Match(filter))
+    }
+
+    /// Adds a `$project` stage that reshapes each document.
+    pub fn project(self, projection: bson::Document) -> Pipeline {
+        self.push(Stage::Project(projection))
+    }
+
+    /// Adds a `$group` stage that groups documents by the `id` expression and computes the
+    /// given fields for each group. Use `Bson::Null` to group all documents together.
+    pub fn group(self, id: Bson, fields: Vec<(String, Accumulator)>) -> Pipeline {
+        self.push(Stage::Group {
+            id: id,
+            fields: fields,
+        })
+    }
+
+    /// Adds a `$sort` stage.
+    pub fn sort(self, sort: bson::Document) -> Pipeline {
+        self.push(Stage::Sort(sort))
+    }
+
+    /// Adds a `$limit` stage.
+    pub fn limit(self, limit: i64) -> Pipeline {
+        self.push(Stage::Limit(limit))
+    }
+
+    /// Adds a `$skip` stage.
+    pub fn skip(self, skip: i64) -> Pipeline {
+        self.push(Stage::Skip(skip))
+    }
+
+    /// Adds an `$unwind` stage that outputs a document for each element of the array at
+    /// `path`, which must be a field path such as `"$tags"`.
+    pub fn unwind(self, path: &str, options: Option<UnwindOptions>) -> Pipeline {
+        self.push(Stage::Unwind {
+            path: String::from(path),
+            options: options.unwrap_or_else(UnwindOptions::new),
+        })
+    }
+
+    /// Adds a `$lookup` stage that joins documents from another collection in the same
+    /// database into the `as_field` array.
+    pub fn lookup(self,
+                  from: &str,
+                  local_field: &str,
+                  foreign_field: &str,
+                  as_field: &str)
+                  -> Pipeline {
+        self.push(Stage::Lookup {
+            from: String::from(from),
+            local_field: String::from(local_field),
+            foreign_field: String::from(foreign_field),
+            as_field: String::from(as_field),
+        })
+    }
+
+    /// Adds a `$facet` stage that runs each named sub-pipeline on the same input documents.
+    pub fn facet(self, facets: Vec<(String, Pipeline)>) -> Pipeline {
+        self.push(Stage::Facet(facets))
+    }
+
+    /// Adds a `$bucket` stage that groups documents into ranges of the `group_by`
+    /// expression, delimited by the sorted `boundaries`.
+    pub fn bucket(self,
+                  group_by: Bson,
+                  boundaries: Vec<Bson>,
+                  options: Option<BucketOptions>)
+                  -> Pipeline {
+        self.push(Stage::Bucket {
+            group_by: group_by,
+            boundaries: boundaries,
+            options: options.unwrap_or_else(BucketOptions::new),
+        })
+    }
+
+    /// Adds an `$addFields` stage.
+    pub fn add_fields(self, fields: bson::Document) -> Pipeline {
+        self.push(Stage::AddFields(fields))
+    }
+
+    /// Adds a `$count` stage that outputs the number of documents in the named field.
+    pub fn count(self, field: &str) -> Pipeline {
+        self.push(Stage::Count(String::from(field)))
+    }
+
+    /// Adds an `$out` stage that writes the results to a collection. It must be the last
+    /// stage of the pipeline.
+    pub fn out(self, coll: &str) -> Pipeline {
+        self.push(Stage::Out(String::from(coll)))
+    }
+
+    /// Validates the pipeline and returns its stages as documents, ready to be passed to
+    /// `Collection::aggregate`.
+    pub fn build(self) -> Result<Vec<bson::Document>> {
+        let last = self.stages.len();
+        for (i, stage) in self.stages.iter().enumerate() {
+            if let Stage::Out(_) = *stage {
+                if i + 1 != last {
+                    return Err(ArgumentError(String::from("The $out stage must be the last \
+                                                           stage of the pipeline.")));
+                }
+            }
+            try!(stage.validate());
+        }
+
+        Ok(self.stages.into_iter().map(Stage::to_bson).collect())
+    }
+}
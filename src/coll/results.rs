@@ -2,6 +2,7 @@
 use bson;
 use bson::Bson;
 use std::collections::BTreeMap;
+use cursor::Cursor;
 use super::error::{BulkWriteException, WriteException};
 use super::options::WriteModel;
 
@@ -61,6 +62,135 @@ pub struct DeleteResult {
     pub write_exception: Option<WriteException>,
 }
 
+/// A single stage of a query plan, such as an index scan or a sort.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlanStage {
+    /// The stage name, such as `IXSCAN` or `COLLSCAN`.
+    pub stage: String,
+    /// The index used by the stage, if any.
+    pub index_name: Option<String>,
+    /// The stages that provide input to this stage.
+    pub input_stages: Vec<PlanStage>,
+}
+
+/// Results for an explain operation.
+#[derive(Clone, Debug)]
+pub struct ExplainResult {
+    /// The stage tree of the plan chosen by the query optimizer.
+    pub winning_plan: Option<PlanStage>,
+    /// The number of documents examined during execution.
+    pub docs_examined: Option<i64>,
+    /// The number of index keys examined during execution.
+    pub keys_examined: Option<i64>,
+    /// The total time spent executing the query.
+    pub execution_time_ms: Option<i64>,
+    /// The number of documents returned by the winning plan.
+    pub n_returned: Option<i64>,
+    /// The complete explain output returned by the server.
+    pub raw: bson::Document,
+}
+
+/// Results for a findAndModify operation.
+#[derive(Clone, Debug)]
+pub struct FindAndModifyResult {
+    /// The original or modified document, if one matched.
+    pub value: Option<bson::Document>,
+    /// The number of documents matched or upserted.
+    pub n: i32,
+    /// Whether an existing document was updated or replaced.
+    pub updated_existing: bool,
+    /// The id of the upserted document, if an upsert took place.
+    pub upserted_id: Option<Bson>,
+}
+
+/// Statistics reported by a mapReduce operation.
+#[derive(Clone, Debug)]
+pub struct MapReduceStats {
+    /// The collection the results were written to, if they were not returned inline.
+    pub collection: Option<String>,
+    /// The database of the output collection, if it differs from the input collection's.
+    pub db: Option<String>,
+    /// How long the operation took.
+    pub time_millis: Option<i64>,
+    /// The number of documents matching the query.
+    pub input_count: Option<i64>,
+    /// The number of times the map function called `emit`.
+    pub emit_count: Option<i64>,
+    /// The number of times the reduce function was called.
+    pub reduce_count: Option<i64>,
+    /// The number of documents produced.
+    pub output_count: Option<i64>,
+    /// The complete reply returned by the server, including any timing details.
+    pub raw: bson::Document,
+}
+
+/// Results for a mapReduce operation.
+pub enum MapReduceResult {
+    /// The results of an inline mapReduce, along with statistics.
+    Inline(Cursor, MapReduceStats),
+    /// Statistics for a mapReduce that wrote its results to a collection.
+    Collection(MapReduceStats),
+}
+
+/// Storage statistics for a collection, as reported by the collStats command.
+#[derive(Clone, Debug)]
+pub struct CollectionStats {
+    pub ns: String,
+    /// The number of documents in the collection.
+    pub count: i64,
+    /// The total uncompressed size in bytes of the documents.
+    pub size: i64,
+    pub avg_obj_size: Option<i64>,
+    /// The storage allocated for the documents, in bytes.
+    pub storage_size: i64,
+    pub nindexes: i64,
+    pub total_index_size: i64,
+    /// The size in bytes of each index, by name.
+    pub index_sizes: BTreeMap<String, i64>,
+    pub capped: bool,
+    /// The maximum number of documents in a capped collection, if limited.
+    pub max: Option<i64>,
+    /// The complete reply returned by the server.
+    pub raw: bson::Document,
+}
+
+/// Results for a validate operation.
+#[derive(Clone, Debug)]
+pub struct ValidateResult {
+    /// Whether the collection and its indexes are valid.
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    /// The number of documents in the collection.
+    pub n_records: Option<i64>,
+    pub n_invalid_documents: Option<i64>,
+    pub nindexes: Option<i64>,
+    /// The number of keys in each index, by name.
+    pub keys_per_index: BTreeMap<String, i64>,
+    /// The complete reply returned by the server.
+    pub raw: bson::Document,
+}
+
+/// The changes made, or that would be made in a dry run, when synchronizing indexes.
+#[derive(Clone, Debug, Default)]
+pub struct SyncIndexesResult {
+    /// Indexes that were missing and were created.
+    pub created: Vec<String>,
+    /// Indexes whose options changed and were dropped and created again.
+    pub recreated: Vec<String>,
+    /// Existing indexes whose options differ from their models, whether or not they
+    /// were recreated.
+    pub changed: Vec<String>,
+    /// Indexes that weren't in the desired set and were dropped.
+    pub dropped: Vec<String>,
+    /// Indexes that weren't in the desired set and were left in place.
+    pub unmanaged: Vec<String>,
+    /// Indexes that already matched their models.
+    pub unchanged: Vec<String>,
+    /// Whether the changes were only reported and not applied.
+    pub dry_run: bool,
+}
+
 /// Results for an update operation.
 #[derive(Clone)]
 pub struct UpdateResult {
@@ -91,9 +221,11 @@ impl BulkWriteResult {
     pub fn process_bulk_delete_result(&mut self,
                                       result: BulkDeleteResult,
                                       models: Vec<WriteModel>,
+                                      indexes: &[i64],
                                       exception: &mut BulkWriteException)
                                       -> bool {
-        let ok = exception.add_bulk_write_exception(result.write_exception, models);
+        let write_exception = original_positions(result.write_exception, &models, indexes);
+        let ok = exception.add_bulk_write_exception(write_exception, models);
         self.deleted_count += result.deleted_count;
 
         ok
@@ -103,14 +235,15 @@ impl BulkWriteResult {
     pub fn process_insert_many_result(&mut self,
                                       result: InsertManyResult,
                                       models: Vec<WriteModel>,
-                                      start_index: i64,
+                                      indexes: &[i64],
                                       exception: &mut BulkWriteException)
                                       -> bool {
-        let ok = exception.add_bulk_write_exception(result.bulk_write_exception, models);
+        let write_exception = original_positions(result.bulk_write_exception, &models, indexes);
+        let ok = exception.add_bulk_write_exception(write_exception, models);
 
         if let Some(ids) = result.inserted_ids {
             for (i, id) in ids {
-                self.inserted_ids.insert(start_index + i, id);
+                self.inserted_ids.insert(original_index(indexes, i), id);
                 self.inserted_count += 1;
             }
         }
@@ -121,18 +254,18 @@ impl BulkWriteResult {
     // Parses an index and id from a single BSON document and adds it to
     // the tree of upserted ids.
     fn parse_upserted_id(mut document: bson::Document,
-                         start_index: i64,
+                         indexes: &[i64],
                          upserted_ids: &mut BTreeMap<i64, Bson>)
                          -> i32 {
         let (index, id) = (document.remove("index"), document.remove("_id"));
 
         match (index, id) {
             (Some(Bson::I32(i)), Some(bson_id)) => {
-                let _ = upserted_ids.insert(start_index + i as i64, bson_id);
+                let _ = upserted_ids.insert(original_index(indexes, i as i64), bson_id);
                 1
             }
             (Some(Bson::I64(i)), Some(bson_id)) => {
-                let _ = upserted_ids.insert(start_index + i, bson_id.clone());
+                let _ = upserted_ids.insert(original_index(indexes, i), bson_id.clone());
                 1
             }
             _ => 0,
@@ -142,19 +275,17 @@ impl BulkWriteResult {
     // Parses multiple indexes and ids from a single BSON document and adds
     // them to the tree of upserted ids.
     fn parse_upserted_ids(bson: Bson,
-                          start_index: i64,
+                          indexes: &[i64],
                           upserted_ids: &mut BTreeMap<i64, Bson>)
                           -> i32 {
         match bson {
-            Bson::Document(doc) => {
-                BulkWriteResult::parse_upserted_id(doc, start_index, upserted_ids)
-            }
+            Bson::Document(doc) => BulkWriteResult::parse_upserted_id(doc, indexes, upserted_ids),
             Bson::Array(vec) => {
                 let mut count = 0;
 
                 for bson in vec {
                     if let Bson::Document(doc) = bson {
-                        count += BulkWriteResult::parse_upserted_id(doc, start_index, upserted_ids)
+                        count += BulkWriteResult::parse_upserted_id(doc, indexes, upserted_ids)
                     }
                 }
 
@@ -168,17 +299,18 @@ impl BulkWriteResult {
     pub fn process_bulk_update_result(&mut self,
                                       result: BulkUpdateResult,
                                       models: Vec<WriteModel>,
-                                      start_index: i64,
+                                      indexes: &[i64],
                                       exception: &mut BulkWriteException)
                                       -> bool {
-        let ok = exception.add_bulk_write_exception(result.write_exception, models);
+        let write_exception = original_positions(result.write_exception, &models, indexes);
+        let ok = exception.add_bulk_write_exception(write_exception, models);
 
         self.matched_count += result.matched_count;
         self.modified_count += result.modified_count;
 
         if let Some(upserted_ids) = result.upserted_ids {
             self.upserted_count += BulkWriteResult::parse_upserted_ids(upserted_ids,
-                                                                       start_index,
+                                                                       indexes,
                                                                        &mut self.upserted_ids);
         }
 
@@ -200,6 +332,15 @@ impl BulkDeleteResult {
             write_exception: exception,
         }
     }
+
+    /// Constructs the result of a delete sent with an unacknowledged write concern.
+    pub fn unacknowledged() -> BulkDeleteResult {
+        BulkDeleteResult {
+            acknowledged: false,
+            deleted_count: 0,
+            write_exception: None,
+        }
+    }
 }
 
 impl BulkUpdateResult {
@@ -210,8 +351,8 @@ impl BulkUpdateResult {
             _ => 0,
         };
 
-        let (n_upserted, id) = match doc.get("upserted") {
-            Some(&Bson::Array(ref arr)) => (arr.len() as i32, Some(arr[0].clone())),
+        let (n_upserted, ids) = match doc.get("upserted") {
+            Some(&Bson::Array(ref arr)) => (arr.len() as i32, Some(Bson::Array(arr.clone()))),
             _ => (0, None),
         };
 
@@ -226,10 +367,21 @@ impl BulkUpdateResult {
             acknowledged: true,
             matched_count: n_matched,
             modified_count: n_modified,
-            upserted_ids: id,
+            upserted_ids: ids,
             write_exception: exception,
         }
     }
+
+    /// Constructs the result of an update sent with an unacknowledged write concern.
+    pub fn unacknowledged() -> BulkUpdateResult {
+        BulkUpdateResult {
+            acknowledged: false,
+            matched_count: 0,
+            modified_count: 0,
+            upserted_ids: None,
+            write_exception: None,
+        }
+    }
 }
 
 impl InsertOneResult {
@@ -285,6 +437,133 @@ impl DeleteResult {
     }
 }
 
+impl FindAndModifyResult {
+    /// Extracts server reply information into a result.
+    pub fn new(doc: &bson::Document) -> FindAndModifyResult {
+        let value = match doc.get("value") {
+            Some(&Bson::Document(ref value)) => Some(value.to_owned()),
+            _ => None,
+        };
+
+        let mut result = FindAndModifyResult {
+            value: value,
+            n: 0,
+            updated_existing: false,
+            upserted_id: None,
+        };
+
+        if let Some(&Bson::Document(ref last_error)) = doc.get("lastErrorObject") {
+            result.n = match last_error.get("n") {
+                Some(&Bson::I32(n)) => n,
+                Some(&Bson::I64(n)) => n as i32,
+                _ => 0,
+            };
+            if let Some(&Bson::Boolean(updated)) = last_error.get("updatedExisting") {
+                result.updated_existing = updated;
+            }
+            result.upserted_id = last_error.get("upserted").cloned();
+        }
+
+        result
+    }
+}
+
+impl MapReduceStats {
+    /// Extracts server reply information into statistics.
+    pub fn new(doc: bson::Document) -> MapReduceStats {
+        let (collection, db) = match doc.get("result") {
+            Some(&Bson::String(ref coll)) => (Some(coll.to_owned()), None),
+            Some(&Bson::Document(ref result)) => {
+                let coll = match result.get("collection") {
+                    Some(&Bson::String(ref coll)) => Some(coll.to_owned()),
+                    _ => None,
+                };
+                let db = match result.get("db") {
+                    Some(&Bson::String(ref db)) => Some(db.to_owned()),
+                    _ => None,
+                };
+                (coll, db)
+            }
+            _ => (None, None),
+        };
+
+        let counts = match doc.get("counts") {
+            Some(&Bson::Document(ref counts)) => counts.to_owned(),
+            _ => bson::Document::new(),
+        };
+
+        MapReduceStats {
+            collection: collection,
+            db: db,
+            time_millis: get_i64(&doc, "timeMillis"),
+            input_count: get_i64(&counts, "input"),
+            emit_count: get_i64(&counts, "emit"),
+            reduce_count: get_i64(&counts, "reduce"),
+            output_count: get_i64(&counts, "output"),
+            raw: doc,
+        }
+    }
+}
+
+impl CollectionStats {
+    /// Extracts server reply information into statistics.
+    pub fn new(doc: bson::Document) -> CollectionStats {
+        let ns = match doc.get("ns") {
+            Some(&Bson::String(ref ns)) => ns.to_owned(),
+            _ => String::new(),
+        };
+
+        let capped = match doc.get("capped") {
+            Some(&Bson::Boolean(capped)) => capped,
+            _ => false,
+        };
+
+        CollectionStats {
+            ns: ns,
+            count: get_i64(&doc, "count").unwrap_or(0),
+            size: get_i64(&doc, "size").unwrap_or(0),
+            avg_obj_size: get_i64(&doc, "avgObjSize"),
+            storage_size: get_i64(&doc, "storageSize").unwrap_or(0),
+            nindexes: get_i64(&doc, "nindexes").unwrap_or(0),
+            total_index_size: get_i64(&doc, "totalIndexSize").unwrap_or(0),
+            index_sizes: get_i64_map(&doc, "indexSizes"),
+            capped: capped,
+            max: get_i64(&doc, "max"),
+            raw: doc,
+        }
+    }
+}
+
+impl ValidateResult {
+    /// Extracts server reply information into a result.
+    pub fn new(doc: bson::Document) -> ValidateResult {
+        let valid = match doc.get("valid") {
+            Some(&Bson::Boolean(valid)) => valid,
+            _ => false,
+        };
+
+        ValidateResult {
+            valid: valid,
+            errors: get_strings(&doc, "errors"),
+            warnings: get_strings(&doc, "warnings"),
+            n_records: get_i64(&doc, "nrecords"),
+            n_invalid_documents: get_i64(&doc, "nInvalidDocuments"),
+            nindexes: get_i64(&doc, "nIndexes"),
+            keys_per_index: get_i64_map(&doc, "keysPerIndex"),
+            raw: doc,
+        }
+    }
+}
+
+impl SyncIndexesResult {
+    /// Returns true if any index was, or would be, created, recreated or dropped, or if
+    /// any index's options differ from its model.
+    pub fn has_changes(&self) -> bool {
+        !self.created.is_empty() || !self.recreated.is_empty() || !self.changed.is_empty() ||
+        !self.dropped.is_empty()
+    }
+}
+
 impl UpdateResult {
     /// Extracts server reply information into a result.
     pub fn new(doc: bson::Document, exception: Option<WriteException>) -> UpdateResult {
@@ -320,12 +599,201 @@ impl UpdateResult {
             None => None,
         };
 
+        // Single updates upsert at most one document.
+        let upserted_id = match result.upserted_ids {
+            Some(Bson::Array(ids)) => ids.into_iter().next(),
+            other => other,
+        };
+
         UpdateResult {
             acknowledged: result.acknowledged,
             matched_count: result.matched_count,
             modified_count: result.modified_count,
-            upserted_id: result.upserted_ids,
+            upserted_id: upserted_id,
             write_exception: exception,
         }
     }
 }
+
+impl PlanStage {
+    /// Parses a plan stage and its input stages from explain output.
+    pub fn new(doc: &bson::Document) -> PlanStage {
+        let stage = match doc.get("stage") {
+            Some(&Bson::String(ref stage)) => stage.to_owned(),
+            _ => String::new(),
+        };
+
+        let index_name = match doc.get("indexName") {
+            Some(&Bson::String(ref name)) => Some(name.to_owned()),
+            _ => None,
+        };
+
+        let mut input_stages = Vec::new();
+        if let Some(&Bson::Document(ref input)) = doc.get("inputStage") {
+            input_stages.push(PlanStage::new(input));
+        }
+
+        if let Some(&Bson::Array(ref inputs)) = doc.get("inputStages") {
+            for input in inputs {
+                if let Bson::Document(ref input) = *input {
+                    input_stages.push(PlanStage::new(input));
+                }
+            }
+        }
+
+        // Sharded plans contain the winning plan of each shard.
+        if let Some(&Bson::Array(ref shards)) = doc.get("shards") {
+            for shard in shards {
+                if let Bson::Document(ref shard) = *shard {
+                    if let Some(&Bson::Document(ref plan)) = shard.get("winningPlan") {
+                        input_stages.push(PlanStage::new(plan));
+                    }
+                }
+            }
+        }
+
+        PlanStage {
+            stage: stage,
+            index_name: index_name,
+            input_stages: input_stages,
+        }
+    }
+}
+
+impl ExplainResult {
+    /// Extracts a summary from explain output, which may be in the format of any
+    /// supported server version.
+    pub fn new(doc: bson::Document) -> ExplainResult {
+        let mut result = ExplainResult {
+            winning_plan: None,
+            docs_examined: None,
+            keys_examined: None,
+            execution_time_ms: None,
+            n_returned: None,
+            raw: bson::Document::new(),
+        };
+
+        match ExplainResult::find_plan(&doc) {
+            Some(plan) => {
+                if let Some(&Bson::Document(ref planner)) = plan.get("queryPlanner") {
+                    if let Some(&Bson::Document(ref winning)) = planner.get("winningPlan") {
+                        result.winning_plan = Some(PlanStage::new(winning));
+                    }
+                }
+
+                if let Some(&Bson::Document(ref stats)) = plan.get("executionStats") {
+                    result.docs_examined = get_i64(stats, "totalDocsExamined");
+                    result.keys_examined = get_i64(stats, "totalKeysExamined");
+                    result.execution_time_ms = get_i64(stats, "executionTimeMillis");
+                    result.n_returned = get_i64(stats, "nReturned");
+                }
+            }
+            None => {
+                // Servers before 3.0 report a flat summary of the chosen cursor.
+                if let Some(&Bson::String(ref cursor)) = doc.get("cursor") {
+                    result.winning_plan = Some(PlanStage {
+                        stage: cursor.to_owned(),
+                        index_name: None,
+                        input_stages: Vec::new(),
+                    });
+                }
+
+                result.docs_examined = get_i64(&doc, "nscannedObjects");
+                result.keys_examined = get_i64(&doc, "nscanned");
+                result.execution_time_ms = get_i64(&doc, "millis");
+                result.n_returned = get_i64(&doc, "n");
+            }
+        }
+
+        result.raw = doc;
+        result
+    }
+
+    // Locates the query plan within explain output, which is nested within the first
+    // stage of an aggregation or within each shard of a sharded aggregation.
+    fn find_plan(doc: &bson::Document) -> Option<&bson::Document> {
+        if doc.contains_key("queryPlanner") {
+            return Some(doc);
+        }
+
+        if let Some(&Bson::Array(ref stages)) = doc.get("stages") {
+            if let Some(&Bson::Document(ref stage)) = stages.first() {
+                if let Some(&Bson::Document(ref cursor)) = stage.get("$cursor") {
+                    return ExplainResult::find_plan(cursor);
+                }
+            }
+        }
+
+        if let Some(&Bson::Document(ref shards)) = doc.get("shards") {
+            for (_, shard) in shards {
+                if let Bson::Document(ref shard) = *shard {
+                    if let Some(plan) = ExplainResult::find_plan(shard) {
+                        return Some(plan);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Reads a numeric field as an i64.
+fn get_i64(doc: &bson::Document, key: &str) -> Option<i64> {
+    match doc.get(key) {
+        Some(&Bson::I32(n)) => Some(n as i64),
+        Some(&Bson::I64(n)) => Some(n),
+        Some(&Bson::FloatingPoint(n)) => Some(n as i64),
+        _ => None,
+    }
+}
+
+// Reads a subdocument of numeric fields as a map.
+fn get_i64_map(doc: &bson::Document, key: &str) -> BTreeMap<String, i64> {
+    let mut map = BTreeMap::new();
+    if let Some(&Bson::Document(ref values)) = doc.get(key) {
+        for name in values.keys() {
+            if let Some(n) = get_i64(values, name) {
+                map.insert(name.to_owned(), n);
+            }
+        }
+    }
+    map
+}
+
+// Reads an array of strings, skipping any other values.
+fn get_strings(doc: &bson::Document, key: &str) -> Vec<String> {
+    match doc.get(key) {
+        Some(&Bson::Array(ref values)) => {
+            values.iter()
+                .filter_map(|value| match *value {
+                    Bson::String(ref s) => Some(s.to_owned()),
+                    _ => None,
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+// Maps an index within a batch to the index of the request in the original bulk write.
+fn original_index(indexes: &[i64], index: i64) -> i64 {
+    indexes.get(index as usize).cloned().unwrap_or(index)
+}
+
+// Rewrites the write errors of a batch to refer to the original positions of their requests,
+// attaching the requests that failed.
+fn original_positions(exception: Option<BulkWriteException>,
+                      models: &[WriteModel],
+                      indexes: &[i64])
+                      -> Option<BulkWriteException> {
+    exception.map(|mut exception| {
+        for error in &mut exception.write_errors {
+            if error.request.is_none() {
+                error.request = models.get(error.index as usize).cloned();
+            }
+            error.index = original_index(indexes, error.index as i64) as i32;
+        }
+        exception
+    })
+}
@@ -0,0 +1,82 @@
+//! Comparison of desired index models against the indexes reported by the server.
+use bson::{Bson, Document};
+
+// Options that change how an index behaves, so an existing index that sets them doesn't
+// match a model that leaves them unset.
+const BEHAVIORAL_OPTIONS: [&'static str; 6] = ["unique",
+                                               "sparse",
+                                               "expireAfterSeconds",
+                                               "partialFilterExpression",
+                                               "collation",
+                                               "hidden"];
+
+// Options that don't affect the index itself, or are filled in by the server.
+const IGNORED_OPTIONS: [&'static str; 4] = ["key", "ns", "v", "background"];
+
+/// Returns true if the values are equal, treating numeric types interchangeably.
+pub fn values_match(desired: &Bson, existing: &Bson) -> bool {
+    match (as_f64(desired), as_f64(existing)) {
+        (Some(a), Some(b)) => a == b,
+        _ => {
+            match (desired, existing) {
+                (&Bson::Document(ref a), &Bson::Document(ref b)) => documents_match(a, b),
+                (&Bson::Array(ref a), &Bson::Array(ref b)) => {
+                    a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| values_match(x, y))
+                }
+                _ => desired == existing,
+            }
+        }
+    }
+}
+
+/// Returns true if the documents have the same keys in the same order with matching values.
+pub fn documents_match(desired: &Document, existing: &Document) -> bool {
+    desired.len() == existing.len() &&
+    desired.iter()
+        .zip(existing.iter())
+        .all(|((k1, v1), (k2, v2))| k1 == k2 && values_match(v1, v2))
+}
+
+/// Returns true if an existing index's options differ from those of the desired index
+/// specification. Options the model leaves unset are only compared if they change the
+/// index's behavior, since the server fills in defaults for the rest. The name is only
+/// compared if the model sets one explicitly.
+pub fn options_differ(desired: &Document, existing: &Document, explicit_name: bool) -> bool {
+    for (key, value) in desired.iter() {
+        if IGNORED_OPTIONS.iter().any(|ignored| ignored == key) ||
+           (key == "name" && !explicit_name) {
+            continue;
+        }
+
+        let matches = match (key.as_str(), value, existing.get(key)) {
+            // The server reports every field of a collation, including defaults.
+            ("collation", &Bson::Document(ref desired), Some(&Bson::Document(ref existing))) => {
+                desired.iter()
+                    .all(|(k, v)| existing.get(k).map_or(false, |e| values_match(v, e)))
+            }
+            (_, value, Some(existing)) => values_match(value, existing),
+            (_, _, None) => false,
+        };
+
+        if !matches {
+            return true;
+        }
+    }
+
+    BEHAVIORAL_OPTIONS.iter().any(|key| {
+        !desired.contains_key(key) &&
+        match existing.get(key) {
+            None | Some(&Bson::Boolean(false)) => false,
+            Some(_) => true,
+        }
+    })
+}
+
+fn as_f64(value: &Bson) -> Option<f64> {
+    match *value {
+        Bson::I32(n) => Some(n as f64),
+        Bson::I64(n) => Some(n as f64),
+        Bson::FloatingPoint(n) => Some(n),
+        _ => None,
+    }
+}
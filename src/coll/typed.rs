@@ -0,0 +1,166 @@
+//! Collection operations on values that are encoded to and decoded from documents.
+//!
+//! ```no_run
+//! # #[macro_use] extern crate bson;
+//! # extern crate mongodb;
+//! # extern crate rustc_serialize;
+//! #
+//! # use mongodb::{Client, ThreadedClient};
+//! # use mongodb::db::ThreadedDatabase;
+//! #
+//! #[derive(RustcEncodable, RustcDecodable)]
+//! struct Job {
+//!     name: String,
+//!     priority: i32,
+//! }
+//!
+//! # fn main() {
+//! # let client = Client::connect("localhost", 27017).unwrap();
+//! let jobs = client.db("queue").collection("jobs").with_type::<Job>();
+//!
+//! jobs.insert_one(&Job { name: String::from("reindex"), priority: 1 }, None).unwrap();
+//!
+//! for job in jobs.find(Some(doc! { "priority" => 1 }), None).unwrap() {
+//!     println!("{}", job.unwrap().name);
+//! }
+//! # }
+//! ```
+use bson;
+use codec;
+use common::WriteConcern;
+use cursor::TypedCursor;
+use Result;
+
+use rustc_serialize::{Decodable, Encodable};
+
+use std::marker::PhantomData;
+
+use super::Collection;
+use super::options::{AggregateOptions, FindOneAndDeleteOptions, FindOneAndUpdateOptions,
+                     FindOptions, InsertManyOptions, ReplaceOptions};
+use super::results::{InsertManyResult, InsertOneResult, UpdateResult};
+
+/// A collection whose documents are encoded from and decoded into values of type `T`.
+///
+/// Filters and updates are still expressed as documents. Read preference and write
+/// concern are inherited from the wrapped collection, which is available through `raw`.
+pub struct TypedCollection<T: Encodable + Decodable> {
+    coll: Collection,
+    marker: PhantomData<T>,
+}
+
+impl<T: Encodable + Decodable> TypedCollection<T> {
+    /// Wraps a document collection.
+    pub fn new(coll: Collection) -> TypedCollection<T> {
+        TypedCollection {
+            coll: coll,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying document collection.
+    pub fn raw(&self) -> &Collection {
+        &self.coll
+    }
+
+    /// Consumes the typed collection, returning the underlying document collection.
+    pub fn into_inner(self) -> Collection {
+        self.coll
+    }
+
+    /// Runs an aggregation framework pipeline, decoding each result into a value of type `T`.
+    pub fn aggregate(&self,
+                     pipeline: Vec<bson::Document>,
+                     options: Option<AggregateOptions>)
+                     -> Result<TypedCursor<T>> {
+        self.coll.aggregate(pipeline, options).map(TypedCursor::new)
+    }
+
+    /// Returns values for all documents within the collection that match the filter.
+    pub fn find(&self,
+                filter: Option<bson::Document>,
+                options: Option<FindOptions>)
+                -> Result<TypedCursor<T>> {
+        self.coll.find_as(filter, options)
+    }
+
+    /// Returns the value for the first document within the collection that matches
+    /// the filter, or None.
+    pub fn find_one(&self,
+                    filter: Option<bson::Document>,
+                    options: Option<FindOptions>)
+                    -> Result<Option<T>> {
+        self.coll.find_one_as(filter, options)
+    }
+
+    /// Finds a single document and deletes it, returning the original value.
+    pub fn find_one_and_delete(&self,
+                               filter: bson::Document,
+                               options: Option<FindOneAndDeleteOptions>)
+                               -> Result<Option<T>> {
+        let result = try!(self.coll.find_one_and_delete(filter, options));
+        TypedCollection::decode_option(result.value)
+    }
+
+    /// Finds a single document and replaces it with the encoded value, returning either
+    /// the original or replaced value.
+    pub fn find_one_and_replace(&self,
+                                filter: bson::Document,
+                                replacement: &T,
+                                options: Option<FindOneAndUpdateOptions>)
+                                -> Result<Option<T>> {
+        let replacement = try!(codec::encode_document(replacement));
+        let result = try!(self.coll.find_one_and_replace(filter, replacement, options));
+        TypedCollection::decode_option(result.value)
+    }
+
+    /// Finds a single document and updates it, returning either the original or
+    /// updated value.
+    pub fn find_one_and_update(&self,
+                               filter: bson::Document,
+                               update: bson::Document,
+                               options: Option<FindOneAndUpdateOptions>)
+                               -> Result<Option<T>> {
+        let result = try!(self.coll.find_one_and_update(filter, update, options));
+        TypedCollection::decode_option(result.value)
+    }
+
+    /// Encodes and inserts the provided value.
+    pub fn insert_one(&self,
+                      value: &T,
+                      write_concern: Option<WriteConcern>)
+                      -> Result<InsertOneResult> {
+        self.coll.insert_one_from(value, write_concern)
+    }
+
+    /// Encodes and inserts the provided values.
+    pub fn insert_many(&self,
+                       values: &[T],
+                       options: Option<InsertManyOptions>)
+                       -> Result<InsertManyResult> {
+        let mut docs = Vec::with_capacity(values.len());
+        for value in values {
+            docs.push(try!(codec::encode_document(value)));
+        }
+
+        self.coll.insert_many(docs, options)
+    }
+
+    /// Replaces a single document matching the filter with the encoded value.
+    pub fn replace_one(&self,
+                       filter: bson::Document,
+                       replacement: &T,
+                       options: Option<ReplaceOptions>)
+                       -> Result<UpdateResult> {
+        let replacement = try!(codec::encode_document(replacement));
+        self.coll.replace_one(filter, replacement, options)
+    }
+
+    // Decodes an optional document returned by a findAndModify command.
+    fn decode_option(doc: Option<bson::Document>) -> Result<Option<T>> {
+        match doc {
+            Some(doc) => codec::decode_document(doc).map(Some),
+            None => Ok(None),
+        }
+    }
+}
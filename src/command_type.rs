@@ -4,7 +4,10 @@
 #[derive(PartialEq, Eq, Clone)]
 pub enum CommandType {
     Aggregate,
+    AggregateWrite,
     BuildInfo,
+    CollMod,
+    CollStats,
     Count,
     CreateCollection,
     CreateIndexes,
@@ -17,6 +20,7 @@ pub enum CommandType {
     DropDatabase,
     DropIndexes,
     DropUser,
+    Explain,
     Find,
     FindOneAndDelete,
     FindOneAndReplace,
@@ -29,16 +33,23 @@ pub enum CommandType {
     ListCollections,
     ListDatabases,
     ListIndexes,
+    MapReduce,
+    MapReduceWrite,
+    RenameCollection,
     Suppressed,
     UpdateMany,
     UpdateOne,
+    Validate,
 }
 
 impl CommandType {
     pub fn to_str(&self) -> &str {
         match *self {
             CommandType::Aggregate => "aggregate",
+            CommandType::AggregateWrite => "aggregate_write",
             CommandType::BuildInfo => "buildinfo",
+            CommandType::CollMod => "coll_mod",
+            CommandType::CollStats => "coll_stats",
             CommandType::Count => "count",
             CommandType::CreateCollection => "create_collection",
             CommandType::CreateIndexes => "create_indexes",
@@ -51,6 +62,7 @@ impl CommandType {
             CommandType::DropDatabase => "drop_database",
             CommandType::DropIndexes => "drop_indexes",
             CommandType::DropUser => "drop_user",
+            CommandType::Explain => "explain",
             CommandType::Find => "find",
             CommandType::FindOneAndDelete => "find_one_and_delete",
             CommandType::FindOneAndReplace => "find_one_and_replace",
@@ -63,14 +75,35 @@ impl CommandType {
             CommandType::ListCollections => "list_collections",
             CommandType::ListDatabases => "list_databases",
             CommandType::ListIndexes => "list_indexes",
+            CommandType::MapReduce => "map_reduce",
+            CommandType::MapReduceWrite => "map_reduce_write",
+            CommandType::RenameCollection => "rename_collection",
             CommandType::Suppressed => "suppressed",
             CommandType::UpdateMany => "update_many",
             CommandType::UpdateOne => "update_one",
+            CommandType::Validate => "validate",
+        }
+    }
+
+    /// Returns true if the command is a read that may be retried after a network or
+    /// failover error.
+    pub fn is_retryable_read(&self) -> bool {
+        match *self {
+            CommandType::Aggregate |
+            CommandType::Count |
+            CommandType::Distinct |
+            CommandType::Find |
+            CommandType::ListCollections |
+            CommandType::ListDatabases |
+            CommandType::ListIndexes => true,
+            _ => false,
         }
     }
 
     pub fn is_write_command(&self) -> bool {
         match *self {
+            CommandType::AggregateWrite |
+            CommandType::CollMod |
             CommandType::CreateCollection |
             CommandType::CreateIndexes |
             CommandType::CreateUser |
@@ -86,12 +119,16 @@ impl CommandType {
             CommandType::FindOneAndUpdate |
             CommandType::InsertMany |
             CommandType::InsertOne |
+            CommandType::MapReduceWrite |
+            CommandType::RenameCollection |
             CommandType::UpdateMany |
             CommandType::UpdateOne => true,
             CommandType::Aggregate |
             CommandType::BuildInfo |
+            CommandType::CollStats |
             CommandType::Count |
             CommandType::Distinct |
+            CommandType::Explain |
             CommandType::Find |
             CommandType::GetUser |
             CommandType::GetUsers |
@@ -99,7 +136,9 @@ impl CommandType {
             CommandType::ListCollections |
             CommandType::ListDatabases |
             CommandType::ListIndexes |
-            CommandType::Suppressed => false,
+            CommandType::MapReduce |
+            CommandType::Suppressed |
+            CommandType::Validate => false,
         }
     }
 }
@@ -65,11 +65,130 @@ impl ReadPreference {
     }
 }
 
+/// The consistency and isolation guarantees requested for data returned by read operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadConcernLevel {
+    /// Returns the most recent data on the queried server.
+    Local,
+    /// Returns data acknowledged by a majority of the replica set.
+    Majority,
+    /// Returns data reflecting all majority-acknowledged writes that completed before
+    /// the read began. Only supported when reading from the primary.
+    Linearizable,
+    /// Returns the most recent data on the queried server, without waiting for orphaned
+    /// documents on shards to be cleaned up.
+    Available,
+    /// Returns data from a snapshot of majority-committed data.
+    Snapshot,
+}
+
+impl FromStr for ReadConcernLevel {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "local" => ReadConcernLevel::Local,
+            "majority" => ReadConcernLevel::Majority,
+            "linearizable" => ReadConcernLevel::Linearizable,
+            "available" => ReadConcernLevel::Available,
+            "snapshot" => ReadConcernLevel::Snapshot,
+            _ => {
+                return Err(ArgumentError(format!("Could not convert '{}' to ReadConcernLevel.", s)))
+            }
+        })
+    }
+}
+
+impl ReadConcernLevel {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ReadConcernLevel::Local => "local",
+            ReadConcernLevel::Majority => "majority",
+            ReadConcernLevel::Linearizable => "linearizable",
+            ReadConcernLevel::Available => "available",
+            ReadConcernLevel::Snapshot => "snapshot",
+        }
+    }
+
+    /// Returns the first wire version of servers that support the level.
+    pub fn min_wire_version(&self) -> i64 {
+        match *self {
+            ReadConcernLevel::Local |
+            ReadConcernLevel::Majority => 4,
+            ReadConcernLevel::Linearizable => 5,
+            ReadConcernLevel::Available => 6,
+            ReadConcernLevel::Snapshot => 7,
+        }
+    }
+}
+
+/// Describes the consistency and isolation of data returned by read operations.
+/// A read concern without a level uses the server's default.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReadConcern {
+    pub level: Option<ReadConcernLevel>,
+}
+
+impl ReadConcern {
+    pub fn new(level: ReadConcernLevel) -> ReadConcern {
+        ReadConcern { level: Some(level) }
+    }
+
+    pub fn to_bson(&self) -> bson::Document {
+        let mut bson = bson::Document::new();
+        if let Some(ref level) = self.level {
+            bson.insert("level", Bson::String(String::from(level.as_str())));
+        }
+        bson
+    }
+}
+
+/// The servers that must acknowledge a write before it is reported as successful.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Acknowledgment {
+    /// A number of servers, including the primary. Zero requests no acknowledgment.
+    Nodes(i32),
+    /// A majority of the replica set's data-bearing voting members.
+    Majority,
+    /// A custom write concern mode defined in the replica set configuration.
+    Tag(String),
+}
+
+impl Default for Acknowledgment {
+    fn default() -> Self {
+        Acknowledgment::Nodes(1)
+    }
+}
+
+impl FromStr for Acknowledgment {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            return Err(ArgumentError(String::from("Write concern 'w' must not be empty.")));
+        }
+
+        Ok(match s.parse::<i32>() {
+            Ok(nodes) => Acknowledgment::Nodes(nodes),
+            Err(_) if s == "majority" => Acknowledgment::Majority,
+            Err(_) => Acknowledgment::Tag(String::from(s)),
+        })
+    }
+}
+
+impl Acknowledgment {
+    pub fn to_bson(&self) -> Bson {
+        match *self {
+            Acknowledgment::Nodes(nodes) => Bson::I32(nodes),
+            Acknowledgment::Majority => Bson::String(String::from("majority")),
+            Acknowledgment::Tag(ref tag) => Bson::String(tag.to_owned()),
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct WriteConcern {
     /// Write replication
-    pub w: i32,
-    /// Used in conjunction with 'w'. Propagation timeout in ms.
+    pub w: Acknowledgment,
+    /// Used in conjunction with 'w'. Propagation timeout in ms; 0 waits indefinitely.
     pub w_timeout: i32,
     /// If true, will block until write operations have been committed to journal.
     pub j: bool,
@@ -80,18 +199,54 @@ pub struct WriteConcern {
 impl WriteConcern {
     pub fn new() -> WriteConcern {
         WriteConcern {
-            w: 1,
+            w: Acknowledgment::Nodes(1),
             w_timeout: 0,
             j: false,
             fsync: false,
         }
     }
 
+    /// Returns true unless the write concern requests no acknowledgment from the server.
+    pub fn is_acknowledged(&self) -> bool {
+        self.w != Acknowledgment::Nodes(0) || self.j || self.fsync
+    }
+
+    /// Checks that the write concern's fields are consistent with each other.
+    pub fn validate(&self) -> Result<()> {
+        match self.w {
+            Acknowledgment::Nodes(nodes) if nodes < 0 => {
+                return Err(ArgumentError(String::from("Write concern 'w' must not be \
+                                                       negative.")));
+            }
+            Acknowledgment::Nodes(0) if self.j || self.fsync => {
+                return Err(ArgumentError(String::from("An unacknowledged write concern cannot \
+                                                       request journaling or fsync.")));
+            }
+            _ => (),
+        }
+
+        if self.w_timeout < 0 {
+            return Err(ArgumentError(String::from("Write concern 'wtimeout' must not be \
+                                                   negative.")));
+        }
+
+        Ok(())
+    }
+
+    /// Converts the write concern to its BSON document representation, omitting
+    /// options left at their defaults.
     pub fn to_bson(&self) -> bson::Document {
         let mut bson = bson::Document::new();
-        bson.insert("w", Bson::I32(self.w));
-        bson.insert("wtimeout", Bson::I32(self.w_timeout));
-        bson.insert("j", Bson::Boolean(self.j));
+        bson.insert("w", self.w.to_bson());
+        if self.w_timeout > 0 {
+            bson.insert("wtimeout", Bson::I32(self.w_timeout));
+        }
+        if self.j {
+            bson.insert("j", Bson::Boolean(self.j));
+        }
+        if self.fsync {
+            bson.insert("fsync", Bson::Boolean(self.fsync));
+        }
         bson
     }
 }
@@ -63,6 +63,20 @@ impl ConnectionOptions {
     pub fn get(&self, key: &str) -> Option<&String> {
         self.options.get(key)
     }
+
+    /// Retrieves a boolean option, failing if it is not `true` or `false`.
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>> {
+        match self.get(key).map(|value| &value[..]) {
+            Some("true") => Ok(Some(true)),
+            Some("false") => Ok(Some(false)),
+            Some(value) => {
+                Err(ArgumentError(format!("Invalid value '{}' for {}; expected 'true' or 'false'.",
+                                          value,
+                                          key)))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 /// Encapsulates information for connection to a single MongoDB host or replicated set.
@@ -27,16 +27,34 @@ use {Client, CommandType, Error, ErrorCode, Result, ThreadedClient};
 use apm::{CommandStarted, CommandResult, EventRunner};
 
 use bson::{self, Bson};
+use codec;
+use coll::options::CursorType;
 use common::{ReadMode, ReadPreference};
+use connstring::Host;
 use pool::PooledStream;
 use time;
 use wire_protocol::flags::OpQueryFlags;
 use wire_protocol::operations::Message;
 
+use rustc_serialize::Decodable;
+
 use std::collections::vec_deque::VecDeque;
+use std::marker::PhantomData;
+use std::thread;
+use std::time::Duration;
 
 pub const DEFAULT_BATCH_SIZE: i32 = 20;
 
+// Wire version of the first servers to support retryable reads.
+const RETRYABLE_READS_WIRE_VERSION: i64 = 6;
+
+// Wire version of the first servers to support the getMore command.
+const GET_MORE_COMMAND_WIRE_VERSION: i64 = 4;
+
+/// How long a tailable cursor without `awaitData` waits between polls for new documents
+/// while being iterated.
+pub const TAILABLE_POLL_INTERVAL_MS: u64 = 100;
+
 /// Maintains a connection to the server and lazily returns documents from a
 /// query.
 ///
@@ -51,6 +69,12 @@ pub const DEFAULT_BATCH_SIZE: i32 = 20;
 /// `count` - How many documents have been returned so far.
 /// `buffer` - A cache for documents received from the query that have not
 ///            yet been returned.
+/// `tailable` - Whether the cursor remains open after its results are exhausted.
+/// `await_data` - Whether the server blocks getMore requests for a while when no
+///                new data is available.
+/// `max_await_time_ms` - How long to wait for new data before `try_next` gives up.
+/// `pinned_stream` - The connection that created the cursor, if the topology
+///                   is load-balanced.
 pub struct Cursor {
     client: Client,
     namespace: String,
@@ -61,6 +85,21 @@ pub struct Cursor {
     buffer: VecDeque<bson::Document>,
     read_preference: ReadPreference,
     cmd_type: CommandType,
+    tailable: bool,
+    await_data: bool,
+    max_await_time_ms: Option<i64>,
+    pinned_stream: Option<PooledStream>,
+    // The server that opened the cursor, if the documents were returned by a server.
+    host: Option<Host>,
+}
+
+/// A cursor that decodes each document it returns into a value of type `T`.
+///
+/// Documents that cannot be decoded are returned as `Error::DecoderError`s that include
+/// the document's `_id`; iteration may continue past them.
+pub struct TypedCursor<T: Decodable> {
+    cursor: Cursor,
+    marker: PhantomData<T>,
 }
 
 macro_rules! try_or_emit {
@@ -120,6 +159,55 @@ impl Cursor {
                       read_pref)
     }
 
+    /// Constructs a new Cursor for a database command sent over a stream returned by
+    /// `select_stream`.
+    pub fn command_cursor_selected(selected: (PooledStream, bool, bool),
+                                   client: Client,
+                                   db: &str,
+                                   doc: bson::Document,
+                                   cmd_type: CommandType,
+                                   read_pref: ReadPreference)
+                                   -> Result<Cursor> {
+        Cursor::query_selected(selected,
+                               client.clone(),
+                               format!("{}.$cmd", db),
+                               1,
+                               OpQueryFlags::no_flags(),
+                               0,
+                               0,
+                               doc,
+                               None,
+                               cmd_type,
+                               true,
+                               read_pref)
+    }
+
+    /// Creates an exhausted cursor that returns the given documents, for commands that
+    /// return their results inline rather than through a server-side cursor.
+    pub fn with_documents(client: Client,
+                          namespace: String,
+                          documents: Vec<bson::Document>,
+                          cmd_type: CommandType,
+                          read_pref: ReadPreference)
+                          -> Cursor {
+        Cursor {
+            client: client,
+            namespace: namespace,
+            batch_size: DEFAULT_BATCH_SIZE,
+            cursor_id: 0,
+            limit: 0,
+            count: 0,
+            buffer: VecDeque::from(documents),
+            read_preference: read_pref,
+            cmd_type: cmd_type,
+            tailable: false,
+            await_data: false,
+            max_await_time_ms: None,
+            pinned_stream: None,
+            host: None,
+        }
+    }
+
     fn get_bson_and_cid_from_message(message: Message)
                                      -> Result<(bson::Document, VecDeque<bson::Document>, i64)> {
         match message {
@@ -129,11 +217,29 @@ impl Cursor {
 
                 if !docs.is_empty() {
                     out_doc = docs[0].clone();
-                    if let Some(&Bson::I32(ref code)) = docs[0].get("code") {
+
+                    // State change errors are reported by code so that the topology can
+                    // stop selecting the server.
+                    if let Some(code) = Cursor::get_state_change_error(&docs[0]) {
+                        return Err(Error::CodedError(code, Cursor::get_errmsg(&docs[0], code)));
+                    }
+
+                    // Network errors between servers are also reported by code, so that
+                    // writes that fail with them can be retried.
+                    if let Some(code) = Cursor::get_number(&docs[0], "code") {
+                        if let Some(code) = ErrorCode::from_i32(code as i32) {
+                            if code.is_network_error() {
+                                let msg = Cursor::get_errmsg(&docs[0], code);
+                                return Err(Error::CodedError(code, msg));
+                            }
+                        }
+                    }
+
+                    if let Some(code) = Cursor::get_number(&docs[0], "code") {
                         // If command doesn't exist or namespace not found, return
                         // an empty array instead of throwing an error.
-                        if *code != ErrorCode::CommandNotFound as i32 &&
-                           *code != ErrorCode::NamespaceNotFound as i32 {
+                        if code != ErrorCode::CommandNotFound as i64 &&
+                           code != ErrorCode::NamespaceNotFound as i64 {
                             if let Some(&Bson::String(ref msg)) = docs[0].get("errmsg") {
                                 return Err(Error::OperationError(msg.to_owned()));
                             }
@@ -151,6 +257,61 @@ impl Cursor {
         }
     }
 
+    // Classifies a failed reply as a "not master" or "node is recovering" error, using
+    // the message for servers that do not report an error code.
+    fn get_state_change_error(doc: &bson::Document) -> Option<ErrorCode> {
+        let failed = match Cursor::get_number(doc, "ok") {
+            Some(ok) => ok == 0,
+            None => doc.contains_key("$err"),
+        };
+
+        if !failed {
+            return None;
+        }
+
+        if let Some(code) = Cursor::get_number(doc, "code") {
+            return ErrorCode::from_i32(code as i32)
+                .and_then(|code| if code.is_state_change_error() {
+                    Some(code)
+                } else {
+                    None
+                });
+        }
+
+        let message = match (doc.get("errmsg"), doc.get("$err")) {
+            (Some(&Bson::String(ref msg)), _) |
+            (_, Some(&Bson::String(ref msg))) => msg,
+            _ => return None,
+        };
+
+        if message.contains("node is recovering") {
+            Some(ErrorCode::NotMasterOrSecondaryCode)
+        } else if message.contains("not master") {
+            Some(ErrorCode::NotMaster)
+        } else {
+            None
+        }
+    }
+
+    // Reads a numeric field of a reply, which servers may send as any numeric type.
+    fn get_number(doc: &bson::Document, key: &str) -> Option<i64> {
+        match doc.get(key) {
+            Some(&Bson::I32(v)) => Some(v as i64),
+            Some(&Bson::I64(v)) => Some(v),
+            Some(&Bson::FloatingPoint(v)) => Some(v as i64),
+            _ => None,
+        }
+    }
+
+    // Returns the message of a failed reply, or the name of its code if it has none.
+    fn get_errmsg(doc: &bson::Document, code: ErrorCode) -> String {
+        match (doc.get("errmsg"), doc.get("$err")) {
+            (Some(&Bson::String(ref msg)), _) |
+            (_, Some(&Bson::String(ref msg))) => msg.to_owned(),
+            _ => format!("{}", code),
+        }
+    }
+
     fn get_bson_and_cursor_info_from_command_message
         (message: Message)
          -> Result<(bson::Document, VecDeque<bson::Document>, i64, String)> {
@@ -166,7 +327,15 @@ impl Cursor {
         if let Some(&Bson::Document(ref cursor)) = doc.get("cursor") {
             if let Some(&Bson::I64(ref id)) = cursor.get("id") {
                 if let Some(&Bson::String(ref ns)) = cursor.get("ns") {
-                    if let Some(&Bson::Array(ref batch)) = cursor.get("firstBatch") {
+                    // Replies to getMore commands return their documents in a
+                    // `nextBatch` rather than a `firstBatch`.
+                    let batch = match (cursor.get("firstBatch"), cursor.get("nextBatch")) {
+                        (Some(batch), _) |
+                        (_, Some(batch)) => Some(batch),
+                        _ => None,
+                    };
+
+                    if let Some(&Bson::Array(ref batch)) = batch {
 
                         // Extract first batch documents
                         let map = batch.iter()
@@ -223,13 +392,171 @@ impl Cursor {
                  read_pref: ReadPreference)
                  -> Result<Cursor> {
 
-        // Select a server stream from the topology.
-        let (stream, slave_ok, send_read_pref) = if cmd_type.is_write_command() {
-            (try!(client.acquire_write_stream()), false, false)
-        } else {
-            try!(client.acquire_stream(read_pref.to_owned()))
+        let selected = try!(Cursor::select_stream(&client, &cmd_type, &read_pref));
+        Cursor::query_selected(selected,
+                               client,
+                               namespace,
+                               batch_size,
+                               flags,
+                               number_to_skip,
+                               number_to_return,
+                               query,
+                               return_field_selector,
+                               cmd_type,
+                               is_cmd_cursor,
+                               read_pref)
+    }
+
+    /// Executes a query over a stream returned by `select_stream`. Operations whose query
+    /// depends on the features of the server select a stream first, so that the features
+    /// are checked on the server that runs the query.
+    ///
+    /// A retryable read that fails with a network or failover error is retried once on a
+    /// newly selected server, provided that it supports at least the wire version of the
+    /// first one. Writes are retried by the collection, which gives them a transaction
+    /// number that lets the server recognize the retry.
+    pub fn query_selected(selected: (PooledStream, bool, bool),
+                          client: Client,
+                          namespace: String,
+                          batch_size: i32,
+                          flags: OpQueryFlags,
+                          number_to_skip: i32,
+                          number_to_return: i32,
+                          query: bson::Document,
+                          return_field_selector: Option<bson::Document>,
+                          cmd_type: CommandType,
+                          is_cmd_cursor: bool,
+                          read_pref: ReadPreference)
+                          -> Result<Cursor> {
+
+        let retryable = Cursor::is_retryable(&client, &cmd_type, &selected.0);
+        let wire_version = client.topology.stream_wire_version(&selected.0).ok();
+
+        let result = Cursor::query_selected_attempt(selected,
+                                                    client.clone(),
+                                                    namespace.clone(),
+                                                    batch_size,
+                                                    flags,
+                                                    number_to_skip,
+                                                    number_to_return,
+                                                    query.clone(),
+                                                    return_field_selector.clone(),
+                                                    cmd_type.clone(),
+                                                    is_cmd_cursor,
+                                                    read_pref.clone(),
+                                                    false);
+
+        match result {
+            Err(ref err) if retryable && err.is_retryable() => (),
+            _ => return result,
+        }
+
+        // Retry once on a newly selected server. The original outcome is reported if no
+        // server that is able to take the retry can be selected.
+        let selected = match Cursor::select_stream(&client, &cmd_type, &read_pref) {
+            Ok(selected) => selected,
+            Err(_) => return result,
         };
 
+        if !Cursor::is_retryable(&client, &cmd_type, &selected.0) {
+            return result;
+        }
+
+        // The query may rely on features of the first server.
+        let retry_version = client.topology.stream_wire_version(&selected.0).ok();
+        if retry_version < wire_version {
+            return result;
+        }
+
+        Cursor::query_selected_attempt(selected,
+                                       client,
+                                       namespace,
+                                       batch_size,
+                                       flags,
+                                       number_to_skip,
+                                       number_to_return,
+                                       query,
+                                       return_field_selector,
+                                       cmd_type,
+                                       is_cmd_cursor,
+                                       read_pref,
+                                       true)
+    }
+
+    /// Executes a query that retries a failed write over a stream returned by
+    /// `select_stream`, reporting it to command listeners as a retry. The query is not
+    /// retried again.
+    pub fn retry_selected(selected: (PooledStream, bool, bool),
+                          client: Client,
+                          namespace: String,
+                          batch_size: i32,
+                          flags: OpQueryFlags,
+                          number_to_skip: i32,
+                          number_to_return: i32,
+                          query: bson::Document,
+                          return_field_selector: Option<bson::Document>,
+                          cmd_type: CommandType,
+                          is_cmd_cursor: bool,
+                          read_pref: ReadPreference)
+                          -> Result<Cursor> {
+        Cursor::query_selected_attempt(selected,
+                                       client,
+                                       namespace,
+                                       batch_size,
+                                       flags,
+                                       number_to_skip,
+                                       number_to_return,
+                                       query,
+                                       return_field_selector,
+                                       cmd_type,
+                                       is_cmd_cursor,
+                                       read_pref,
+                                       true)
+    }
+
+    /// Selects a server stream for a command of the given type, along with whether the
+    /// slave_ok flag and the read preference should be sent to it. Write commands are sent
+    /// to a writable server, and other commands to a server suitable for the read preference.
+    pub fn select_stream(client: &Client,
+                         cmd_type: &CommandType,
+                         read_pref: &ReadPreference)
+                         -> Result<(PooledStream, bool, bool)> {
+        if cmd_type.is_write_command() {
+            Ok((try!(client.acquire_write_stream()), false, false))
+        } else {
+            client.acquire_stream(read_pref.to_owned())
+        }
+    }
+
+    // Returns true if the operation is a read that may be retried on the selected server.
+    // Only the initial query is retried; the getMore commands of cursors that have already
+    // returned batches are not, since the position of the server-side cursor would be lost.
+    fn is_retryable(client: &Client, cmd_type: &CommandType, stream: &PooledStream) -> bool {
+        client.retry_reads && cmd_type.is_retryable_read() &&
+        client.topology
+            .stream_wire_version(stream)
+            .map(|version| version >= RETRYABLE_READS_WIRE_VERSION)
+            .unwrap_or(false)
+    }
+
+    // Sends a query over a selected server stream.
+    fn query_selected_attempt(selected: (PooledStream, bool, bool),
+                              client: Client,
+                              namespace: String,
+                              batch_size: i32,
+                              flags: OpQueryFlags,
+                              number_to_skip: i32,
+                              number_to_return: i32,
+                              query: bson::Document,
+                              return_field_selector: Option<bson::Document>,
+                              cmd_type: CommandType,
+                              is_cmd_cursor: bool,
+                              read_pref: ReadPreference,
+                              retry: bool)
+                              -> Result<Cursor> {
+
+        let (stream, slave_ok, send_read_pref) = selected;
+
         // Set slave_ok flag based on the result from server selection.
         let new_flags = if !slave_ok {
             flags
@@ -257,18 +584,19 @@ impl Cursor {
             }
         };
 
-        Cursor::query_with_stream(stream,
-                                  client,
-                                  namespace,
-                                  batch_size,
-                                  new_flags,
-                                  number_to_skip,
-                                  number_to_return,
-                                  new_query,
-                                  return_field_selector,
-                                  cmd_type,
-                                  is_cmd_cursor,
-                                  Some(read_pref))
+        Cursor::query_with_stream_attempt(stream,
+                                          client,
+                                          namespace,
+                                          batch_size,
+                                          new_flags,
+                                          number_to_skip,
+                                          number_to_return,
+                                          new_query,
+                                          return_field_selector,
+                                          cmd_type,
+                                          is_cmd_cursor,
+                                          Some(read_pref),
+                                          retry)
     }
 
     pub fn query_with_stream(stream: PooledStream,
@@ -284,10 +612,44 @@ impl Cursor {
                              is_cmd_cursor: bool,
                              read_pref: Option<ReadPreference>)
                              -> Result<Cursor> {
+        Cursor::query_with_stream_attempt(stream,
+                                          client,
+                                          namespace,
+                                          batch_size,
+                                          flags,
+                                          number_to_skip,
+                                          number_to_return,
+                                          query,
+                                          return_field_selector,
+                                          cmd_type,
+                                          is_cmd_cursor,
+                                          read_pref,
+                                          false)
+    }
+
+    // Sends a query over a stream, reporting whether it retries a failed attempt to the
+    // command listeners.
+    fn query_with_stream_attempt(stream: PooledStream,
+                                 client: Client,
+                                 namespace: String,
+                                 batch_size: i32,
+                                 flags: OpQueryFlags,
+                                 number_to_skip: i32,
+                                 number_to_return: i32,
+                                 query: bson::Document,
+                                 return_field_selector: Option<bson::Document>,
+                                 cmd_type: CommandType,
+                                 is_cmd_cursor: bool,
+                                 read_pref: Option<ReadPreference>,
+                                 retry: bool)
+                                 -> Result<Cursor> {
 
         let mut stream = stream;
+        let host = stream.host().clone();
         let mut socket = stream.get_socket();
         let req_id = client.get_req_id();
+        let tailable = flags.tailable_cursor;
+        let await_data = flags.await_data;
 
         let index = namespace.find('.').unwrap_or(namespace.len());
         let db_name = String::from(&namespace[..index]);
@@ -311,7 +673,7 @@ impl Cursor {
         };
 
         let command = match cmd_type {
-            CommandType::Find => {
+            CommandType::Find if !is_cmd_cursor => {
                 doc! {
                 "find" => coll_name,
                 "filter" => filter,
@@ -344,6 +706,7 @@ impl Cursor {
                 command_name: String::from(cmd_name),
                 request_id: req_id as i64,
                 connection_string: connstring.clone(),
+                retry: retry,
             });
 
             if let Err(_) = hook_result {
@@ -366,27 +729,28 @@ impl Cursor {
 
         let fin_time = time::precise_time_ns();
 
-        let (doc, buf, cursor_id, namespace) = if is_cmd_cursor {
-            try_or_emit!(cmd_type,
-                         cmd_name,
-                         req_id,
-                         connstring,
-                         Cursor::get_bson_and_cursor_info_from_command_message(reply),
-                         client)
+        let parsed = if is_cmd_cursor {
+            Cursor::get_bson_and_cursor_info_from_command_message(reply)
         } else {
-            let (doc, buf, id) = try_or_emit!(cmd_type,
-                                              cmd_name,
-                                              req_id,
-                                              connstring,
-                                              Cursor::get_bson_and_cid_from_message(reply),
-                                              client);
-            (doc, buf, id, namespace)
+            Cursor::get_bson_and_cid_from_message(reply)
+                .map(|(doc, buf, id)| (doc, buf, id, namespace))
         };
 
+        if let Err(Error::CodedError(code, ref msg)) = parsed {
+            client.topology.mark_server_unknown(client.clone(), &host, code, msg);
+        }
+
+        let (doc, buf, cursor_id, namespace) = try_or_emit!(cmd_type,
+                                                            cmd_name,
+                                                            req_id,
+                                                            connstring,
+                                                            parsed,
+                                                            client);
+
         let vec: Vec<_> = buf.iter().map(|doc| Bson::Document(doc.clone())).collect();
 
         let reply = match cmd_type {
-            CommandType::Find => {
+            CommandType::Find if !is_cmd_cursor => {
                 doc! {
                 "cursor" => {
                     "id" => cursor_id,
@@ -412,6 +776,18 @@ impl Cursor {
         let read_preference =
             read_pref.unwrap_or_else(|| ReadPreference::new(ReadMode::Primary, None));
 
+        if cursor_id != 0 {
+            client.register_cursor(host.clone(), cursor_id);
+        }
+
+        // Behind a load balancer, only the connection that created the cursor
+        // can continue to iterate it.
+        let pinned_stream = if cursor_id != 0 && client.topology.is_load_balanced() {
+            Some(stream)
+        } else {
+            None
+        };
+
         Ok(Cursor {
             client: client,
             namespace: namespace,
@@ -422,31 +798,93 @@ impl Cursor {
             buffer: buf,
             read_preference: read_preference,
             cmd_type: cmd_type.clone(),
+            tailable: tailable,
+            await_data: await_data,
+            max_await_time_ms: None,
+            pinned_stream: pinned_stream,
+            host: Some(host),
         })
     }
 
     fn get_from_stream(&mut self) -> Result<()> {
-        let (mut stream, _, _) = try!(self.client.acquire_stream(self.read_preference.to_owned()));
-        let mut socket = stream.get_socket();
+        self.get_more(None).map(|_| ())
+    }
 
-        let req_id = self.client.get_req_id();
-        let get_more = Message::new_get_more(req_id,
-                                             self.namespace.to_owned(),
-                                             self.batch_size,
-                                             self.cursor_id);
+    // Sends a getMore for the next batch of documents. If an await time is given and the
+    // server supports the getMore command, the command is sent with `maxTimeMS` so that
+    // the server waits for new documents itself; returns whether it did so.
+    fn get_more(&mut self, max_await_time_ms: Option<i64>) -> Result<bool> {
+        // Cursors only exist on the server that opened them, so getMores must be sent
+        // there rather than to a newly selected server.
+        let mut stream = match (self.pinned_stream.take(), self.host.as_ref()) {
+            (Some(stream), _) => stream,
+            (None, Some(host)) => try!(self.client.topology.acquire_host_stream(host)),
+            (None, None) => try!(self.client.acquire_stream(self.read_preference.to_owned())).0,
+        };
+
+        let pinned = self.client.topology.is_load_balanced();
+        let host = stream.host().clone();
+
+        let max_await_time_ms = match max_await_time_ms {
+            Some(ms) => {
+                match self.client.topology.stream_wire_version(&stream) {
+                    Ok(version) if version >= GET_MORE_COMMAND_WIRE_VERSION => Some(ms),
+                    _ => None,
+                }
+            }
+            None => None,
+        };
+
+        let mut socket = stream.get_socket();
 
         let index = self.namespace.rfind('.').unwrap_or(self.namespace.len());
         let db_name = String::from(&self.namespace[..index]);
         let cmd_name = String::from("get_more");
         let connstring = format!("{}", try!(socket.get_ref().peer_addr()));
 
+        let req_id = self.client.get_req_id();
+        let (get_more, command) = match max_await_time_ms {
+            Some(ms) => {
+                let (db, coll) = match self.namespace.find('.') {
+                    Some(index) => (&self.namespace[..index], &self.namespace[index + 1..]),
+                    None => (&self.namespace[..], ""),
+                };
+
+                let command = doc! {
+                    "getMore" => (self.cursor_id),
+                    "collection" => coll,
+                    "batchSize" => (self.batch_size),
+                    "maxTimeMS" => ms
+                };
+
+                // The cursor may have been opened on a secondary.
+                let flags = OpQueryFlags { slave_ok: true, ..OpQueryFlags::no_flags() };
+                let message = try!(Message::new_query(req_id,
+                                                      flags,
+                                                      format!("{}.$cmd", db),
+                                                      0,
+                                                      1,
+                                                      command.clone(),
+                                                      None));
+                (message, command)
+            }
+            None => {
+                let message = Message::new_get_more(req_id,
+                                                    self.namespace.to_owned(),
+                                                    self.batch_size,
+                                                    self.cursor_id);
+                (message, doc! { "cursor_id" => (self.cursor_id) })
+            }
+        };
+
         if self.cmd_type != CommandType::Suppressed {
             let hook_result = self.client.run_start_hooks(&CommandStarted {
-                command: doc! { "cursor_id" => (self.cursor_id) },
+                command: command,
                 database_name: db_name,
                 command_name: cmd_name.clone(),
                 request_id: req_id as i64,
                 connection_string: connstring.clone(),
+                retry: false,
             });
 
             if let Err(_) = hook_result {
@@ -462,9 +900,31 @@ impl Cursor {
                      self.client);
         let reply = try!(Message::read(socket.get_mut()));
 
-        let (_, v, _) = try!(Cursor::get_bson_and_cid_from_message(reply));
+        let parsed = if max_await_time_ms.is_some() {
+            Cursor::get_bson_and_cursor_info_from_command_message(reply)
+                .map(|(doc, buf, id, _)| (doc, buf, id))
+        } else {
+            Cursor::get_bson_and_cid_from_message(reply)
+        };
+
+        if let Err(Error::CodedError(code, ref msg)) = parsed {
+            self.client.topology.mark_server_unknown(self.client.clone(), &host, code, msg);
+        }
+
+        let (_, v, cursor_id) = try!(parsed);
         self.buffer.extend(v);
-        Ok(())
+
+        // The server closes the cursor once it has been exhausted.
+        if cursor_id == 0 {
+            if let Some(ref host) = self.host {
+                self.client.deregister_cursor(host, self.cursor_id);
+            }
+            self.cursor_id = 0;
+        } else if pinned {
+            self.pinned_stream = Some(stream);
+        }
+
+        Ok(max_await_time_ms.is_some())
     }
 
     /// Attempts to read a specified number of BSON documents from the cursor.
@@ -502,6 +962,81 @@ impl Cursor {
         self.next_n(n)
     }
 
+    /// Converts the cursor into one that decodes each document into a value of type `T`.
+    pub fn with_type<T: Decodable>(self) -> TypedCursor<T> {
+        TypedCursor::new(self)
+    }
+
+    /// Sets how long `try_next` waits for new documents on a tailable cursor with
+    /// `awaitData` before returning `Ok(None)`. The time is sent to the server as the
+    /// getMore's `maxTimeMS`; older servers block each getMore for up to a second, so
+    /// their getMores are repeated until this time has elapsed. If unset, a single
+    /// getMore is sent.
+    pub fn set_max_await_time_ms(&mut self, max_await_time_ms: Option<i64>) {
+        self.max_await_time_ms = max_await_time_ms;
+    }
+
+    /// Sets whether the cursor is tailable, for cursors created by a find command that
+    /// requested a tailable cursor rather than through query flags.
+    pub fn set_cursor_type(&mut self, cursor_type: CursorType) {
+        self.tailable = cursor_type != CursorType::NonTailable;
+        self.await_data = cursor_type == CursorType::TailableAwait;
+    }
+
+    /// Returns true if the server has closed the cursor, either because its results have
+    /// been exhausted or because it is no longer valid, such as when a tailed capped
+    /// collection is dropped. Buffered documents may still be returned.
+    pub fn is_closed(&self) -> bool {
+        self.cursor_id == 0
+    }
+
+    /// Attempts to read a BSON document from the cursor without ending iteration.
+    ///
+    /// # Return value
+    ///
+    /// Returns a BSON document if one is available, or `None` if no documents are
+    /// currently available. A tailable cursor may return further documents after
+    /// returning `None`, until `is_closed` returns true.
+    pub fn try_next(&mut self) -> Result<Option<bson::Document>> {
+        if self.limit > 0 && self.count >= self.limit {
+            return Ok(None);
+        }
+
+        if self.buffer.is_empty() && self.limit != 1 && self.cursor_id != 0 {
+            try!(self.await_from_stream());
+        }
+
+        match self.buffer.pop_front() {
+            Some(doc) => {
+                self.count += 1;
+                Ok(Some(doc))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Requests more documents on an awaitData cursor, waiting up to the await time for
+    // them to arrive. Servers without the getMore command cannot be told how long to
+    // wait, so their getMores are repeated until documents arrive or the time elapses.
+    fn await_from_stream(&mut self) -> Result<()> {
+        let max_await_time_ms = match self.max_await_time_ms {
+            Some(ms) if self.await_data => ms,
+            _ => return self.get_from_stream(),
+        };
+
+        let start = time::precise_time_ns();
+        loop {
+            if try!(self.get_more(Some(max_await_time_ms))) {
+                return Ok(());
+            }
+
+            let elapsed_ms = ((time::precise_time_ns() - start) / 1000000) as i64;
+            if !self.buffer.is_empty() || self.cursor_id == 0 || elapsed_ms >= max_await_time_ms {
+                return Ok(());
+            }
+        }
+    }
+
     /// Checks whether there are any more documents for the cursor to return.
     ///
     /// # Return value
@@ -519,6 +1054,17 @@ impl Cursor {
     }
 }
 
+impl Drop for Cursor {
+    fn drop(&mut self) {
+        // Kill the server cursor if it is still open, so that it is not left to time out.
+        if self.cursor_id != 0 {
+            if let Some(ref host) = self.host {
+                self.client.kill_cursor(host, self.cursor_id, self.pinned_stream.take());
+            }
+        }
+    }
+}
+
 impl Iterator for Cursor {
     type Item = Result<bson::Document>;
 
@@ -528,18 +1074,73 @@ impl Iterator for Cursor {
     ///
     /// Returns a BSON document if there is another one to return; `None` if
     /// there are no more documents to return; or an Error if the request for
-    /// another document fails.
+    /// another document fails. Tailable cursors block until a new document is
+    /// available or the server closes the cursor.
     fn next(&mut self) -> Option<Result<bson::Document>> {
-        match self.has_next() {
-            Ok(true) => {
-                self.count += 1;
-                match self.buffer.pop_front() {
-                    Some(bson) => Some(Ok(bson)),
-                    None => None,
+        loop {
+            match self.try_next() {
+                Ok(Some(bson)) => return Some(Ok(bson)),
+                Ok(None) => {
+                    if !self.tailable || self.cursor_id == 0 ||
+                       (self.limit > 0 && self.count >= self.limit) {
+                        return None;
+                    }
                 }
+                Err(err) => return Some(Err(err)),
+            }
+
+            // An awaitData getMore already blocks on the server.
+            if !self.await_data {
+                thread::sleep(Duration::from_millis(TAILABLE_POLL_INTERVAL_MS));
             }
-            Ok(false) => None,
-            Err(err) => Some(Err(err)),
         }
     }
 }
+
+impl<T: Decodable> TypedCursor<T> {
+    /// Wraps a document cursor.
+    pub fn new(cursor: Cursor) -> TypedCursor<T> {
+        TypedCursor {
+            cursor: cursor,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying document cursor.
+    pub fn into_inner(self) -> Cursor {
+        self.cursor
+    }
+
+    /// Attempts to read and decode a document from the cursor without ending iteration.
+    /// See `Cursor::try_next`.
+    pub fn try_next(&mut self) -> Result<Option<T>> {
+        match try!(self.cursor.try_next()) {
+            Some(doc) => codec::decode_document(doc).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Attempts to read and decode a specified number of documents from the cursor.
+    pub fn next_n(&mut self, n: i32) -> Result<Vec<T>> {
+        let docs = try!(self.cursor.next_n(n));
+        docs.into_iter().map(codec::decode_document).collect()
+    }
+
+    /// Checks whether there are any more documents for the cursor to return.
+    pub fn has_next(&mut self) -> Result<bool> {
+        self.cursor.has_next()
+    }
+
+    /// Returns true if the server has closed the cursor. See `Cursor::is_closed`.
+    pub fn is_closed(&self) -> bool {
+        self.cursor.is_closed()
+    }
+}
+
+impl<T: Decodable> Iterator for TypedCursor<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        self.cursor.next().map(|result| result.and_then(codec::decode_document))
+    }
+}
@@ -64,12 +64,14 @@ use bson::Bson;
 use {Client, CommandType, ThreadedClient, Result};
 use Error::{CursorNotFoundError, OperationError, ResponseError};
 use coll::Collection;
-use coll::options::FindOptions;
-use common::{ReadPreference, WriteConcern};
+use coll::options::{COLLATION_WIRE_VERSION, FindOptions};
+use common::{ReadConcern, ReadMode, ReadPreference, WriteConcern};
 use cursor::{Cursor, DEFAULT_BATCH_SIZE};
+use pool::PooledStream;
 use self::options::{CreateCollectionOptions, CreateUserOptions, UserInfoOptions};
 use self::roles::Role;
 use semver::Version;
+use wire_protocol::flags::OpQueryFlags;
 use std::error::Error;
 use std::sync::Arc;
 
@@ -84,6 +86,8 @@ pub struct DatabaseInner {
     /// Describes the guarantees provided by MongoDB when reporting the success of a write
     /// operation.
     pub write_concern: WriteConcern,
+    /// Describes the consistency and isolation of data returned by read operations.
+    pub read_concern: ReadConcern,
 }
 
 pub type Database = Arc<DatabaseInner>;
@@ -108,6 +112,9 @@ pub trait ThreadedDatabase {
                              read_preference: Option<ReadPreference>,
                              write_concern: Option<WriteConcern>)
                              -> Collection;
+    /// Creates a copy of the database representation with a different read concern,
+    /// which is inherited by its collections.
+    fn with_read_concern(&self, read_concern: ReadConcern) -> Database;
     /// Return a unique operational request id.
     fn get_req_id(&self) -> i32;
     /// Generates a cursor for a relevant operational command.
@@ -164,6 +171,83 @@ pub trait ThreadedDatabase {
                  -> Result<Vec<bson::Document>>;
 }
 
+impl DatabaseInner {
+    /// Sends a command over a stream returned by `Cursor::select_stream`, for commands
+    /// that depend on the features of the server that runs them.
+    pub fn command_selected(&self,
+                            selected: (PooledStream, bool, bool),
+                            spec: bson::Document,
+                            cmd_type: CommandType,
+                            read_pref: ReadPreference)
+                            -> Result<bson::Document> {
+        self.command_attempt(selected, spec, cmd_type, read_pref, false)
+    }
+
+    /// Sends a command that retries a failed write over a stream returned by
+    /// `Cursor::select_stream`, reporting it to command listeners as a retry.
+    pub fn retry_command_selected(&self,
+                                  selected: (PooledStream, bool, bool),
+                                  spec: bson::Document,
+                                  cmd_type: CommandType,
+                                  read_pref: ReadPreference)
+                                  -> Result<bson::Document> {
+        self.command_attempt(selected, spec, cmd_type, read_pref, true)
+    }
+
+    // Sends a command over a selected stream, returning the first document of the reply.
+    fn command_attempt(&self,
+                       selected: (PooledStream, bool, bool),
+                       spec: bson::Document,
+                       cmd_type: CommandType,
+                       read_pref: ReadPreference,
+                       retry: bool)
+                       -> Result<bson::Document> {
+        let namespace = format!("{}.$cmd", self.name);
+        let client = self.client.clone();
+        let query = if retry {
+            Cursor::retry_selected
+        } else {
+            Cursor::query_selected
+        };
+
+        let mut cursor = try!(query(selected,
+                                    client,
+                                    namespace,
+                                    1,
+                                    OpQueryFlags::no_flags(),
+                                    0,
+                                    1,
+                                    spec.clone(),
+                                    None,
+                                    cmd_type,
+                                    false,
+                                    read_pref));
+        match cursor.next() {
+            Some(Ok(doc)) => Ok(doc),
+            Some(Err(err)) => Err(err),
+            None => {
+                Err(OperationError(format!("Failed to execute command with spec {:?}.", spec)))
+            }
+        }
+    }
+
+    /// Generates a cursor for a command sent over a stream returned by
+    /// `Cursor::select_stream`.
+    pub fn command_cursor_selected(&self,
+                                   selected: (PooledStream, bool, bool),
+                                   spec: bson::Document,
+                                   cmd_type: CommandType,
+                                   read_pref: ReadPreference)
+                                   -> Result<Cursor> {
+        Cursor::command_cursor_selected(selected,
+                                        self.client.clone(),
+                                        &self.name[..],
+                                        spec,
+                                        cmd_type,
+                                        read_pref)
+    }
+}
+
 impl ThreadedDatabase for Database {
     fn open(client: Client,
             name: &str,
@@ -172,12 +256,14 @@ impl ThreadedDatabase for Database {
             -> Database {
         let rp = read_preference.unwrap_or(client.read_preference.to_owned());
         let wc = write_concern.unwrap_or(client.write_concern.to_owned());
+        let rc = client.read_concern.to_owned();
 
         Arc::new(DatabaseInner {
             name: String::from(name),
             client: client,
             read_preference: rp,
             write_concern: wc,
+            read_concern: rc,
         })
     }
 
@@ -207,6 +293,16 @@ impl ThreadedDatabase for Database {
                         write_concern)
     }
 
+    fn with_read_concern(&self, read_concern: ReadConcern) -> Database {
+        Arc::new(DatabaseInner {
+            name: self.name.to_owned(),
+            client: self.client.clone(),
+            read_preference: self.read_preference.to_owned(),
+            write_concern: self.write_concern.to_owned(),
+            read_concern: read_concern,
+        })
+    }
+
     fn get_req_id(&self) -> i32 {
         self.client.get_req_id()
     }
@@ -322,7 +418,22 @@ impl ThreadedDatabase for Database {
 
         doc.insert("flags", Bson::I32(flag_one + flag_two));
 
-        self.command(doc, CommandType::CreateCollection, None).map(|_| ())
+        let read_pref = ReadPreference::new(ReadMode::Primary, None);
+        let selected = try!(Cursor::select_stream(&self.client,
+                                                  &CommandType::CreateCollection,
+                                                  &read_pref));
+
+        if let Some(collation) = coll_options.collation {
+            let wire_version = try!(self.client.topology.stream_wire_version(&selected.0));
+            if wire_version < COLLATION_WIRE_VERSION {
+                return Err(OperationError(String::from("The server does not support \
+                                                        collations.")));
+            }
+            doc.insert("collation", Bson::Document(collation.to_bson()));
+        }
+
+        self.command_selected(selected, doc, CommandType::CreateCollection, read_pref)
+            .map(|_| ())
     }
 
     fn create_user(&self,
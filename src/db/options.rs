@@ -1,5 +1,6 @@
 //! Options for database-level commands.
 use bson::Document;
+use coll::options::Collation;
 use common::WriteConcern;
 use db::roles::Role;
 
@@ -11,6 +12,8 @@ pub struct CreateCollectionOptions {
     pub max: Option<i64>,
     pub use_power_of_two_sizes: bool,
     pub no_padding: bool,
+    /// The default collation for the collection and its indexes.
+    pub collation: Option<Collation>,
 }
 
 impl CreateCollectionOptions {
@@ -22,6 +25,7 @@ impl CreateCollectionOptions {
             max: None,
             use_power_of_two_sizes: true,
             no_padding: false,
+            collation: None,
         }
     }
 }
@@ -58,8 +58,8 @@ pub enum Error {
     CursorNotFoundError,
     /// The application failed to secure a mutex due to a poisoned lock.
     PoisonLockError,
-    /// A server error with a given code.
-    CodedError(ErrorCode),
+    /// A server error with a given code, along with the message reported by the server.
+    CodedError(ErrorCode, String),
     /// The client was unable to emit the events to the listeners due to a poisoned lock;
     /// all event listeners were dropped, so they will have to be registered again. If the
     /// client is unable to emit a failure result, the error it failed to report is bundled
@@ -139,6 +139,18 @@ impl<T> From<sync::PoisonError<T>> for Error {
     }
 }
 
+impl Error {
+    /// Returns true if an operation that failed with the error may be safely retried, either
+    /// because the network failed or because the server stopped being able to serve it.
+    pub fn is_retryable(&self) -> bool {
+        match *self {
+            Error::IoError(_) => true,
+            Error::CodedError(ref code, _) => code.is_retryable_error(),
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -156,7 +168,7 @@ impl fmt::Display for Error {
             Error::PoisonLockError => {
                 write!(fmt, "Socket lock poisoned while attempting to access.")
             }
-            Error::CodedError(ref err) => write!(fmt, "{}", err),
+            Error::CodedError(ref err, ref msg) => write!(fmt, "{}: {}", err, msg),
             Error::EventListenerError(ref err) => {
                 match *err {
                     Some(ref e) => {
@@ -185,7 +197,7 @@ impl error::Error for Error {
             Error::IoError(ref inner) => inner.description(),
             Error::CursorNotFoundError => "No cursor found for cursor operation.",
             Error::PoisonLockError => "Socket lock poisoned while attempting to access.",
-            Error::CodedError(ref err) => err.to_str(),
+            Error::CodedError(_, ref msg) => msg,
             Error::EventListenerError(ref err) => {
                 match *err {
                     Some(_) => "Due to a poisoned lock on the listeners, unable to emit failure",
@@ -226,7 +238,7 @@ impl error::Error for Error {
             Error::ResponseError(_) |
             Error::CursorNotFoundError |
             Error::PoisonLockError |
-            Error::CodedError(_) |
+            Error::CodedError(..) |
             Error::EventListenerError(_) |
             Error::MaliciousServerError(_) |
             Error::DefaultError(_) => None,
@@ -235,7 +247,7 @@ impl error::Error for Error {
 }
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ErrorCode {
     OK = 0,
     InternalError = 1,
@@ -376,10 +388,12 @@ pub enum ErrorCode {
     IncompatibleShardingConfigVersion = 137,
     RemoteOplogStale = 138,
     JSInterpreterFailure = 139,
+    PrimarySteppedDown = 189,
     NotMaster = 10107,
     DuplicateKey = 11000,
     InterruptedAtShutdown = 11600,
     Interrupted = 11601,
+    InterruptedDueToReplStateChange = 11602,
     BackgroundOperationInProgressForDatabase = 12586,
     BackgroundOperationInProgressForNamespace = 12587,
     PrepareConfigsFailedCode = 13104,
@@ -394,6 +408,168 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
+    /// Maps a server error code to its `ErrorCode`, or `None` if the code is not known.
+    pub fn from_i32(code: i32) -> Option<ErrorCode> {
+        Some(match code {
+            0 => ErrorCode::OK,
+            1 => ErrorCode::InternalError,
+            2 => ErrorCode::BadValue,
+            3 => ErrorCode::OBSOLETE_DuplicateKey,
+            4 => ErrorCode::NoSuchKey,
+            5 => ErrorCode::GraphContainsCycle,
+            6 => ErrorCode::HostUnreachable,
+            7 => ErrorCode::HostNotFound,
+            8 => ErrorCode::UnknownError,
+            9 => ErrorCode::FailedToParse,
+            10 => ErrorCode::CannotMutateObject,
+            11 => ErrorCode::UserNotFound,
+            12 => ErrorCode::UnsupportedFormat,
+            13 => ErrorCode::Unauthorized,
+            14 => ErrorCode::TypeMismatch,
+            15 => ErrorCode::Overflow,
+            16 => ErrorCode::InvalidLength,
+            17 => ErrorCode::ProtocolError,
+            18 => ErrorCode::AuthenticationFailed,
+            19 => ErrorCode::CannotReuseObject,
+            20 => ErrorCode::IllegalOperation,
+            21 => ErrorCode::EmptyArrayOperation,
+            22 => ErrorCode::InvalidBSON,
+            23 => ErrorCode::AlreadyInitialized,
+            24 => ErrorCode::LockTimeout,
+            25 => ErrorCode::RemoteValidationError,
+            26 => ErrorCode::NamespaceNotFound,
+            27 => ErrorCode::IndexNotFound,
+            28 => ErrorCode::PathNotViable,
+            29 => ErrorCode::NonExistentPath,
+            30 => ErrorCode::InvalidPath,
+            31 => ErrorCode::RoleNotFound,
+            32 => ErrorCode::RolesNotRelated,
+            33 => ErrorCode::PrivilegeNotFound,
+            34 => ErrorCode::CannotBackfillArray,
+            35 => ErrorCode::UserModificationFailed,
+            36 => ErrorCode::RemoteChangeDetected,
+            37 => ErrorCode::FileRenameFailed,
+            38 => ErrorCode::FileNotOpen,
+            39 => ErrorCode::FileStreamFailed,
+            40 => ErrorCode::ConflictingUpdateOperators,
+            41 => ErrorCode::FileAlreadyOpen,
+            42 => ErrorCode::LogWriteFailed,
+            43 => ErrorCode::CursorNotFound,
+            45 => ErrorCode::UserDataInconsistent,
+            46 => ErrorCode::LockBusy,
+            47 => ErrorCode::NoMatchingDocument,
+            48 => ErrorCode::NamespaceExists,
+            49 => ErrorCode::InvalidRoleModification,
+            50 => ErrorCode::ExceededTimeLimit,
+            51 => ErrorCode::ManualInterventionRequired,
+            52 => ErrorCode::DollarPrefixedFieldName,
+            53 => ErrorCode::InvalidIdField,
+            54 => ErrorCode::NotSingleValueField,
+            55 => ErrorCode::InvalidDBRef,
+            56 => ErrorCode::EmptyFieldName,
+            57 => ErrorCode::DottedFieldName,
+            58 => ErrorCode::RoleModificationFailed,
+            59 => ErrorCode::CommandNotFound,
+            60 => ErrorCode::DatabaseNotFound,
+            61 => ErrorCode::ShardKeyNotFound,
+            62 => ErrorCode::OplogOperationUnsupported,
+            63 => ErrorCode::StaleShardVersion,
+            64 => ErrorCode::WriteConcernFailed,
+            65 => ErrorCode::MultipleErrorsOccurred,
+            66 => ErrorCode::ImmutableField,
+            67 => ErrorCode::CannotCreateIndex,
+            68 => ErrorCode::IndexAlreadyExists,
+            69 => ErrorCode::AuthSchemaIncompatible,
+            70 => ErrorCode::ShardNotFound,
+            71 => ErrorCode::ReplicaSetNotFound,
+            72 => ErrorCode::InvalidOptions,
+            73 => ErrorCode::InvalidNamespace,
+            74 => ErrorCode::NodeNotFound,
+            75 => ErrorCode::WriteConcernLegacyOK,
+            76 => ErrorCode::NoReplicationEnabled,
+            77 => ErrorCode::OperationIncomplete,
+            78 => ErrorCode::CommandResultSchemaViolation,
+            79 => ErrorCode::UnknownReplWriteConcern,
+            80 => ErrorCode::RoleDataInconsistent,
+            81 => ErrorCode::NoWhereParseContext,
+            82 => ErrorCode::NoProgressMade,
+            83 => ErrorCode::RemoteResultsUnavailable,
+            84 => ErrorCode::DuplicateKeyValue,
+            85 => ErrorCode::IndexOptionsConflict,
+            86 => ErrorCode::IndexKeySpecsConflict,
+            87 => ErrorCode::CannotSplit,
+            88 => ErrorCode::SplitFailed,
+            89 => ErrorCode::NetworkTimeout,
+            90 => ErrorCode::CallbackCanceled,
+            91 => ErrorCode::ShutdownInProgress,
+            92 => ErrorCode::SecondaryAheadOfPrimary,
+            93 => ErrorCode::InvalidReplicaSetConfig,
+            94 => ErrorCode::NotYetInitialized,
+            95 => ErrorCode::NotSecondary,
+            96 => ErrorCode::OperationFailed,
+            97 => ErrorCode::NoProjectionFound,
+            98 => ErrorCode::DBPathInUse,
+            99 => ErrorCode::WriteConcernNotDefined,
+            100 => ErrorCode::CannotSatisfyWriteConcern,
+            101 => ErrorCode::OutdatedClient,
+            102 => ErrorCode::IncompatibleAuditMetadata,
+            103 => ErrorCode::NewReplicaSetConfigurationIncompatible,
+            104 => ErrorCode::NodeNotElectable,
+            105 => ErrorCode::IncompatibleShardingMetadata,
+            106 => ErrorCode::DistributedClockSkewed,
+            107 => ErrorCode::LockFailed,
+            108 => ErrorCode::InconsistentReplicaSetNames,
+            109 => ErrorCode::ConfigurationInProgress,
+            110 => ErrorCode::CannotInitializeNodeWithData,
+            111 => ErrorCode::NotExactValueField,
+            112 => ErrorCode::WriteConflict,
+            113 => ErrorCode::InitialSyncFailure,
+            114 => ErrorCode::InitialSyncOplogSourceMissing,
+            115 => ErrorCode::CommandNotSupported,
+            116 => ErrorCode::DocTooLargeForCapped,
+            117 => ErrorCode::ConflictingOperationInProgress,
+            118 => ErrorCode::NamespaceNotSharded,
+            119 => ErrorCode::InvalidSyncSource,
+            120 => ErrorCode::OplogStartMissing,
+            121 => ErrorCode::DocumentValidationFailure,
+            122 => ErrorCode::OBSOLETE_ReadAfterOptimeTimeout,
+            123 => ErrorCode::NotAReplicaSet,
+            124 => ErrorCode::IncompatibleElectionProtocol,
+            125 => ErrorCode::CommandFailed,
+            126 => ErrorCode::RPCProtocolNegotiationFailed,
+            127 => ErrorCode::UnrecoverableRollbackError,
+            128 => ErrorCode::LockNotFound,
+            129 => ErrorCode::LockStateChangeFailed,
+            130 => ErrorCode::SymbolNotFound,
+            131 => ErrorCode::RLPInitializationFailed,
+            132 => ErrorCode::ConfigServersInconsistent,
+            133 => ErrorCode::FailedToSatisfyReadPreference,
+            134 => ErrorCode::XXX_TEMP_NAME_ReadCommittedCurrentlyUnavailable,
+            135 => ErrorCode::StaleTerm,
+            136 => ErrorCode::CappedPositionLost,
+            137 => ErrorCode::IncompatibleShardingConfigVersion,
+            138 => ErrorCode::RemoteOplogStale,
+            139 => ErrorCode::JSInterpreterFailure,
+            189 => ErrorCode::PrimarySteppedDown,
+            10107 => ErrorCode::NotMaster,
+            11000 => ErrorCode::DuplicateKey,
+            11600 => ErrorCode::InterruptedAtShutdown,
+            11601 => ErrorCode::Interrupted,
+            11602 => ErrorCode::InterruptedDueToReplStateChange,
+            12586 => ErrorCode::BackgroundOperationInProgressForDatabase,
+            12587 => ErrorCode::BackgroundOperationInProgressForNamespace,
+            13104 => ErrorCode::PrepareConfigsFailedCode,
+            13297 => ErrorCode::DatabaseDifferCase,
+            13334 => ErrorCode::ShardKeyTooBig,
+            13388 => ErrorCode::SendStaleConfig,
+            13435 => ErrorCode::NotMasterNoSlaveOkCode,
+            13436 => ErrorCode::NotMasterOrSecondaryCode,
+            14031 => ErrorCode::OutOfDiskSpace,
+            17280 => ErrorCode::KeyTooLong,
+            _ => return None,
+        })
+    }
+
     pub fn is_network_error(&self) -> bool {
         *self == ErrorCode::HostUnreachable || *self == ErrorCode::HostNotFound ||
         *self == ErrorCode::NetworkTimeout
@@ -404,6 +580,36 @@ impl ErrorCode {
         *self == ErrorCode::ExceededTimeLimit
     }
 
+    /// Returns true if the server is no longer a primary.
+    pub fn is_not_master(&self) -> bool {
+        *self == ErrorCode::NotMaster || *self == ErrorCode::NotMasterNoSlaveOkCode
+    }
+
+    /// Returns true if the server is recovering, stepping down, or shutting down.
+    pub fn is_node_recovering(&self) -> bool {
+        *self == ErrorCode::InterruptedAtShutdown ||
+        *self == ErrorCode::InterruptedDueToReplStateChange ||
+        *self == ErrorCode::NotMasterOrSecondaryCode ||
+        *self == ErrorCode::PrimarySteppedDown || *self == ErrorCode::ShutdownInProgress
+    }
+
+    /// Returns true if the server is shutting down, in which case its connections
+    /// will be closed.
+    pub fn is_shutdown(&self) -> bool {
+        *self == ErrorCode::InterruptedAtShutdown || *self == ErrorCode::ShutdownInProgress
+    }
+
+    /// Returns true if the error indicates that the server's replica set state has changed,
+    /// and it should no longer be selected until it has been checked again.
+    pub fn is_state_change_error(&self) -> bool {
+        self.is_not_master() || self.is_node_recovering()
+    }
+
+    /// Returns true if an operation that failed with the error may be safely retried.
+    pub fn is_retryable_error(&self) -> bool {
+        self.is_state_change_error() || self.is_network_error()
+    }
+
     pub fn is_index_creation_error(&self) -> bool {
         *self == ErrorCode::CannotCreateIndex || *self == ErrorCode::IndexOptionsConflict ||
         *self == ErrorCode::IndexKeySpecsConflict || *self == ErrorCode::IndexAlreadyExists
@@ -550,10 +756,12 @@ impl ErrorCode {
             ErrorCode::IncompatibleShardingConfigVersion => "IncompatibleShardingConfigVersion",
             ErrorCode::RemoteOplogStale => "RemoteOplogStale",
             ErrorCode::JSInterpreterFailure => "JSInterpreterFailure",
+            ErrorCode::PrimarySteppedDown => "PrimarySteppedDown",
             ErrorCode::NotMaster => "NotMaster",
             ErrorCode::DuplicateKey => "DuplicateKey",
             ErrorCode::InterruptedAtShutdown => "InterruptedAtShutdown",
             ErrorCode::Interrupted => "Interrupted",
+            ErrorCode::InterruptedDueToReplStateChange => "InterruptedDueToReplStateChange",
             ErrorCode::BackgroundOperationInProgressForDatabase => "BackgroundOperationInProgressForDatabase",
             ErrorCode::BackgroundOperationInProgressForNamespace => "BackgroundOperationInProgressForNamespace",
             ErrorCode::PrepareConfigsFailedCode => "PrepareConfigsFailedCode",
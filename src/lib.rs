@@ -81,11 +81,25 @@
 //! the driver's view of the current topology is updated in response to this. This allows the
 //! driver to be aware of the status of the server set it is communicating with, and to make server
 //! selections appropriately with regards to the user-specified `ReadPreference` and `WriteConcern`.
+//! Server checks for a client share a small, bounded pool of monitoring threads, regardless of
+//! the number of servers in the topology.
 //!
 //! ## Connection Pooling
 //!
 //! Each server within a MongoDB server set is maintained by the driver with a separate connection
 //! pool. By default, each pool has a maximum of 5 concurrent open connections.
+//!
+//! ## Shutting Down
+//!
+//! Server monitors and pooled connections are released when the client is shut down, which
+//! also kills any cursors that are still open. Dropping the last client handle performs the
+//! same cleanup on a best-effort basis.
+//!
+//! ```no_run
+//! # use mongodb::{Client, ThreadedClient};
+//! let client = Client::connect("localhost", 27017).unwrap();
+//! client.shutdown().unwrap();
+//! ```
 
 #[doc(html_root_url = "https://mongodbinc-interns.github.io/mongo-rust-driver-prototype")]
 #[macro_use(bson, doc)]
@@ -104,6 +118,7 @@ extern crate time;
 extern crate bufstream;
 
 pub mod db;
+pub mod codec;
 pub mod coll;
 pub mod common;
 pub mod connstring;
@@ -117,27 +132,34 @@ pub mod wire_protocol;
 mod apm;
 mod auth;
 mod command_type;
+mod session;
 
-pub use apm::{CommandStarted, CommandResult};
+pub use apm::{CommandStarted, CommandResult, TopologyEvent};
 pub use command_type::CommandType;
 pub use error::{Error, ErrorCode, Result};
 
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::ops::DerefMut;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::{AtomicIsize, Ordering, ATOMIC_ISIZE_INIT};
 
-use apm::Listener;
+use apm::{EventRunner, Listener};
 use bson::Bson;
-use common::{ReadPreference, ReadMode, WriteConcern};
-use connstring::ConnectionString;
+use common::{Acknowledgment, ReadConcern, ReadConcernLevel, ReadPreference, ReadMode,
+             WriteConcern};
+use connstring::{ConnectionString, Host};
 use db::{Database, ThreadedDatabase};
-use error::Error::ResponseError;
+use error::Error::{ArgumentError, ResponseError};
 use pool::PooledStream;
-use topology::{Topology, TopologyDescription, TopologyType, DEFAULT_HEARTBEAT_FREQUENCY_MS,
-               DEFAULT_LOCAL_THRESHOLD_MS, DEFAULT_SERVER_SELECTION_TIMEOUT_MS};
-use topology::server::Server;
+use session::ServerSessionPool;
+use wire_protocol::operations::Message;
+use topology::{Topology, TopologyDescription, TopologySnapshot, TopologyType,
+               DEFAULT_HEARTBEAT_FREQUENCY_MS, DEFAULT_LOCAL_THRESHOLD_MS,
+               DEFAULT_SERVER_SELECTION_TIMEOUT_MS};
+use topology::server::{Server, ServerType};
 
 /// Interfaces with a MongoDB server or replica set.
 pub struct ClientInner {
@@ -146,10 +168,21 @@ pub struct ClientInner {
     /// Describes the guarantees provided by MongoDB when reporting the success of a write
     /// operation.
     pub write_concern: WriteConcern,
+    /// Describes the consistency and isolation of data returned by read operations.
+    pub read_concern: ReadConcern,
+    /// Whether eligible writes are retried once after a network or failover error.
+    pub retry_writes: bool,
+    /// Whether reads are retried once after a network or failover error.
+    pub retry_reads: bool,
     req_id: Arc<AtomicIsize>,
     topology: Topology,
     listener: Listener,
     log_file: Option<Mutex<File>>,
+    // Server cursors that are currently held open by a `Cursor`, keyed by their host as well
+    // as their id, since cursor ids are only unique within a server.
+    cursors: Mutex<HashSet<(Host, i64)>>,
+    // Server sessions that are not in use by an operation.
+    session_pool: ServerSessionPool,
 }
 
 /// Configuration options for a client.
@@ -161,12 +194,21 @@ pub struct ClientOptions {
     pub read_preference: Option<ReadPreference>,
     /// Client-level write guarantees when reporting a write success.
     pub write_concern: Option<WriteConcern>,
+    /// Client-level consistency and isolation for read operations. Overrides the
+    /// `readConcernLevel` connection string option.
+    pub read_concern: Option<ReadConcern>,
     /// Frequency of server monitor updates; default 10000 ms.
     pub heartbeat_frequency_ms: u32,
     /// Timeout for selecting an appropriate server for operations; default 30000 ms.
     pub server_selection_timeout_ms: i64,
     /// The size of the latency window for selecting suitable servers; default 15 ms.
     pub local_threshold_ms: i64,
+    /// Whether eligible writes are retried once after a network or failover error.
+    /// Overrides the `retryWrites` connection string option; enabled by default.
+    pub retry_writes: Option<bool>,
+    /// Whether reads are retried once after a network or failover error. Overrides the
+    /// `retryReads` connection string option; enabled by default.
+    pub retry_reads: Option<bool>,
 }
 
 impl ClientOptions {
@@ -176,9 +218,12 @@ impl ClientOptions {
             log_file: None,
             read_preference: None,
             write_concern: None,
+            read_concern: None,
             heartbeat_frequency_ms: DEFAULT_HEARTBEAT_FREQUENCY_MS,
             server_selection_timeout_ms: DEFAULT_SERVER_SELECTION_TIMEOUT_MS,
             local_threshold_ms: DEFAULT_LOCAL_THRESHOLD_MS,
+            retry_writes: None,
+            retry_reads: None,
         }
     }
 
@@ -231,6 +276,13 @@ pub trait ThreadedClient: Sync + Sized {
     fn add_start_hook(&mut self, hook: fn(Client, &CommandStarted)) -> Result<()>;
     /// Sets a function to be run every time a command completes.
     fn add_completion_hook(&mut self, hook: fn(Client, &CommandResult)) -> Result<()>;
+    /// Sets a function to be run every time the state of the topology changes.
+    fn add_topology_hook(&mut self, hook: fn(Client, &TopologyEvent)) -> Result<()>;
+    /// Returns a copy of the client's current view of the topology.
+    fn topology_snapshot(&self) -> Result<TopologySnapshot>;
+    /// Kills all open cursors, stops monitoring the topology, and closes all pooled
+    /// connections. Any further operations on this client will fail.
+    fn shutdown(&self) -> Result<()>;
 }
 
 pub type Client = Arc<ClientInner>;
@@ -269,13 +321,48 @@ impl ThreadedClient for Client {
 
         let rp = client_options.read_preference
             .unwrap_or_else(|| ReadPreference::new(ReadMode::Primary, None));
-        let wc = client_options.write_concern.unwrap_or_else(WriteConcern::new);
+        let wc = match client_options.write_concern {
+            Some(write_concern) => write_concern,
+            None => try!(parse_write_concern(&config)).unwrap_or_else(WriteConcern::new),
+        };
+        try!(wc.validate());
+
+        let rc = match client_options.read_concern {
+            Some(read_concern) => read_concern,
+            None => {
+                match config.options.as_ref().and_then(|opts| opts.get("readConcernLevel")) {
+                    Some(level) => ReadConcern::new(try!(ReadConcernLevel::from_str(level))),
+                    None => ReadConcern::default(),
+                }
+            }
+        };
+
+        let retry_writes = match client_options.retry_writes {
+            Some(retry_writes) => retry_writes,
+            None => {
+                match config.options {
+                    Some(ref opts) => try!(opts.get_bool("retryWrites")).unwrap_or(true),
+                    None => true,
+                }
+            }
+        };
+
+        let retry_reads = match client_options.retry_reads {
+            Some(retry_reads) => retry_reads,
+            None => {
+                match config.options {
+                    Some(ref opts) => try!(opts.get_bool("retryReads")).unwrap_or(true),
+                    None => true,
+                }
+            }
+        };
 
         let listener = Listener::new();
         let file = match client_options.log_file {
             Some(string) => {
                 let _ = listener.add_start_hook(log_command_started);
                 let _ = listener.add_completion_hook(log_command_completed);
+                let _ = listener.add_topology_hook(log_topology_event);
                 Some(Mutex::new(try!(OpenOptions::new()
                     .write(true)
                     .append(true)
@@ -291,7 +378,12 @@ impl ThreadedClient for Client {
             listener: listener,
             read_preference: rp,
             write_concern: wc,
+            read_concern: rc,
+            retry_writes: retry_writes,
+            retry_reads: retry_reads,
             log_file: file,
+            cursors: Mutex::new(HashSet::new()),
+            session_pool: ServerSessionPool::new(),
         });
 
         // Fill servers array and set options
@@ -302,9 +394,20 @@ impl ThreadedClient for Client {
             top.server_selection_timeout_ms = client_options.server_selection_timeout_ms;
             top.local_threshold_ms = client_options.local_threshold_ms;
 
+            // A load balancer is not monitored; it is always treated as available.
+            let load_balanced = top.topology_type == TopologyType::LoadBalanced;
+
             for host in &config.hosts {
-                let server =
-                    Server::new(client.clone(), host.clone(), top_description.clone(), true);
+                let server = Server::new(client.clone(),
+                                         host.clone(),
+                                         top_description.clone(),
+                                         !load_balanced);
+
+                if load_balanced {
+                    let mut description = try!(server.description.write());
+                    description.server_type = ServerType::LoadBalancer;
+                }
+
                 top.servers.insert(host.clone(), server);
             }
         }
@@ -388,6 +491,132 @@ impl ThreadedClient for Client {
     fn add_completion_hook(&mut self, hook: fn(Client, &CommandResult)) -> Result<()> {
         self.listener.add_completion_hook(hook)
     }
+
+    fn add_topology_hook(&mut self, hook: fn(Client, &TopologyEvent)) -> Result<()> {
+        self.listener.add_topology_hook(hook)
+    }
+
+    fn topology_snapshot(&self) -> Result<TopologySnapshot> {
+        self.topology.snapshot()
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        // Cursors must be killed while their servers' pools are still open.
+        self.kill_open_cursors();
+
+        let hosts = match try!(self.topology.shutdown()) {
+            Some(hosts) => hosts,
+            None => return Ok(()),
+        };
+
+        for host in hosts {
+            try!(self.run_topology_hooks(&TopologyEvent::ServerClosed { host: host }));
+        }
+
+        self.run_topology_hooks(&TopologyEvent::TopologyClosed)
+    }
+}
+
+impl ClientInner {
+    // Records a server cursor that is held open by a `Cursor`.
+    fn register_cursor(&self, host: Host, cursor_id: i64) {
+        if let Ok(mut cursors) = self.cursors.lock() {
+            cursors.insert((host, cursor_id));
+        }
+    }
+
+    // Removes a server cursor that has been exhausted by the server.
+    fn deregister_cursor(&self, host: &Host, cursor_id: i64) {
+        if let Ok(mut cursors) = self.cursors.lock() {
+            cursors.remove(&(host.clone(), cursor_id));
+        }
+    }
+
+    // Kills a server cursor that is no longer referenced, over the given stream if the cursor
+    // is pinned to one. Cursors that were already killed by a shutdown are skipped, and
+    // failures are ignored, as the server will eventually time out the cursor.
+    fn kill_cursor(&self, host: &Host, cursor_id: i64, stream: Option<PooledStream>) {
+        let registered = match self.cursors.lock() {
+            Ok(mut cursors) => cursors.remove(&(host.clone(), cursor_id)),
+            Err(_) => return,
+        };
+
+        if !registered {
+            return;
+        }
+
+        let stream = match stream {
+            Some(stream) => Ok(stream),
+            None => self.topology.acquire_host_stream(host),
+        };
+
+        if let Ok(mut stream) = stream {
+            let req_id = self.req_id.fetch_add(1, Ordering::SeqCst) as i32;
+            let message = Message::new_kill_cursors(req_id, vec![cursor_id]);
+            let _ = message.write(stream.get_socket());
+        }
+    }
+
+    // Kills all registered cursors on their respective servers. Failures are ignored,
+    // as the server will eventually time out any cursors that could not be killed.
+    fn kill_open_cursors(&self) {
+        let cursors = match self.cursors.lock() {
+            Ok(mut cursors) => cursors.drain().collect::<Vec<_>>(),
+            Err(_) => return,
+        };
+
+        let mut by_host: HashMap<Host, Vec<i64>> = HashMap::new();
+        for (host, cursor_id) in cursors {
+            by_host.entry(host).or_insert_with(Vec::new).push(cursor_id);
+        }
+
+        for (host, cursor_ids) in by_host {
+            if let Ok(mut stream) = self.topology.acquire_host_stream(&host) {
+                let req_id = self.req_id.fetch_add(1, Ordering::SeqCst) as i32;
+                let message = Message::new_kill_cursors(req_id, cursor_ids);
+                let _ = message.write(stream.get_socket());
+            }
+        }
+    }
+}
+
+impl Drop for ClientInner {
+    fn drop(&mut self) {
+        // No client handles remain, so no cursors can be open and topology events
+        // cannot be emitted; stop the monitors and close the pools.
+        let _ = self.topology.shutdown();
+    }
+}
+
+// Builds a write concern from the `w`, `wtimeoutMS`, `journal` and `fsync` connection
+// string options, or returns None if none are present.
+fn parse_write_concern(config: &ConnectionString) -> Result<Option<WriteConcern>> {
+    let options = match config.options {
+        Some(ref options) => options,
+        None => return Ok(None),
+    };
+
+    let w = options.get("w");
+    let w_timeout = options.get("wtimeoutMS");
+    let journal = try!(options.get_bool("journal"));
+    let fsync = try!(options.get_bool("fsync"));
+
+    if w.is_none() && w_timeout.is_none() && journal.is_none() && fsync.is_none() {
+        return Ok(None);
+    }
+
+    let mut write_concern = WriteConcern::new();
+    if let Some(w) = w {
+        write_concern.w = try!(Acknowledgment::from_str(w));
+    }
+    if let Some(w_timeout) = w_timeout {
+        write_concern.w_timeout = try!(w_timeout.parse()
+            .map_err(|_| ArgumentError(format!("Invalid value '{}' for wtimeoutMS.", w_timeout))));
+    }
+    write_concern.j = journal.unwrap_or(false);
+    write_concern.fsync = fsync.unwrap_or(false);
+
+    Ok(Some(write_concern))
 }
 
 fn log_command_started(client: Client, command_started: &CommandStarted) {
@@ -417,3 +646,17 @@ fn log_command_completed(client: Client, command_result: &CommandResult) {
 
     let _ = writeln!(guard.deref_mut(), "{}", command_result);
 }
+
+fn log_topology_event(client: Client, event: &TopologyEvent) {
+    let mutex = match client.log_file {
+        Some(ref mutex) => mutex,
+        None => return,
+    };
+
+    let mut guard = match mutex.lock() {
+        Ok(guard) => guard,
+        Err(_) => return,
+    };
+
+    let _ = writeln!(guard.deref_mut(), "{}", event);
+}
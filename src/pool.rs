@@ -33,6 +33,8 @@ struct Pool {
     // The pool iteration. When a server monitor fails to execute ismaster,
     // the connection pool is cleared and the iteration is incremented.
     iteration: usize,
+    // Set once the pool has been closed; no further connections will be made.
+    closed: bool,
 }
 
 /// Holds an available socket, with logic to return the socket
@@ -47,6 +49,8 @@ pub struct PooledStream {
     wait_lock: Arc<Condvar>,
     // The pool iteration at the moment of extraction.
     iteration: usize,
+    // The host that the socket is connected to.
+    host: Host,
 }
 
 impl PooledStream {
@@ -54,6 +58,11 @@ impl PooledStream {
     pub fn get_socket(&mut self) -> &mut BufStream<TcpStream> {
         self.socket.as_mut().unwrap()
     }
+
+    /// Returns the host that the socket is connected to.
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
 }
 
 impl Drop for PooledStream {
@@ -86,6 +95,7 @@ impl ConnectionPool {
                 size: size,
                 sockets: Vec::with_capacity(size),
                 iteration: 0,
+                closed: false,
             })),
         }
     }
@@ -110,6 +120,20 @@ impl ConnectionPool {
         }
     }
 
+    /// Closes all open socket connections and prevents new connections from being made.
+    /// Streams that are currently in use will be discarded when they are returned.
+    pub fn close(&self) {
+        if let Ok(mut locked) = self.inner.lock() {
+            locked.closed = true;
+            locked.iteration += 1;
+            locked.sockets.clear();
+            locked.len.store(0, Ordering::SeqCst);
+        }
+
+        // Wake any threads waiting on the pool so that they can fail.
+        self.wait_lock.notify_all();
+    }
+
     /// Attempts to acquire a connected socket. If none are available and
     /// the pool has not reached its maximum size, a new socket will connect.
     /// Otherwise, the function will block until a socket is returned to the pool.
@@ -121,6 +145,10 @@ impl ConnectionPool {
         }
 
         loop {
+            if locked.closed {
+                return Err(OperationError(String::from("The connection pool has been closed.")));
+            }
+
             // Acquire available existing socket
             if let Some(stream) = locked.sockets.pop() {
                 return Ok(PooledStream {
@@ -128,6 +156,7 @@ impl ConnectionPool {
                     pool: self.inner.clone(),
                     wait_lock: self.wait_lock.clone(),
                     iteration: locked.iteration,
+                    host: self.host.clone(),
                 });
             }
 
@@ -141,6 +170,7 @@ impl ConnectionPool {
                     pool: self.inner.clone(),
                     wait_lock: self.wait_lock.clone(),
                     iteration: locked.iteration,
+                    host: self.host.clone(),
                 });
             }
 
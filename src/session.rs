@@ -0,0 +1,101 @@
+//! Server sessions, which let the server recognize writes that the driver retries.
+use Result;
+
+use bson::{self, Bson};
+use bson::spec::BinarySubtype;
+use rand::{thread_rng, Rng};
+use time;
+
+use std::sync::Mutex;
+
+/// How long servers behind a load balancer, which don't report a timeout, keep idle sessions.
+pub const DEFAULT_SESSION_TIMEOUT_MINUTES: i64 = 30;
+
+// Sessions are discarded when they come within this many seconds of expiring on the server.
+const SESSION_EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// A logical session on the server, identified by a random UUID.
+pub struct ServerSession {
+    /// The session identifier sent as the `lsid` of commands.
+    pub id: bson::Document,
+    // The transaction number of the most recent retryable write in this session.
+    txn_number: i64,
+    // The time in seconds at which the session was last used.
+    last_use: i64,
+    // Set when a network error leaves the session's state on the server unknown.
+    dirty: bool,
+}
+
+/// Sessions that are not currently in use, most recently used first.
+pub struct ServerSessionPool {
+    sessions: Mutex<Vec<ServerSession>>,
+}
+
+impl ServerSession {
+    /// Returns a new session with a random version 4 UUID.
+    pub fn new() -> ServerSession {
+        let mut uuid = vec![0u8; 16];
+        thread_rng().fill_bytes(&mut uuid);
+        uuid[6] = (uuid[6] & 0x0f) | 0x40;
+        uuid[8] = (uuid[8] & 0x3f) | 0x80;
+
+        let mut id = bson::Document::new();
+        id.insert("id", Bson::Binary(BinarySubtype::Uuid, uuid));
+
+        ServerSession {
+            id: id,
+            txn_number: 0,
+            last_use: time::get_time().sec,
+            dirty: false,
+        }
+    }
+
+    /// Advances the session to a new transaction number for a retryable write.
+    pub fn next_txn_number(&mut self) -> i64 {
+        self.txn_number += 1;
+        self.last_use = time::get_time().sec;
+        self.txn_number
+    }
+
+    /// Marks the session as unusable once the current operation completes.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    // Returns true if the server may expire the session before another operation completes.
+    fn is_expiring(&self, timeout_minutes: i64) -> bool {
+        let elapsed = time::get_time().sec - self.last_use;
+        elapsed >= timeout_minutes * 60 - SESSION_EXPIRY_MARGIN_SECS
+    }
+}
+
+impl ServerSessionPool {
+    /// Returns an empty session pool.
+    pub fn new() -> ServerSessionPool {
+        ServerSessionPool { sessions: Mutex::new(Vec::new()) }
+    }
+
+    /// Takes the most recently used session that is not about to expire, or starts a new one.
+    pub fn checkout(&self, timeout_minutes: i64) -> Result<ServerSession> {
+        let mut sessions = try!(self.sessions.lock());
+
+        while let Some(session) = sessions.pop() {
+            if !session.is_expiring(timeout_minutes) {
+                return Ok(session);
+            }
+        }
+
+        Ok(ServerSession::new())
+    }
+
+    /// Returns a session to the pool, discarding it if it is dirty or about to expire.
+    pub fn checkin(&self, session: ServerSession, timeout_minutes: i64) -> Result<()> {
+        if session.dirty || session.is_expiring(timeout_minutes) {
+            return Ok(());
+        }
+
+        let mut sessions = try!(self.sessions.lock());
+        sessions.push(session);
+        Ok(())
+    }
+}
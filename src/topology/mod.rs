@@ -1,9 +1,10 @@
 //! MongoDB server set topology and asynchronous monitoring.
 pub mod server;
 pub mod monitor;
+pub mod scheduler;
 
-use {Client, Result};
-use Error::{self, ArgumentError, OperationError};
+use {Client, ErrorCode, Result};
+use Error::{self, ArgumentError, CodedError, OperationError};
 
 use bson::oid;
 
@@ -21,6 +22,7 @@ use std::thread;
 use std::time::Duration;
 use time;
 
+use self::scheduler::Scheduler;
 use self::server::{Server, ServerDescription, ServerType};
 
 pub const DEFAULT_HEARTBEAT_FREQUENCY_MS: u32 = 10000;
@@ -34,6 +36,9 @@ pub enum TopologyType {
     ReplicaSetNoPrimary,
     ReplicaSetWithPrimary,
     Sharded,
+    /// A single load balancer in front of a set of servers. Servers are not
+    /// monitored, and cursors are pinned to the connection that created them.
+    LoadBalanced,
     Unknown,
 }
 
@@ -63,6 +68,19 @@ pub struct TopologyDescription {
     // The largest set version seen from a primary in the topology.
     max_set_version: Option<i64>,
     compat_error: String,
+    // If true, the topology has been shut down and servers are no longer monitored.
+    closed: bool,
+}
+
+/// A point-in-time copy of the topology and the descriptions of its servers.
+#[derive(Clone, Debug)]
+pub struct TopologySnapshot {
+    pub topology_type: TopologyType,
+    /// The set name for a replica set topology.
+    pub set_name: String,
+    /// The description of each known server, including its raw and averaged
+    /// round-trip times.
+    pub servers: HashMap<Host, ServerDescription>,
 }
 
 /// Holds status and connection information about a server set.
@@ -72,6 +90,8 @@ pub struct Topology {
     pub config: ConnectionString,
     /// Monitored topology information.
     pub description: Arc<RwLock<TopologyDescription>>,
+    /// Runs the server checks for all monitors in the topology.
+    pub scheduler: Arc<Scheduler>,
 }
 
 impl FromStr for TopologyType {
@@ -82,6 +102,7 @@ impl FromStr for TopologyType {
             "ReplicaSetNoPrimary" => TopologyType::ReplicaSetNoPrimary,
             "ReplicaSetWithPrimary" => TopologyType::ReplicaSetWithPrimary,
             "Sharded" => TopologyType::Sharded,
+            "LoadBalanced" => TopologyType::LoadBalanced,
             _ => TopologyType::Unknown,
         })
     }
@@ -107,43 +128,10 @@ impl TopologyDescription {
             compatible: true,
             compat_error: String::new(),
             max_set_version: None,
+            closed: false,
         }
     }
 
-    /// Returns the nearest server stream, calculated by round trip time.
-    fn get_nearest_from_vec(&self, servers: &mut Vec<Host>) -> Result<(PooledStream, ServerType)> {
-        servers.sort_by(|a, b| {
-            let mut a_rtt = i64::MAX;
-            let mut b_rtt = i64::MAX;
-            if let Some(server) = self.servers.get(a) {
-                if let Ok(a_description) = server.description.read() {
-                    a_rtt = a_description.round_trip_time.unwrap_or(i64::MAX);
-                }
-            }
-            if let Some(server) = self.servers.get(b) {
-                if let Ok(b_description) = server.description.read() {
-                    b_rtt = b_description.round_trip_time.unwrap_or(i64::MAX);
-                }
-            }
-
-            a_rtt.cmp(&b_rtt)
-        });
-
-        // Iterate over each host until one's stream can be acquired.
-        for host in servers.iter() {
-            if let Some(server) = self.servers.get(host) {
-                if let Ok(description) = server.description.read() {
-                    if description.round_trip_time.is_none() {
-                        break;
-                    } else if let Ok(stream) = server.acquire_stream() {
-                        return Ok((stream, description.server_type));
-                    }
-                }
-            }
-        }
-        Err(OperationError(String::from("No servers available for the provided ReadPreference.")))
-    }
-
     /// Returns a random server stream from the vector.
     fn get_rand_from_vec(&self, servers: &mut Vec<Host>) -> Result<(PooledStream, ServerType)> {
         while !servers.is_empty() {
@@ -166,7 +154,7 @@ impl TopologyDescription {
     pub fn acquire_stream(&self,
                           read_preference: &ReadPreference)
                           -> Result<(PooledStream, bool, bool)> {
-        let (mut hosts, rand) = self.choose_hosts(read_preference);
+        let mut hosts = self.choose_hosts(read_preference);
 
         // Filter hosts by tagsets
         if self.topology_type != TopologyType::Sharded &&
@@ -192,12 +180,8 @@ impl TopologyDescription {
         // Filter hosts by round trip times within the latency window.
         self.filter_latency_hosts(&mut hosts);
 
-        // Retrieve a server stream from the list of acceptable hosts.
-        let (pooled_stream, server_type) = if rand {
-            try!(self.get_rand_from_vec(&mut hosts))
-        } else {
-            try!(self.get_nearest_from_vec(&mut hosts))
-        };
+        // Retrieve a server stream from a random host within the latency window.
+        let (pooled_stream, server_type) = try!(self.get_rand_from_vec(&mut hosts));
 
         // Determine how to handle server-side logic based on ReadMode and TopologyType.
         let (slave_ok, send_read_pref) = match self.topology_type {
@@ -225,7 +209,8 @@ impl TopologyDescription {
                     _ => (true, false),
                 }
             }
-            TopologyType::Sharded => {
+            TopologyType::Sharded |
+            TopologyType::LoadBalanced => {
                 match read_preference.mode {
                     ReadMode::Primary => (false, false),
                     ReadMode::SecondaryPreferred => (true, !read_preference.tag_sets.is_empty()),
@@ -241,7 +226,7 @@ impl TopologyDescription {
 
     /// Returns a server stream for write operations.
     pub fn acquire_write_stream(&self) -> Result<PooledStream> {
-        let (mut hosts, rand) = self.choose_write_hosts();
+        let mut hosts = self.choose_write_hosts();
 
         // If no servers are available, request an update from all monitors.
         if hosts.is_empty() {
@@ -250,18 +235,18 @@ impl TopologyDescription {
             }
         }
 
-        if rand {
-            Ok(try!(self.get_rand_from_vec(&mut hosts)).0)
-        } else {
-            Ok(try!(self.get_nearest_from_vec(&mut hosts)).0)
-        }
+        self.filter_latency_hosts(&mut hosts);
+        Ok(try!(self.get_rand_from_vec(&mut hosts)).0)
     }
 
     /// Filters a given set of hosts based on the provided read preference tag sets.
     pub fn filter_hosts(&self, hosts: &mut Vec<Host>, read_preference: &ReadPreference) {
         let mut tag_filter = None;
 
-        if read_preference.tag_sets.is_empty() {
+        // A load balancer is never monitored, so its tags are unknown; tag sets are instead
+        // applied by the servers behind it.
+        if read_preference.tag_sets.is_empty() ||
+           self.topology_type == TopologyType::LoadBalanced {
             return;
         }
 
@@ -403,21 +388,22 @@ impl TopologyDescription {
         });
     }
 
-    /// Returns suitable servers for write operations and whether to take a random element.
-    pub fn choose_write_hosts(&self) -> (Vec<Host>, bool) {
+    /// Returns suitable servers for write operations.
+    pub fn choose_write_hosts(&self) -> Vec<Host> {
         if self.servers.is_empty() {
-            return (Vec::new(), true);
+            return Vec::new();
         }
 
         match self.topology_type {
             // No servers are suitable.
-            TopologyType::Unknown => (Vec::new(), true),
+            TopologyType::Unknown => Vec::new(),
             // All servers are suitable.
-            TopologyType::Single => (self.servers.keys().cloned().collect(), true),
-            TopologyType::Sharded => (self.servers.keys().cloned().collect(), false),
+            TopologyType::Single |
+            TopologyType::Sharded |
+            TopologyType::LoadBalanced => self.servers.keys().cloned().collect(),
             // Only primary replica set members are suitable.
             _ => {
-                (self.servers
+                self.servers
                     .keys()
                     .filter_map(|host| {
                         if let Some(server) = self.servers.get(host) {
@@ -429,30 +415,30 @@ impl TopologyDescription {
                         }
                         None
                     })
-                    .collect(),
-                 true)
+                    .collect()
             }
         }
     }
 
-    /// Returns suitable servers for read operations and whether to take a random element.
-    pub fn choose_hosts(&self, read_preference: &ReadPreference) -> (Vec<Host>, bool) {
+    /// Returns suitable servers for read operations.
+    pub fn choose_hosts(&self, read_preference: &ReadPreference) -> Vec<Host> {
         if self.servers.is_empty() {
-            return (Vec::new(), true);
+            return Vec::new();
         }
 
         match self.topology_type {
             // No servers are suitable.
-            TopologyType::Unknown => (Vec::new(), true),
+            TopologyType::Unknown => Vec::new(),
             // All servers are suitable.
-            TopologyType::Single => (self.servers.keys().cloned().collect(), true),
-            TopologyType::Sharded => (self.servers.keys().cloned().collect(), false),
+            TopologyType::Single |
+            TopologyType::Sharded |
+            TopologyType::LoadBalanced => self.servers.keys().cloned().collect(),
             _ => {
 
                 // Handle replica set server selection
                 // Short circuit if nearest
                 if read_preference.mode == ReadMode::Nearest {
-                    return (self.servers.keys().cloned().collect(), false);
+                    return self.servers.keys().cloned().collect();
                 }
 
                 let mut primaries = Vec::new();
@@ -468,27 +454,25 @@ impl TopologyDescription {
                     }
                 }
 
-                // Choose an appropriate server at random based on the read preference.
+                // Choose the appropriate servers based on the read preference.
                 match read_preference.mode {
-                    ReadMode::Primary => (primaries, true),
+                    ReadMode::Primary => primaries,
                     ReadMode::PrimaryPreferred => {
-                        let servers = if !primaries.is_empty() {
+                        if !primaries.is_empty() {
                             primaries
                         } else {
                             secondaries
-                        };
-                        (servers, true)
+                        }
                     }
-                    ReadMode::Secondary => (secondaries, true),
+                    ReadMode::Secondary => secondaries,
                     ReadMode::SecondaryPreferred => {
-                        let servers = if !secondaries.is_empty() {
+                        if !secondaries.is_empty() {
                             secondaries
                         } else {
                             primaries
-                        };
-                        (servers, true)
+                        }
                     }
-                    ReadMode::Nearest => (self.servers.keys().cloned().collect(), false),
+                    ReadMode::Nearest => self.servers.keys().cloned().collect(),
                 }
             }
         }
@@ -520,6 +504,12 @@ impl TopologyDescription {
                       top_arc: Arc<RwLock<TopologyDescription>>,
                       run_monitor: bool) {
 
+        // A closed topology no longer tracks or discovers servers, and a load balancer
+        // is never monitored.
+        if self.closed || self.topology_type == TopologyType::LoadBalanced {
+            return;
+        }
+
         let stype = description.server_type;
         match self.topology_type {
             TopologyType::Unknown => {
@@ -581,7 +571,7 @@ impl TopologyDescription {
                     }
                 }
             }
-            TopologyType::Single => (),
+            TopologyType::Single | TopologyType::LoadBalanced => (),
         }
     }
 
@@ -793,6 +783,50 @@ impl Topology {
                 options.set_name = name.to_owned();
                 options.topology_type = TopologyType::ReplicaSetNoPrimary;
             }
+
+            let direct_connection = try!(config_opts.get_bool("directConnection"));
+            let load_balanced = try!(config_opts.get_bool("loadBalanced"));
+
+            if load_balanced == Some(true) {
+                if config.hosts.len() > 1 {
+                    return Err(ArgumentError(String::from("loadBalanced=true cannot be used \
+                                                           with multiple hosts.")));
+                }
+
+                if !options.set_name.is_empty() || direct_connection == Some(true) {
+                    return Err(ArgumentError(String::from("loadBalanced=true cannot be used \
+                                                           with replicaSet or \
+                                                           directConnection=true.")));
+                }
+
+                options.topology_type = TopologyType::LoadBalanced;
+            } else {
+                match direct_connection {
+                    Some(true) => {
+                        if config.hosts.len() > 1 {
+                            return Err(ArgumentError(String::from("directConnection=true \
+                                                                   cannot be used with \
+                                                                   multiple seeds.")));
+                        }
+
+                        if !options.set_name.is_empty() {
+                            return Err(ArgumentError(String::from("directConnection=true \
+                                                                   cannot be used with \
+                                                                   replicaSet.")));
+                        }
+
+                        options.topology_type = TopologyType::Single;
+                    }
+                    Some(false) => {
+                        if options.topology_type == TopologyType::Single {
+                            return Err(ArgumentError(String::from("directConnection=false \
+                                                                   cannot be used with \
+                                                                   TopologyType::Single.")));
+                        }
+                    }
+                    None => (),
+                }
+            }
         }
 
         if !options.set_name.is_empty() &&
@@ -806,6 +840,7 @@ impl Topology {
         Ok(Topology {
             config: config,
             description: top_description,
+            scheduler: Arc::new(Scheduler::new()),
         })
     }
 
@@ -821,6 +856,10 @@ impl Topology {
         loop {
             {
                 let description = try!(self.description.read());
+                if description.closed {
+                    return Err(OperationError(String::from("The client has been shut down.")));
+                }
+
                 let result = if write {
                     match description.acquire_write_stream() {
                         Ok(stream) => Ok((stream, false, false)),
@@ -860,4 +899,138 @@ impl Topology {
         let (stream, _, _) = try!(self.acquire_stream_private(None, true));
         Ok(stream)
     }
+
+    /// Returns true if the topology is a load balancer, in which case cursors must
+    /// continue to use the connection that created them.
+    pub fn is_load_balanced(&self) -> bool {
+        match self.description.read() {
+            Ok(description) => description.topology_type == TopologyType::LoadBalanced,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns a copy of the current topology and server descriptions.
+    pub fn snapshot(&self) -> Result<TopologySnapshot> {
+        let description = try!(self.description.read());
+        let mut servers = HashMap::new();
+
+        for (host, server) in &description.servers {
+            let server_description = try!(server.description.read());
+            servers.insert(host.clone(), server_description.clone());
+        }
+
+        Ok(TopologySnapshot {
+            topology_type: description.topology_type,
+            set_name: description.set_name.clone(),
+            servers: servers,
+        })
+    }
+
+    /// Returns the maximum wire version supported by the server a stream is connected to.
+    pub fn stream_wire_version(&self, stream: &PooledStream) -> Result<i64> {
+        let server_description = try!(self.stream_server_description(stream));
+        if server_description.server_type == ServerType::LoadBalancer {
+            // Load balancing is only supported by servers that support every
+            // feature the driver checks for.
+            Ok(i64::MAX)
+        } else {
+            Ok(server_description.max_wire_version)
+        }
+    }
+
+    /// Returns a copy of the description of the server a stream is connected to.
+    pub fn stream_server_description(&self, stream: &PooledStream) -> Result<ServerDescription> {
+        let description = try!(self.description.read());
+        match description.servers.get(stream.host()) {
+            Some(server) => Ok(try!(server.description.read()).clone()),
+            None => {
+                Err(OperationError(String::from("The selected server is no longer part of the \
+                                                 topology.")))
+            }
+        }
+    }
+
+    /// Returns a stream to a specific server within the topology.
+    pub fn acquire_host_stream(&self, host: &Host) -> Result<PooledStream> {
+        let description = try!(self.description.read());
+        match description.servers.get(host) {
+            Some(server) => server.acquire_stream(),
+            None => {
+                Err(OperationError(format!("Server {}:{} is no longer part of the topology.",
+                                           host.host_name,
+                                           host.port)))
+            }
+        }
+    }
+
+    /// Marks a server as unknown after an operation on it failed with a "not master" or
+    /// "node is recovering" error, so that it is not selected again until its monitor has
+    /// checked it. Connections are closed if the server is shutting down.
+    pub fn mark_server_unknown(&self, client: Client, host: &Host, code: ErrorCode, msg: &str) {
+        // Server and topology locks are taken one at a time, since monitors may
+        // acquire them in either order.
+        let server_description = {
+            let description = match self.description.read() {
+                Ok(description) => description,
+                Err(_) => return,
+            };
+
+            // The state of servers behind a load balancer is not tracked.
+            if description.topology_type == TopologyType::LoadBalanced {
+                return;
+            }
+
+            match description.servers.get(host) {
+                Some(server) => {
+                    if code.is_shutdown() {
+                        server.clear_pool();
+                    }
+                    server.description.clone()
+                }
+                None => return,
+            }
+        };
+
+        let updated = match server_description.write() {
+            Ok(mut server_description) => {
+                server_description.set_err(CodedError(code, msg.to_owned()));
+                server_description.clone()
+            }
+            Err(_) => return,
+        };
+
+        if let Ok(mut description) = self.description.write() {
+            description.update(host.clone(), updated, client, self.description.clone());
+            if let Some(server) = description.servers.get(host) {
+                server.request_update();
+            }
+        }
+    }
+
+    /// Stops monitoring all servers and closes their connection pools. Once closed,
+    /// server selection will fail for all operations.
+    ///
+    /// Returns the hosts of the servers that were closed, or `None` if the topology
+    /// had already been closed.
+    pub fn shutdown(&self) -> Result<Option<Vec<Host>>> {
+        // Monitors acquire the description lock while updating, so it must be released
+        // before waiting on them to exit.
+        let servers: Vec<Server> = {
+            let mut description = try!(self.description.write());
+            if description.closed {
+                return Ok(None);
+            }
+
+            description.closed = true;
+            description.servers.values().cloned().collect()
+        };
+
+        for server in &servers {
+            server.shutdown();
+        }
+
+        self.scheduler.shutdown();
+
+        Ok(Some(servers.iter().map(|server| server.host.clone()).collect()))
+    }
 }
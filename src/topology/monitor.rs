@@ -1,5 +1,5 @@
 //! Asynchronous server and topology discovery and monitoring using isMaster results.
-use {Client, Result};
+use {Client, ClientInner, Result};
 use Error::{self, ArgumentError, OperationError};
 
 use bson::{self, Bson, oid};
@@ -13,17 +13,18 @@ use pool::ConnectionPool;
 use wire_protocol::flags::OpQueryFlags;
 
 use std::collections::BTreeMap;
-use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::sync::{Arc, RwLock, Weak};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::time::Duration;
 
 use time;
 
+use super::scheduler::Scheduler;
 use super::server::{ServerDescription, ServerType};
 use super::{DEFAULT_HEARTBEAT_FREQUENCY_MS, TopologyDescription};
 
-const DEFAULT_MAX_BSON_OBJECT_SIZE: i64 = 16 * 1024 * 1024;
-const DEFAULT_MAX_MESSAGE_SIZE_BYTES: i64 = 48000000;
+pub const DEFAULT_MAX_BSON_OBJECT_SIZE: i64 = 16 * 1024 * 1024;
+pub const DEFAULT_MAX_MESSAGE_SIZE_BYTES: i64 = 48000000;
+pub const DEFAULT_MAX_WRITE_BATCH_SIZE: i64 = 1000;
 
 /// The result of an isMaster operation.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -32,6 +33,8 @@ pub struct IsMasterResult {
     pub is_master: bool,
     pub max_bson_object_size: i64,
     pub max_message_size_bytes: i64,
+    pub max_write_batch_size: i64,
+    pub logical_session_timeout_minutes: Option<i64>,
     pub local_time: Option<DateTime<UTC>>,
     pub min_wire_version: i64,
     pub max_wire_version: i64,
@@ -66,20 +69,20 @@ pub struct Monitor {
     top_description: Arc<RwLock<TopologyDescription>>,
     // Server description to update.
     server_description: Arc<RwLock<ServerDescription>>,
-    // Client reference.
-    client: Client,
+    // Client reference. The monitor does not keep the client alive, so that
+    // dropping the last client handle can shut down the topology.
+    client: Weak<ClientInner>,
     // Owned, single-threaded pool.
     personal_pool: Arc<ConnectionPool>,
     // Owned copy of the topology's heartbeat frequency.
     heartbeat_frequency_ms: AtomicUsize,
-    // Used for condvar functionality.
-    dummy_lock: Mutex<()>,
-    // To allow servers to request an immediate update, this
-    // condvar can be notified to wake up the monitor.
-    condvar: Condvar,
+    // The topology's scheduler, which runs the checks for this monitor.
+    scheduler: Weak<Scheduler>,
     /// While true, the monitor will check server connection health
     /// at the topology's heartbeat frequency rate.
     pub running: Arc<AtomicBool>,
+    // Set once the monitor has been stopped; a stopped monitor cannot be restarted.
+    stopped: AtomicBool,
 }
 
 impl IsMasterResult {
@@ -97,6 +100,8 @@ impl IsMasterResult {
             is_master: false,
             max_bson_object_size: DEFAULT_MAX_BSON_OBJECT_SIZE,
             max_message_size_bytes: DEFAULT_MAX_MESSAGE_SIZE_BYTES,
+            max_write_batch_size: DEFAULT_MAX_WRITE_BATCH_SIZE,
+            logical_session_timeout_minutes: None,
             local_time: None,
             min_wire_version: -1,
             max_wire_version: -1,
@@ -120,6 +125,30 @@ impl IsMasterResult {
             result.is_master = b;
         }
 
+        match doc.get("maxBsonObjectSize") {
+            Some(&Bson::I32(v)) => result.max_bson_object_size = v as i64,
+            Some(&Bson::I64(v)) => result.max_bson_object_size = v,
+            _ => (),
+        }
+
+        match doc.get("maxMessageSizeBytes") {
+            Some(&Bson::I32(v)) => result.max_message_size_bytes = v as i64,
+            Some(&Bson::I64(v)) => result.max_message_size_bytes = v,
+            _ => (),
+        }
+
+        match doc.get("maxWriteBatchSize") {
+            Some(&Bson::I32(v)) => result.max_write_batch_size = v as i64,
+            Some(&Bson::I64(v)) => result.max_write_batch_size = v,
+            _ => (),
+        }
+
+        match doc.get("logicalSessionTimeoutMinutes") {
+            Some(&Bson::I32(v)) => result.logical_session_timeout_minutes = Some(v as i64),
+            Some(&Bson::I64(v)) => result.logical_session_timeout_minutes = Some(v),
+            _ => (),
+        }
+
         if let Some(&Bson::UtcDatetime(ref datetime)) = doc.get("localTime") {
             result.local_time = Some(*datetime);
         }
@@ -227,19 +256,26 @@ impl Monitor {
                -> Monitor {
 
         Monitor {
-            client: client,
+            scheduler: Arc::downgrade(&client.topology.scheduler),
+            client: Arc::downgrade(&client),
             host: host.clone(),
             server_pool: pool,
             personal_pool: Arc::new(ConnectionPool::with_size(host, 1)),
             top_description: top_description,
             server_description: server_description,
             heartbeat_frequency_ms: AtomicUsize::new(DEFAULT_HEARTBEAT_FREQUENCY_MS as usize),
-            dummy_lock: Mutex::new(()),
-            condvar: Condvar::new(),
             running: Arc::new(AtomicBool::new(false)),
+            stopped: AtomicBool::new(false),
         }
     }
 
+    // Returns a strong reference to the client, or an error if it has been dropped.
+    fn client(&self) -> Result<Client> {
+        self.client
+            .upgrade()
+            .ok_or_else(|| OperationError(String::from("The client has been dropped.")))
+    }
+
     // Set server description error field.
     fn set_err(&self, err: Error) {
         let mut server_description = self.server_description.write().unwrap();
@@ -254,12 +290,13 @@ impl Monitor {
         let mut filter = bson::Document::new();
         filter.insert("isMaster", Bson::I32(1));
 
+        let client = try!(self.client());
         let stream = try!(self.personal_pool.acquire_stream());
 
         let time_start = time::get_time();
 
         let cursor = try!(Cursor::query_with_stream(stream,
-                                                    self.client.clone(),
+                                                    client,
                                                     String::from("local.$cmd"),
                                                     1,
                                                     flags,
@@ -283,8 +320,11 @@ impl Monitor {
         Ok((cursor, round_trip_time))
     }
 
+    /// Requests an immediate check of the server.
     pub fn request_update(&self) {
-        self.condvar.notify_one();
+        if let Some(scheduler) = self.scheduler.upgrade() {
+            scheduler.request_check(self);
+        }
     }
 
     // Updates the server description associated with this monitor using an isMaster server
@@ -309,10 +349,17 @@ impl Monitor {
 
     // Updates the topology description associated with this monitor using a new server description.
     fn update_top_description(&self, description: ServerDescription) {
+        // The client must outlive the topology lock; if this is the last reference,
+        // dropping it will shut down the topology.
+        let client = match self.client() {
+            Ok(client) => client,
+            Err(_) => return,
+        };
+
         let mut top_description = self.top_description.write().unwrap();
         top_description.update(self.host.clone(),
                                description,
-                               self.client.clone(),
+                               client.clone(),
                                self.top_description.clone());
     }
 
@@ -359,30 +406,44 @@ impl Monitor {
         }
     }
 
-    /// Starts server monitoring.
-    pub fn run(&self) {
-        if self.running.load(Ordering::SeqCst) {
+    /// Starts server monitoring on the topology's scheduler.
+    pub fn start(monitor: Arc<Monitor>) {
+        if monitor.stopped.load(Ordering::SeqCst) {
             return;
         }
 
-        self.running.store(true, Ordering::SeqCst);
+        let scheduler = match monitor.scheduler.upgrade() {
+            Some(scheduler) => scheduler,
+            None => return,
+        };
 
-        let mut guard = self.dummy_lock.lock().unwrap();
+        monitor.running.store(true, Ordering::SeqCst);
+        Scheduler::register(&scheduler, monitor);
+    }
 
-        loop {
-            if !self.running.load(Ordering::SeqCst) {
-                break;
-            }
+    /// Stops server monitoring. A check that is already running will complete, but its
+    /// result is discarded once the topology has been shut down.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        self.running.store(false, Ordering::SeqCst);
+        self.personal_pool.close();
+    }
 
-            self.execute_update();
+    /// Returns true if the server should continue to be checked.
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst) && !self.stopped.load(Ordering::SeqCst)
+    }
 
-            if let Ok(description) = self.top_description.read() {
-                self.heartbeat_frequency_ms.store(description.heartbeat_frequency_ms as usize,
-                                                  Ordering::SeqCst);
-            }
+    /// Checks the server and updates the server and topology descriptions, returning the
+    /// number of milliseconds to wait before the next check.
+    pub fn check(&self) -> u64 {
+        self.execute_update();
 
-            let frequency = self.heartbeat_frequency_ms.load(Ordering::SeqCst) as u64;
-            guard = self.condvar.wait_timeout(guard, Duration::from_millis(frequency)).unwrap().0;
+        if let Ok(description) = self.top_description.read() {
+            self.heartbeat_frequency_ms.store(description.heartbeat_frequency_ms as usize,
+                                              Ordering::SeqCst);
         }
+
+        self.heartbeat_frequency_ms.load(Ordering::SeqCst) as u64
     }
 }
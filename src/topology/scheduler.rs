@@ -0,0 +1,199 @@
+//! Scheduling of server monitor checks on a small pool of threads shared by the topology.
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::monitor::Monitor;
+
+/// The maximum number of threads used to check the servers of a single topology.
+pub const MAX_MONITOR_THREADS: usize = 4;
+
+// A monitor registered with the scheduler.
+struct ScheduledMonitor {
+    monitor: Arc<Monitor>,
+    // The time at which the next check should run.
+    next_check: Instant,
+    // True while a thread is checking the server.
+    in_progress: bool,
+    // True if an update was requested while a check was in progress.
+    update_requested: bool,
+}
+
+// Mutable scheduler state, guarded by a single lock.
+struct SchedulerState {
+    monitors: Vec<ScheduledMonitor>,
+    workers: Vec<JoinHandle<()>>,
+    closed: bool,
+}
+
+/// Runs isMaster checks for every monitored server in a topology at each server's
+/// heartbeat frequency, using at most `MAX_MONITOR_THREADS` threads.
+pub struct Scheduler {
+    state: Mutex<SchedulerState>,
+    // Notified when a monitor is registered, an update is requested,
+    // or the scheduler is shut down.
+    condvar: Condvar,
+}
+
+impl ScheduledMonitor {
+    fn is(&self, monitor: &Monitor) -> bool {
+        &*self.monitor as *const Monitor == monitor as *const Monitor
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    /// Returns a new scheduler. Threads are started as monitors are registered.
+    pub fn new() -> Scheduler {
+        Scheduler {
+            state: Mutex::new(SchedulerState {
+                monitors: Vec::new(),
+                workers: Vec::new(),
+                closed: false,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Schedules an immediate check of the monitor's server, followed by periodic checks
+    /// until the monitor is stopped.
+    pub fn register(scheduler: &Arc<Scheduler>, monitor: Arc<Monitor>) {
+        let mut state = match scheduler.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+
+        if state.closed || state.monitors.iter().any(|scheduled| scheduled.is(&monitor)) {
+            return;
+        }
+
+        state.monitors.push(ScheduledMonitor {
+            monitor: monitor,
+            next_check: Instant::now(),
+            in_progress: false,
+            update_requested: false,
+        });
+
+        if state.workers.len() < MAX_MONITOR_THREADS &&
+           state.workers.len() < state.monitors.len() {
+            let scheduler_clone = scheduler.clone();
+            state.workers.push(thread::spawn(move || scheduler_clone.run()));
+        }
+
+        scheduler.condvar.notify_all();
+    }
+
+    /// Requests an immediate check of the monitor's server. If a check is already running,
+    /// another is run as soon as it completes.
+    pub fn request_check(&self, monitor: &Monitor) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+
+        if let Some(scheduled) = state.monitors.iter_mut().find(|scheduled| scheduled.is(monitor)) {
+            if scheduled.in_progress {
+                scheduled.update_requested = true;
+            } else {
+                scheduled.next_check = Instant::now();
+            }
+        }
+
+        self.condvar.notify_all();
+    }
+
+    /// Stops scheduling checks and waits for running checks to complete.
+    pub fn shutdown(&self) {
+        let workers = match self.state.lock() {
+            Ok(mut state) => {
+                state.closed = true;
+                state.monitors.clear();
+                self.condvar.notify_all();
+                state.workers.drain(..).collect::<Vec<_>>()
+            }
+            Err(_) => return,
+        };
+
+        for worker in workers {
+            // A worker may be the one shutting down the topology.
+            if worker.thread().id() != thread::current().id() {
+                let _ = worker.join();
+            }
+        }
+    }
+
+    // Repeatedly runs the check that is due soonest, waiting until it is due.
+    fn run(&self) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+
+        loop {
+            if state.closed {
+                return;
+            }
+
+            state.monitors.retain(|scheduled| scheduled.monitor.is_running());
+
+            let now = Instant::now();
+            let next = state.monitors
+                .iter_mut()
+                .filter(|scheduled| !scheduled.in_progress)
+                .min_by_key(|scheduled| scheduled.next_check);
+
+            let wait = match next {
+                Some(ref scheduled) if scheduled.next_check > now => {
+                    Some(scheduled.next_check.duration_since(now))
+                }
+                Some(scheduled) => {
+                    scheduled.in_progress = true;
+                    scheduled.update_requested = false;
+                    let monitor = scheduled.monitor.clone();
+
+                    drop(state);
+                    let frequency = monitor.check();
+
+                    state = match self.state.lock() {
+                        Ok(state) => state,
+                        Err(_) => return,
+                    };
+
+                    if let Some(scheduled) = state.monitors
+                        .iter_mut()
+                        .find(|scheduled| scheduled.is(&monitor)) {
+                        scheduled.in_progress = false;
+                        scheduled.next_check = if scheduled.update_requested {
+                            Instant::now()
+                        } else {
+                            Instant::now() + Duration::from_millis(frequency)
+                        };
+                    }
+
+                    continue;
+                }
+                None => None,
+            };
+
+            state = match wait {
+                Some(duration) => {
+                    match self.condvar.wait_timeout(state, duration) {
+                        Ok((state, _)) => state,
+                        Err(_) => return,
+                    }
+                }
+                None => {
+                    match self.condvar.wait(state) {
+                        Ok(state) => state,
+                        Err(_) => return,
+                    }
+                }
+            };
+        }
+    }
+}
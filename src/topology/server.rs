@@ -10,9 +10,9 @@ use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 use std::sync::atomic::Ordering;
-use std::thread;
 
-use super::monitor::{IsMasterResult, Monitor};
+use super::monitor::{DEFAULT_MAX_BSON_OBJECT_SIZE, DEFAULT_MAX_MESSAGE_SIZE_BYTES,
+                     DEFAULT_MAX_WRITE_BATCH_SIZE, IsMasterResult, Monitor};
 use super::TopologyDescription;
 
 /// Server round trip time is calculated as an exponentially-weighted moving
@@ -21,6 +21,9 @@ use super::TopologyDescription;
 /// of a floating point provides the closest integer accuracy.
 pub const ROUND_TRIP_DIVISOR: i64 = 5;
 
+// Wire version of the first servers to support retryable writes.
+const RETRYABLE_WRITES_WIRE_VERSION: i64 = 6;
+
 /// Describes the server role within a server set.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ServerType {
@@ -38,6 +41,8 @@ pub enum ServerType {
     RSOther,
     /// Replica set ghost member.
     RSGhost,
+    /// Load balancer in front of the servers of a load-balanced topology.
+    LoadBalancer,
     /// Server type is currently unknown.
     Unknown,
 }
@@ -49,12 +54,23 @@ pub struct ServerDescription {
     pub server_type: ServerType,
     /// Any error encountered while monitoring this server.
     pub err: Arc<Option<Error>>,
-    /// The average round-trip time over the last 5 monitoring checks.
+    /// The exponentially-weighted moving average of round-trip times measured by
+    /// successful monitoring checks since the last error.
     pub round_trip_time: Option<i64>,
+    /// The round-trip time measured by the most recent successful monitoring check.
+    pub last_round_trip_time: Option<i64>,
     /// The minimum wire version supported by this server.
     pub min_wire_version: i64,
     /// The maximum wire version supported by this server.
     pub max_wire_version: i64,
+    /// The maximum size of a BSON document accepted by this server.
+    pub max_bson_object_size: i64,
+    /// The maximum size of a wire protocol message accepted by this server.
+    pub max_message_size_bytes: i64,
+    /// The maximum number of statements in a single write command.
+    pub max_write_batch_size: i64,
+    /// How long the server keeps idle sessions, if it supports sessions.
+    pub logical_session_timeout_minutes: Option<i64>,
     /// The server's host information, if it is part of a replica set.
     pub me: Option<Host>,
     /// All hosts in the replica set known by this server.
@@ -99,6 +115,7 @@ impl FromStr for ServerType {
             "RSArbiter" => ServerType::RSArbiter,
             "RSOther" => ServerType::RSOther,
             "RSGhost" => ServerType::RSGhost,
+            "LoadBalancer" => ServerType::LoadBalancer,
             _ => ServerType::Unknown,
         })
     }
@@ -117,8 +134,13 @@ impl ServerDescription {
             server_type: ServerType::Unknown,
             err: Arc::new(None),
             round_trip_time: None,
+            last_round_trip_time: None,
             min_wire_version: 0,
             max_wire_version: 0,
+            max_bson_object_size: DEFAULT_MAX_BSON_OBJECT_SIZE,
+            max_message_size_bytes: DEFAULT_MAX_MESSAGE_SIZE_BYTES,
+            max_write_batch_size: DEFAULT_MAX_WRITE_BATCH_SIZE,
+            logical_session_timeout_minutes: None,
             me: None,
             hosts: Vec::new(),
             passives: Vec::new(),
@@ -140,6 +162,10 @@ impl ServerDescription {
 
         self.min_wire_version = ismaster.min_wire_version;
         self.max_wire_version = ismaster.max_wire_version;
+        self.max_bson_object_size = ismaster.max_bson_object_size;
+        self.max_message_size_bytes = ismaster.max_message_size_bytes;
+        self.max_write_batch_size = ismaster.max_write_batch_size;
+        self.logical_session_timeout_minutes = ismaster.logical_session_timeout_minutes;
         self.me = ismaster.me;
         self.hosts = ismaster.hosts;
         self.passives = ismaster.passives;
@@ -149,11 +175,11 @@ impl ServerDescription {
         self.election_id = ismaster.election_id;
         self.primary = ismaster.primary;
         self.set_version = ismaster.set_version;
+        self.last_round_trip_time = Some(round_trip_time);
         self.round_trip_time = match self.round_trip_time {
             Some(old_rtt) => {
-                // (rtt / div) + (old_rtt * (div-1)/div)
-                Some(round_trip_time / ROUND_TRIP_DIVISOR +
-                     (old_rtt / (ROUND_TRIP_DIVISOR)) * (ROUND_TRIP_DIVISOR - 1))
+                // (rtt + old_rtt * (div-1)) / div
+                Some((round_trip_time + old_rtt * (ROUND_TRIP_DIVISOR - 1)) / ROUND_TRIP_DIVISOR)
             }
             None => Some(round_trip_time),
         };
@@ -180,6 +206,20 @@ impl ServerDescription {
         }
     }
 
+    /// Returns true if the server can recognize retried writes, which requires sessions and
+    /// a replica set member or mongos. Servers behind a load balancer support every feature
+    /// the driver checks for.
+    pub fn supports_retryable_writes(&self) -> bool {
+        match self.server_type {
+            ServerType::LoadBalancer => true,
+            ServerType::Standalone | ServerType::Unknown => false,
+            _ => {
+                self.max_wire_version >= RETRYABLE_WRITES_WIRE_VERSION &&
+                self.logical_session_timeout_minutes.is_some()
+            }
+        }
+    }
+
     // Sets an encountered error and reverts the server type to Unknown.
     pub fn set_err(&mut self, err: Error) {
         self.err = Arc::new(Some(err));
@@ -190,6 +230,7 @@ impl ServerDescription {
     pub fn clear(&mut self) {
         self.election_id = None;
         self.round_trip_time = None;
+        self.last_round_trip_time = None;
         self.server_type = ServerType::Unknown;
         self.set_name = String::new();
     }
@@ -225,10 +266,7 @@ impl Server {
                                             desc_clone));
 
         if run_monitor {
-            let monitor_clone = monitor.clone();
-            thread::spawn(move || {
-                monitor_clone.run();
-            });
+            Monitor::start(monitor.clone());
         }
 
         Server {
@@ -248,4 +286,15 @@ impl Server {
     pub fn request_update(&self) {
         self.monitor.request_update();
     }
+
+    /// Closes all idle pooled connections; streams in use are discarded when returned.
+    pub fn clear_pool(&self) {
+        self.pool.clear();
+    }
+
+    /// Stops the server monitor and closes all pooled connections.
+    pub fn shutdown(&self) {
+        self.monitor.stop();
+        self.pool.close();
+    }
 }
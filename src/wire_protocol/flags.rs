@@ -47,6 +47,22 @@ pub struct OpInsertFlags {
                                   * All bits remaining must be 0 */
 }
 
+/// Represents the bit vector of flags for an OP_DELETE message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpDeleteFlags {
+    pub single_remove: bool, /* Bit 0
+                              *
+                              * All bits remaining must be 0 */
+}
+
+/// Represents the bit vector of flags for an OP_MSG message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpMsgFlags {
+    pub more_to_come: bool, /* Bit 1
+                             *
+                             * Bit 0 (checksumPresent) is never set by the driver. */
+}
+
 /// Represents the bit vector of flags for an OP_QUERY message.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct OpQueryFlags {
@@ -109,6 +125,46 @@ impl OpInsertFlags {
     }
 }
 
+impl OpDeleteFlags {
+    /// Constructs a new struct with all flags set to false.
+    ///
+    /// # Return value
+    ///
+    /// Returns the newly-created struct.
+    pub fn no_flags() -> OpDeleteFlags {
+        OpDeleteFlags { single_remove: false }
+    }
+
+    /// Gets the actual bit vector that the struct represents.
+    ///
+    /// # Return value
+    ///
+    /// Returns the bit vector as an i32.
+    pub fn to_i32(&self) -> i32 {
+        if self.single_remove { 1 } else { 0 }
+    }
+}
+
+impl OpMsgFlags {
+    /// Constructs a new struct with all flags set to false.
+    ///
+    /// # Return value
+    ///
+    /// Returns the newly-created struct.
+    pub fn no_flags() -> OpMsgFlags {
+        OpMsgFlags { more_to_come: false }
+    }
+
+    /// Gets the actual bit vector that the struct represents.
+    ///
+    /// # Return value
+    ///
+    /// Returns the bit vector as an i32.
+    pub fn to_i32(&self) -> i32 {
+        if self.more_to_come { 1 << 1 } else { 0 }
+    }
+}
+
 impl OpQueryFlags {
     /// Constructs a new struct with all flags set to false.
     ///
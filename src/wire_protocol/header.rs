@@ -14,6 +14,9 @@ pub enum OpCode {
     Insert = 2002,
     Query = 2004,
     GetMore = 2005,
+    Delete = 2006,
+    KillCursors = 2007,
+    Msg = 2013,
 }
 
 impl OpCode {
@@ -34,6 +37,9 @@ impl OpCode {
             2002 => Some(OpCode::Insert),
             2004 => Some(OpCode::Query),
             2005 => Some(OpCode::GetMore),
+            2006 => Some(OpCode::Delete),
+            2007 => Some(OpCode::KillCursors),
+            2013 => Some(OpCode::Msg),
             _ => None,
         }
     }
@@ -47,6 +53,9 @@ impl fmt::Display for OpCode {
             OpCode::Insert => write!(fmt, "OP_INSERT"),
             OpCode::Query => write!(fmt, "OP_QUERY"),
             OpCode::GetMore => write!(fmt, "OP_GET_MORE"),
+            OpCode::Delete => write!(fmt, "OP_DELETE"),
+            OpCode::KillCursors => write!(fmt, "OP_KILL_CURSORS"),
+            OpCode::Msg => write!(fmt, "OP_MSG"),
         }
     }
 }
@@ -105,6 +114,24 @@ impl Header {
         Header::new_request(message_length, request_id, OpCode::GetMore)
     }
 
+    /// Constructs a new Header for an OP_DELETE, with `response_to` set to 0 and
+    /// `op_code` set to `Delete`.
+    pub fn new_delete(message_length: i32, request_id: i32) -> Header {
+        Header::new_request(message_length, request_id, OpCode::Delete)
+    }
+
+    /// Constructs a new Header for an OP_KILL_CURSORS, with `response_to` set to 0 and
+    /// `op_code` set to `KillCursors`.
+    pub fn new_kill_cursors(message_length: i32, request_id: i32) -> Header {
+        Header::new_request(message_length, request_id, OpCode::KillCursors)
+    }
+
+    /// Constructs a new Header for an OP_MSG, with `response_to` set to 0 and
+    /// `op_code` set to `Msg`.
+    pub fn new_msg(message_length: i32, request_id: i32) -> Header {
+        Header::new_request(message_length, request_id, OpCode::Msg)
+    }
+
     /// Writes the serialized Header to a buffer.
     ///
     /// # Arguments
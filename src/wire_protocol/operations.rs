@@ -4,7 +4,8 @@ use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 use Error::{ArgumentError, ResponseError};
 use Result;
 use wire_protocol::header::{Header, OpCode};
-use wire_protocol::flags::{OpInsertFlags, OpQueryFlags, OpReplyFlags, OpUpdateFlags};
+use wire_protocol::flags::{OpDeleteFlags, OpInsertFlags, OpMsgFlags, OpQueryFlags, OpReplyFlags,
+                           OpUpdateFlags};
 
 use std::io::{Read, Write};
 use std::mem;
@@ -71,6 +72,18 @@ pub enum Message {
         /// The documents to be inserted.
         documents: Vec<bson::Document>,
     },
+    OpDelete {
+        /// The message header.
+        header: Header,
+        // The wire protocol specifies that a 32-bit 0 field goes here
+        /// The full qualified name of the collection, beginning with the
+        /// database name and a dot separator.
+        namespace: String,
+        /// A bit vector of delete options.
+        flags: OpDeleteFlags,
+        /// Identifies the document(s) to be removed.
+        selector: bson::Document,
+    },
     OpQuery {
         /// The message header.
         header: Header,
@@ -101,6 +114,21 @@ pub enum Message {
         /// Uniquely identifies the cursor being returned.
         cursor_id: i64,
     },
+    OpKillCursors {
+        /// The message header.
+        header: Header,
+        // The wire protocol specifies that a 32-bit 0 field goes here
+        /// The cursors to close.
+        cursor_ids: Vec<i64>,
+    },
+    OpMsg {
+        /// The message header.
+        header: Header,
+        /// A bit vector of message options.
+        flags: OpMsgFlags,
+        /// The command document, sent as a single kind 0 section.
+        body: bson::Document,
+    },
 }
 
 impl Message {
@@ -122,6 +150,20 @@ impl Message {
         }
     }
 
+    /// Returns the header of the message.
+    pub fn header(&self) -> &Header {
+        match *self {
+            Message::OpReply { ref header, .. } |
+            Message::OpUpdate { ref header, .. } |
+            Message::OpInsert { ref header, .. } |
+            Message::OpDelete { ref header, .. } |
+            Message::OpQuery { ref header, .. } |
+            Message::OpGetMore { ref header, .. } |
+            Message::OpKillCursors { ref header, .. } |
+            Message::OpMsg { ref header, .. } => header,
+        }
+    }
+
     /// Constructs a new message for an update.
     pub fn new_update(request_id: i32,
                       namespace: String,
@@ -183,6 +225,35 @@ impl Message {
         })
     }
 
+    /// Constructs a new message request for a deletion.
+    pub fn new_delete(request_id: i32,
+                      namespace: String,
+                      flags: OpDeleteFlags,
+                      selector: bson::Document)
+                      -> Result<Message> {
+        let header_length = mem::size_of::<Header>() as i32;
+
+        // Add an extra byte after the string for null-termination.
+        let string_length = namespace.len() as i32 + 1;
+
+        // There are two i32 fields -- `flags` is represented in the struct as
+        // a bit vector, and the wire protocol-specified ZERO field.
+        let i32_length = mem::size_of::<i32>() as i32 * 2;
+
+        let selector_length = try!(selector.byte_length());
+
+        let total_length = header_length + string_length + i32_length + selector_length;
+
+        let header = Header::new_delete(total_length, request_id);
+
+        Ok(Message::OpDelete {
+            header: header,
+            namespace: namespace,
+            flags: flags,
+            selector: selector,
+        })
+    }
+
     /// Constructs a new message request for a query.
     pub fn new_query(request_id: i32,
                      flags: OpQueryFlags,
@@ -252,6 +323,43 @@ impl Message {
         }
     }
 
+    /// Constructs a new "kill cursors" request message.
+    pub fn new_kill_cursors(request_id: i32, cursor_ids: Vec<i64>) -> Message {
+        let header_length = mem::size_of::<Header>() as i32;
+
+        // There are two i32 fields because of the reserved "ZERO".
+        let i32_length = 2 * mem::size_of::<i32>() as i32;
+
+        let i64_length = mem::size_of::<i64>() as i32 * cursor_ids.len() as i32;
+        let total_length = header_length + i32_length + i64_length;
+
+        let header = Header::new_kill_cursors(total_length, request_id);
+
+        Message::OpKillCursors {
+            header: header,
+            cursor_ids: cursor_ids,
+        }
+    }
+
+    /// Constructs a new OP_MSG request carrying a single command document.
+    pub fn new_msg(request_id: i32, flags: OpMsgFlags, body: bson::Document) -> Result<Message> {
+        let header_length = mem::size_of::<Header>() as i32;
+        let flags_length = mem::size_of::<i32>() as i32;
+
+        // Add an extra byte for the section kind.
+        let body_length = try!(body.byte_length()) + 1;
+
+        let total_length = header_length + flags_length + body_length;
+
+        let header = Header::new_msg(total_length, request_id);
+
+        Ok(Message::OpMsg {
+            header: header,
+            flags: flags,
+            body: body,
+        })
+    }
+
     /// Writes a serialized BSON document to a given buffer.
     ///
     /// # Arguments
@@ -353,6 +461,47 @@ impl Message {
         Ok(())
     }
 
+    /// Writes a serialized delete message to a given buffer.
+    ///
+    /// # Arguments
+    ///
+    /// `buffer` - The buffer to write to.
+    /// `header` - The header for the given message.
+    /// `namespace` - The full qualified name of the collection, beginning with
+    ///               the database name and a dot.
+    /// `flags` - Bit vector of delete options.
+    /// `selector` - Identifies the document(s) to be removed.
+    ///
+    /// # Return value
+    ///
+    /// Returns nothing on success, or an Error on failure.
+    pub fn write_delete<W: Write>(buffer: &mut W,
+                                  header: &Header,
+                                  namespace: &str,
+                                  flags: &OpDeleteFlags,
+                                  selector: &bson::Document)
+                                  -> Result<()> {
+
+        try!(header.write(buffer));
+
+        // Write ZERO field
+        try!(buffer.write_i32::<LittleEndian>(0));
+
+        for byte in namespace.bytes() {
+            try!(buffer.write_u8(byte));
+        }
+
+        // Writes the null terminator for the collection name string.
+        try!(buffer.write_u8(0));
+
+        try!(buffer.write_i32::<LittleEndian>(flags.to_i32()));
+
+        try!(Message::write_bson_document(buffer, selector));
+
+        let _ = buffer.flush();
+        Ok(())
+    }
+
     /// Writes a serialized query message to a given buffer.
     ///
     /// # Arguments
@@ -447,6 +596,66 @@ impl Message {
         Ok(())
     }
 
+    /// Writes a serialized "kill cursors" request to a given buffer.
+    ///
+    /// # Arguments
+    ///
+    /// `buffer` - The buffer to write to.
+    /// `header` - The header for the given message.
+    /// `cursor_ids` - Specifies which cursors to close.
+    ///
+    /// # Return value
+    ///
+    /// Returns nothing on success, or an Error on failure.
+    pub fn write_kill_cursors<W: Write>(buffer: &mut W,
+                                        header: &Header,
+                                        cursor_ids: &[i64])
+                                        -> Result<()> {
+
+        try!(header.write(buffer));
+
+        // Write ZERO field
+        try!(buffer.write_i32::<LittleEndian>(0));
+
+        try!(buffer.write_i32::<LittleEndian>(cursor_ids.len() as i32));
+
+        for cursor_id in cursor_ids {
+            try!(buffer.write_i64::<LittleEndian>(*cursor_id));
+        }
+
+        let _ = buffer.flush();
+        Ok(())
+    }
+
+    /// Writes a serialized OP_MSG request to a given buffer.
+    ///
+    /// # Arguments
+    ///
+    /// `buffer` - The buffer to write to.
+    /// `header` - The header for the given message.
+    /// `flags` - Bit vector of message options.
+    /// `body` - The command document.
+    ///
+    /// # Return value
+    ///
+    /// Returns nothing on success, or an Error on failure.
+    pub fn write_msg<W: Write>(buffer: &mut W,
+                               header: &Header,
+                               flags: &OpMsgFlags,
+                               body: &bson::Document)
+                               -> Result<()> {
+
+        try!(header.write(buffer));
+        try!(buffer.write_i32::<LittleEndian>(flags.to_i32()));
+
+        // Section kind 0: a single BSON document.
+        try!(buffer.write_u8(0));
+        try!(Message::write_bson_document(buffer, body));
+
+        let _ = buffer.flush();
+        Ok(())
+    }
+
     /// Attemps to write the serialized message to a buffer.
     ///
     /// # Arguments
@@ -472,6 +681,9 @@ impl Message {
             Message::OpInsert { ref header, ref flags, ref namespace, ref documents } => {
                 Message::write_insert(buffer, header, flags, namespace, documents)
             }
+            Message::OpDelete { ref header, ref namespace, ref flags, ref selector } => {
+                Message::write_delete(buffer, header, namespace, flags, selector)
+            }
             Message::OpQuery { ref header,
                                ref flags,
                                ref namespace,
@@ -491,6 +703,12 @@ impl Message {
             Message::OpGetMore { ref header, ref namespace, number_to_return, cursor_id } => {
                 Message::write_get_more(buffer, header, namespace, number_to_return, cursor_id)
             }
+            Message::OpKillCursors { ref header, ref cursor_ids } => {
+                Message::write_kill_cursors(buffer, header, cursor_ids)
+            }
+            Message::OpMsg { ref header, ref flags, ref body } => {
+                Message::write_msg(buffer, header, flags, body)
+            }
         }
     }
 
@@ -118,7 +118,7 @@ fn logging() {
     line.clear();
     read_first_non_monitor_line(&mut file, &mut line);
     assert_eq!("COMMAND.insert_one 127.0.0.1:27017 STARTED: { insert: \"logging\", documents: [{ \
-                _id: 1 }], ordered: true, writeConcern: { w: 1, wtimeout: 0, j: false } }\n",
+                _id: 1 }], ordered: true, writeConcern: { w: 1 } }\n",
                &line);
 
     // First insert completed
@@ -131,7 +131,7 @@ fn logging() {
     line.clear();
     read_first_non_monitor_line(&mut file, &mut line);
     assert_eq!("COMMAND.insert_one 127.0.0.1:27017 STARTED: { insert: \"logging\", documents: [{ \
-                _id: 2 }], ordered: true, writeConcern: { w: 1, wtimeout: 0, j: false } }\n",
+                _id: 2 }], ordered: true, writeConcern: { w: 1 } }\n",
                &line);
 
     // Second insert completed
@@ -144,7 +144,7 @@ fn logging() {
     line.clear();
     read_first_non_monitor_line(&mut file, &mut line);
     assert_eq!("COMMAND.insert_one 127.0.0.1:27017 STARTED: { insert: \"logging\", documents: [{ \
-                _id: 3 }], ordered: true, writeConcern: { w: 1, wtimeout: 0, j: false } }\n",
+                _id: 3 }], ordered: true, writeConcern: { w: 1 } }\n",
                &line);
 
     // Third insert completed
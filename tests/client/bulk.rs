@@ -1,5 +1,6 @@
 use bson::Bson;
-use mongodb::coll::options::WriteModel;
+use mongodb::coll::options::{Collation, WriteModel};
+use mongodb::common::{Acknowledgment, WriteConcern};
 use mongodb::{Client, ThreadedClient};
 use mongodb::db::ThreadedDatabase;
 
@@ -107,15 +108,20 @@ fn bulk_ordered_mix() {
             filter: doc! { "_id" => (3) },
             replacement: doc! { "x" => (37) },
             upsert: true,
+            collation: None,
         },
         WriteModel::UpdateMany {
             filter: doc! { "_id" => { "$lt" => (3) } },
             update: doc! { "$inc" => { "x" => (1) } },
             upsert: false,
+            collation: None,
+        },
+        WriteModel::DeleteOne {
+            filter: doc! {
+                "_id" => (4)
+            },
+            collation: None,
         },
-        WriteModel::DeleteOne { filter: doc! {
-            "_id" => (4)
-        }},
         WriteModel::InsertOne { document: doc! {
             "_id" => (5),
             "x" => (55)
@@ -123,7 +129,8 @@ fn bulk_ordered_mix() {
         WriteModel::UpdateOne {
             filter: doc! { "_id" => (6) },
             update: doc! { "$set" =>  { "x" => (62) } },
-            upsert: true
+            upsert: true,
+            collation: None,
         },
         WriteModel::InsertOne { document: doc! {
             "_id" => (101),
@@ -141,9 +148,12 @@ fn bulk_ordered_mix() {
             "_id" => (104),
             "x" => ("bananas")
         }},
-        WriteModel::DeleteMany { filter: doc! {
-            "_id" => { "$gte" => (103) }
-        }},
+        WriteModel::DeleteMany {
+            filter: doc! {
+                "_id" => { "$gte" => (103) }
+            },
+            collation: None,
+        },
     ];
 
     let client = Client::connect("localhost", 27017).unwrap();
@@ -182,3 +192,116 @@ fn bulk_ordered_mix() {
     check_value_in_tree!(result.inserted_ids, 12, 104);
     check_value_in_tree!(result.upserted_ids, 8, 6);
 }
+
+#[test]
+fn bulk_unordered_split_batches() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-bulk");
+    let coll = db.collection("bulk_unordered_split_batches");
+    coll.drop().unwrap();
+
+    let padding: String = (0..1024 * 1024).map(|_| 'x').collect();
+    let mut models = vec![];
+
+    for i in 0..40 {
+        models.push(WriteModel::DeleteOne {
+            filter: doc! { "_id" => (-i - 1) },
+            collation: None,
+        });
+        models.push(WriteModel::InsertOne {
+            document: doc! { "_id" => (i), "padding" => (padding.clone()) },
+        });
+    }
+
+    // The inserts are sent in several commands, and this one fails in a later command.
+    models[61] = WriteModel::InsertOne {
+        document: doc! { "_id" => 0, "padding" => (padding.clone()) },
+    };
+
+    let result = coll.bulk_write(models, false);
+
+    assert_eq!(result.inserted_count, 39);
+    assert_eq!(result.deleted_count, 0);
+    assert!(!result.inserted_ids.contains_key(&61));
+    assert!(result.inserted_ids.contains_key(&79));
+
+    let exception = result.bulk_write_exception.unwrap();
+    assert_eq!(exception.write_errors.len(), 1);
+    assert_eq!(exception.write_errors[0].index, 61);
+
+    match exception.write_errors[0].request {
+        Some(WriteModel::InsertOne { ref document }) => {
+            assert_eq!(document.get("_id"), Some(&Bson::I32(0)))
+        }
+        ref request => panic!("Unexpected request: {:?}", request),
+    }
+}
+
+#[test]
+fn bulk_builder() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-bulk");
+    let coll = db.collection("bulk_builder");
+    coll.drop().unwrap();
+
+    let mut bulk = coll.initialize_ordered_bulk();
+    bulk.insert(doc! { "_id" => 1, "x" => 11 });
+    bulk.insert(doc! { "_id" => 2, "x" => 22 });
+    bulk.insert(doc! { "_id" => 3, "x" => 33 });
+    bulk.find(doc! { "_id" => 1 }).update_one(doc! { "$inc" => { "x" => 1 } });
+    bulk.find(doc! { "_id" => 4 }).upsert().update_one(doc! { "$set" => { "x" => 44 } });
+    bulk.find(doc! { "_id" => 5 }).upsert().replace_one(doc! { "x" => 55 });
+    bulk.find(doc! { "_id" => 2 }).replace_one(doc! { "x" => 23 });
+    bulk.find(doc! { "_id" => 3 }).delete_one();
+    assert_eq!(bulk.len(), 8);
+
+    let result = bulk.execute(None).expect("Failed to execute bulk operation.");
+
+    assert!(result.bulk_write_exception.is_none());
+    assert_eq!(result.inserted_count, 3);
+    assert_eq!(result.matched_count, 2);
+    assert_eq!(result.modified_count, 2);
+    assert_eq!(result.upserted_count, 2);
+    assert_eq!(result.deleted_count, 1);
+    assert_eq!(result.upserted_ids.get(&4), Some(&Bson::I32(4)));
+    assert_eq!(result.upserted_ids.get(&5), Some(&Bson::I32(5)));
+
+    let mut bulk = coll.initialize_unordered_bulk();
+    bulk.find(doc! { "_id" => 1 }).update_one(doc! { "x" => 0 });
+    assert!(bulk.execute(None).is_err());
+
+    let bulk = coll.initialize_unordered_bulk();
+    assert!(bulk.execute(None).is_err());
+
+    let mut bulk = coll.initialize_unordered_bulk();
+    bulk.insert(doc! { "_id" => 1 });
+    bulk.insert(doc! { "_id" => 6 });
+    bulk.find(doc! { "x" => { "$gt" => 0 } }).delete_many();
+
+    let result = bulk.execute(None).expect("Failed to execute bulk operation.");
+    assert_eq!(result.inserted_count, 1);
+    assert_eq!(result.deleted_count, 4);
+
+    let exception = result.bulk_write_exception.expect("Expected a bulk write exception.");
+    assert_eq!(exception.write_errors.len(), 1);
+    assert_eq!(exception.write_errors[0].index, 0);
+}
+
+#[test]
+fn bulk_builder_batch_error() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-bulk");
+    let coll = db.collection("bulk_builder_batch_error");
+    coll.drop().unwrap();
+
+    // Collations require an acknowledged write concern, so the update batch can't be sent.
+    let mut bulk = coll.initialize_ordered_bulk();
+    bulk.insert(doc! { "_id" => 1 });
+    bulk.find(doc! { "_id" => 1 }).collation(Collation::new("en")).update_one(doc! {
+        "$set" => { "x" => 1 }
+    });
+
+    let mut write_concern = WriteConcern::new();
+    write_concern.w = Acknowledgment::Nodes(0);
+    assert!(bulk.execute(Some(write_concern)).is_err());
+}
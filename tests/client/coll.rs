@@ -1,9 +1,22 @@
 use bson::Bson;
+use bson::oid::ObjectId;
+use chrono::{TimeZone, UTC};
 
-use mongodb::{Client, ThreadedClient};
+use mongodb::{Client, Error, ThreadedClient};
+use mongodb::codec::{Oid, UtcDateTime};
+use mongodb::common::{Acknowledgment, WriteConcern};
 use mongodb::db::ThreadedDatabase;
-use mongodb::coll::options::{FindOptions, FindOneAndUpdateOptions, IndexModel, IndexOptions,
-                             ReturnDocument};
+use mongodb::coll::options::{AggregateOptions, CollModOptions, Collation, CountOptions,
+                             CreateIndexesOptions, DeleteOptions, ExplainVerbosity, FindOptions,
+                             FindOneAndUpdateOptions, IndexModel, IndexModification,
+                             IndexOptions, InsertManyOptions, MapReduceOptions,
+                             MapReduceOutput, ReturnDocument, SyncOptions, UpdateOptions,
+                             ValidationAction, ValidationLevel};
+use mongodb::coll::results::MapReduceResult;
+use mongodb::coll::pipeline::{Accumulator, Pipeline};
+
+use std::thread;
+use std::time::Duration;
 
 #[test]
 fn find_sorted() {
@@ -119,7 +132,7 @@ fn find_one_and_delete() {
     let result = coll.find_one_and_delete(doc2.clone(), None)
         .expect("Failed to execute find_one_and_delete command.");
 
-    match result.unwrap().get("title") {
+    match result.value.unwrap().get("title") {
         Some(&Bson::String(ref title)) => assert_eq!("Back to the Future", title),
         _ => panic!("Expected Bson::String!"),
     }
@@ -160,7 +173,7 @@ fn find_one_and_replace() {
     let result = coll.find_one_and_replace(doc2.clone(), doc3.clone(), None)
         .expect("Failed to execute find_one_and_replace command.");
 
-    match result.unwrap().get("title") {
+    match result.value.unwrap().get("title") {
         Some(&Bson::String(ref title)) => assert_eq!("Back to the Future", title),
         _ => panic!("Expected Bson::String!"),
     }
@@ -190,7 +203,7 @@ fn find_one_and_replace() {
     let result = coll.find_one_and_replace(doc3.clone(), doc2.clone(), Some(opts))
         .expect("Failed to execute find_one_and_replace command.");
 
-    match result.unwrap().get("title") {
+    match result.value.unwrap().get("title") {
         Some(&Bson::String(ref title)) => assert_eq!("Back to the Future", title),
         _ => panic!("Expected Bson::String!"),
     }
@@ -218,7 +231,7 @@ fn find_one_and_update() {
     let result = coll.find_one_and_update(doc2.clone(), update, None)
         .expect("Failed to execute find_one_and_update command.");
 
-    match result.unwrap().get("title") {
+    match result.value.unwrap().get("title") {
         Some(&Bson::String(ref title)) => assert_eq!("Back to the Future", title),
         _ => panic!("Expected Bson::String!"),
     }
@@ -237,6 +250,49 @@ fn find_one_and_update() {
     }
 }
 
+#[test]
+fn find_one_and_update_last_error_object() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("find_one_and_update_last_error_object");
+
+    coll.drop().expect("Failed to drop database");
+    coll.insert_one(doc! { "title" => "Jaws" }, None).expect("Failed to insert document.");
+
+    let mut opts = FindOneAndUpdateOptions::new();
+    opts.max_time_ms = Some(1000);
+    let result = coll.find_one_and_update(doc! { "title" => "Jaws" },
+                             doc! { "$set" => { "year" => 1975 } },
+                             Some(opts))
+        .expect("Failed to execute find_one_and_update command.");
+    assert!(result.updated_existing);
+    assert!(result.upserted_id.is_none());
+    assert_eq!(1, result.n);
+
+    let mut opts = FindOneAndUpdateOptions::new();
+    opts.upsert = true;
+    opts.return_document = ReturnDocument::After;
+    let result = coll.find_one_and_update(doc! { "title" => "Jaws 2" },
+                             doc! { "$set" => { "year" => 1978 } },
+                             Some(opts))
+        .expect("Failed to execute find_one_and_update command.");
+    assert!(!result.updated_existing);
+    assert!(result.upserted_id.is_some());
+    assert_eq!(result.upserted_id, result.value.unwrap().get("_id").cloned());
+
+    match coll.find_one_and_update(doc! { "title" => "Jaws" }, doc! { "year" => 1975 }, None) {
+        Err(Error::ArgumentError(_)) => (),
+        _ => panic!("Expected an update without operators to be rejected."),
+    }
+
+    match coll.find_one_and_replace(doc! { "title" => "Jaws" },
+                                    doc! { "$set" => { "year" => 1975 } },
+                                    None) {
+        Err(Error::ArgumentError(_)) => (),
+        _ => panic!("Expected a replacement with operators to be rejected."),
+    }
+}
+
 #[test]
 fn aggregate() {
     let client = Client::connect("localhost", 27017).unwrap();
@@ -317,6 +373,44 @@ fn count() {
     assert_eq!(0, count_none);
 }
 
+#[test]
+fn count_documents() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("count_documents");
+
+    coll.drop().expect("Failed to drop database");
+
+    let doc1 = doc! { "title" => "Jaws" };
+    let doc2 = doc! { "title" => "Back to the Future" };
+
+    let mut vec = vec![doc1.clone()];
+    for _ in 0..10 {
+        vec.push(doc2.clone());
+    }
+
+    coll.insert_many(vec, None).expect("Failed to insert documents.");
+    assert_eq!(1,
+               coll.count_documents(Some(doc1), None).expect("Failed to count documents."));
+    assert_eq!(11, coll.count_documents(None, None).expect("Failed to count documents."));
+
+    let mut options = CountOptions::new();
+    options.skip = 2;
+    options.limit = 5;
+    options.max_time_ms = Some(10000);
+    options.hint_doc = Some(doc! { "_id" => 1 });
+    assert_eq!(5,
+               coll.count_documents(Some(doc2), Some(options))
+                   .expect("Failed to count documents."));
+
+    let no_doc = doc! { "title" => "Houdini" };
+    assert_eq!(0,
+               coll.count_documents(Some(no_doc), None).expect("Failed to count documents."));
+
+    assert_eq!(11,
+               coll.estimated_document_count(None).expect("Failed to estimate document count."));
+}
+
 #[test]
 fn distinct_none() {
     let client = Client::connect("localhost", 27017).unwrap();
@@ -437,6 +531,36 @@ fn insert_many() {
     }
 }
 
+#[test]
+fn insert_many_split_batches() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("insert_many_split_batches");
+    coll.drop().expect("Failed to drop collection");
+
+    // Forty megabyte-sized documents take several commands to insert.
+    let padding: String = (0..1024 * 1024).map(|_| 'x').collect();
+    let mut docs: Vec<_> = (0..40)
+        .map(|i| doc! { "_id" => (i), "padding" => (padding.clone()) })
+        .collect();
+
+    // Duplicate the first document's id in a later batch.
+    docs[30] = doc! { "_id" => 0, "padding" => (padding.clone()) };
+
+    let result = coll.insert_many(docs, Some(InsertManyOptions::new(true, None)))
+        .expect("Failed to insert documents.");
+
+    let exception = result.bulk_write_exception.expect("Expected a bulk write exception.");
+    assert_eq!(1, exception.write_errors.len());
+    assert_eq!(30, exception.write_errors[0].index);
+
+    let ids = result.inserted_ids.expect("Expected inserted ids.");
+    assert_eq!(30, ids.len());
+    assert!(!ids.contains_key(&30));
+    assert!(!ids.contains_key(&31));
+    assert_eq!(30, coll.count(None, None).expect("Failed to count documents."));
+}
+
 #[test]
 fn delete_one() {
     let client = Client::connect("localhost", 27017).unwrap();
@@ -714,3 +838,624 @@ fn drop_all_indexes() {
 
     assert_eq!(1, results.len());
 }
+
+#[derive(Debug, PartialEq, RustcEncodable, RustcDecodable)]
+struct Movie {
+    _id: Option<Oid>,
+    title: String,
+    year: i32,
+}
+
+#[test]
+fn find_and_insert_typed() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("find_and_insert_typed");
+
+    coll.drop().expect("Failed to drop collection");
+
+    let movie = Movie {
+        _id: None,
+        title: String::from("Jaws"),
+        year: 1975,
+    };
+
+    let result = coll.insert_one_from(&movie, None).expect("Failed to insert movie.");
+    let id = match result.inserted_id {
+        Some(Bson::ObjectId(id)) => id,
+        _ => panic!("Expected an ObjectId to be generated."),
+    };
+
+    let found: Movie = coll.find_one_as(None, None)
+        .expect("Failed to execute find_one_as.")
+        .expect("Expected a movie to be found.");
+    assert_eq!(Some(Oid(id)), found._id);
+    assert_eq!(movie.title, found.title);
+    assert_eq!(movie.year, found.year);
+
+    // Documents that cannot be decoded, such as those with a malformed object id,
+    // report their _id.
+    coll.insert_one(doc! { "_id" => "bad", "title" => "Jaws 2", "year" => 1978 }, None)
+        .expect("Failed to insert document.");
+
+    let results: Vec<_> = coll.find_as::<Movie>(None, None)
+        .expect("Failed to execute find_as.")
+        .collect();
+    assert_eq!(2, results.len());
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(Error::DecoderError(ref err)) => assert!(format!("{}", err).contains("bad")),
+        _ => panic!("Expected a decoder error."),
+    }
+}
+
+#[derive(Debug, PartialEq, RustcEncodable, RustcDecodable)]
+struct Release {
+    _id: Option<Oid>,
+    title: String,
+    year: i32,
+    released: UtcDateTime,
+}
+
+#[test]
+fn typed_round_trip_keeps_bson_types() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("typed_round_trip_keeps_bson_types");
+
+    coll.drop().expect("Failed to drop collection");
+
+    let id = ObjectId::new().unwrap();
+    let release = Release {
+        _id: Some(Oid(id.clone())),
+        title: String::from("Jaws"),
+        year: 1975,
+        released: UtcDateTime(UTC.ymd(1975, 6, 20).and_hms_milli(12, 30, 0, 250)),
+    };
+
+    let result = coll.insert_one_from(&release, None).expect("Failed to insert release.");
+    assert_eq!(Some(Bson::ObjectId(id.clone())), result.inserted_id);
+
+    // The stored document keeps the BSON types of the fields.
+    let doc = coll.find_one(Some(doc! { "_id" => (id.clone()) }), None)
+        .expect("Failed to execute find_one.")
+        .expect("Expected the release to be stored with an ObjectId.");
+    assert_eq!(Some(&Bson::I32(1975)), doc.get("year"));
+    match doc.get("released") {
+        Some(&Bson::UtcDatetime(ref released)) => assert_eq!(release.released.0, *released),
+        _ => panic!("Expected released to be stored as a datetime."),
+    }
+
+    let found: Release = coll.find_one_as(None, None)
+        .expect("Failed to execute find_one_as.")
+        .expect("Expected a release to be found.");
+    assert_eq!(release, found);
+}
+
+#[test]
+fn typed_collection_crud() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("typed_collection_crud");
+    coll.drop().expect("Failed to drop collection");
+
+    let movies = coll.with_type::<Movie>();
+    let jaws = Movie {
+        _id: None,
+        title: String::from("Jaws"),
+        year: 1975,
+    };
+    let dobby = Movie {
+        _id: None,
+        title: String::from("Dobby"),
+        year: 2001,
+    };
+
+    movies.insert_many(&[jaws, dobby], None).expect("Failed to insert movies.");
+
+    let found = movies.find_one(Some(doc! { "title" => "Jaws" }), None)
+        .expect("Failed to execute find_one.")
+        .expect("Expected a movie to be found.");
+    assert_eq!(1975, found.year);
+
+    let replacement = Movie {
+        _id: None,
+        title: String::from("Jaws 2"),
+        year: 1978,
+    };
+    let result = movies.replace_one(doc! { "title" => "Jaws" }, &replacement, None)
+        .expect("Failed to replace movie.");
+    assert_eq!(1, result.modified_count);
+
+    let mut options = FindOneAndUpdateOptions::new();
+    options.return_document = ReturnDocument::After;
+    let updated = movies.find_one_and_update(doc! { "title" => "Dobby" },
+                             doc! { "$set" => { "year" => 2002 } },
+                             Some(options))
+        .expect("Failed to update movie.")
+        .expect("Expected a movie to be updated.");
+    assert_eq!(2002, updated.year);
+
+    let years: Vec<i32> = movies.find(None, None)
+        .expect("Failed to execute find.")
+        .map(|movie| movie.expect("Failed to decode movie.").year)
+        .collect();
+    assert_eq!(vec![1978, 2002], years);
+
+    let deleted = movies.find_one_and_delete(doc! { "title" => "Jaws 2" }, None)
+        .expect("Failed to delete movie.")
+        .expect("Expected a movie to be deleted.");
+    assert_eq!(replacement.title, deleted.title);
+    assert_eq!(1, movies.raw().count(None, None).expect("Failed to count movies."));
+}
+
+#[test]
+fn typed_replace_fetched_document() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("typed_replace_fetched_document");
+    coll.drop().expect("Failed to drop collection");
+
+    let movies = coll.with_type::<Movie>();
+    let jaws = Movie {
+        _id: None,
+        title: String::from("Jaws"),
+        year: 1975,
+    };
+    movies.insert_one(&jaws, None).expect("Failed to insert movie.");
+
+    // Replacements that carry the fetched _id keep it as an ObjectId.
+    let mut fetched: Movie = coll.find_one_as(None, None)
+        .expect("Failed to execute find_one_as.")
+        .expect("Expected a movie to be found.");
+    let id = fetched._id.clone().expect("Expected the movie to have an _id.").0;
+
+    fetched.year = 1976;
+    let result = movies.replace_one(doc! { "_id" => (id.clone()) }, &fetched, None)
+        .expect("Failed to replace movie.");
+    assert_eq!(1, result.matched_count);
+    assert_eq!(1, result.modified_count);
+
+    fetched.title = String::from("Jaws 2");
+    let mut options = FindOneAndUpdateOptions::new();
+    options.return_document = ReturnDocument::After;
+    let replaced = movies.find_one_and_replace(doc! { "_id" => (id.clone()) },
+                              &fetched,
+                              Some(options))
+        .expect("Failed to replace movie.")
+        .expect("Expected a movie to be replaced.");
+    assert_eq!(fetched, replaced);
+
+    let doc = coll.find_one(None, None)
+        .expect("Failed to execute find_one.")
+        .expect("Expected a movie to be found.");
+    assert_eq!(Some(&Bson::ObjectId(id)), doc.get("_id"));
+    assert_eq!(1, coll.count(None, None).expect("Failed to count movies."));
+}
+
+#[test]
+fn explain_operations() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("explain_operations");
+
+    coll.drop().expect("Failed to drop collection");
+    coll.insert_many(vec![doc! { "x" => 1 }, doc! { "x" => 2 }, doc! { "x" => 3 }], None)
+        .expect("Failed to insert documents.");
+
+    let explained = coll.explain_find(Some(doc! { "x" => { "$gt" => 1 } }),
+                      None,
+                      ExplainVerbosity::ExecutionStats)
+        .expect("Failed to explain find.");
+    assert!(explained.winning_plan.is_some());
+    assert_eq!(Some(3), explained.docs_examined);
+    assert_eq!(Some(2), explained.n_returned);
+
+    let explained = coll.explain_count(None, None, ExplainVerbosity::QueryPlanner)
+        .expect("Failed to explain count.");
+    assert!(explained.winning_plan.is_some());
+
+    let explained = coll.explain_delete_many(doc! { "x" => 1 }, ExplainVerbosity::ExecutionStats)
+        .expect("Failed to explain delete.");
+    assert!(explained.winning_plan.is_some());
+
+    // Explaining a write does not apply it.
+    assert_eq!(3, coll.count(None, None).expect("Failed to count documents."));
+}
+
+#[test]
+fn case_insensitive_collation() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("case_insensitive_collation");
+
+    coll.drop().expect("Failed to drop collection");
+    coll.insert_many(vec![doc! { "name" => "Alice" }, doc! { "name" => "bob" }], None)
+        .expect("Failed to insert documents.");
+
+    let mut collation = Collation::new("en");
+    collation.strength = Some(2);
+
+    let mut index_options = IndexOptions::new();
+    index_options.collation = Some(collation.clone());
+    coll.create_index(doc! { "name" => 1 }, Some(index_options))
+        .expect("Failed to create index with collation.");
+
+    let mut options = FindOptions::new();
+    options.collation = Some(collation.clone());
+    let found = coll.find_one(Some(doc! { "name" => "ALICE" }), Some(options))
+        .expect("Failed to execute find with collation.")
+        .expect("Expected a case-insensitive match.");
+    match found.get("name") {
+        Some(&Bson::String(ref name)) => assert_eq!("Alice", name),
+        _ => panic!("Expected Bson::String!"),
+    }
+
+    let mut count_options = CountOptions::new();
+    count_options.collation = Some(collation.clone());
+    assert_eq!(1,
+               coll.count(Some(doc! { "name" => "BOB" }), Some(count_options))
+                   .expect("Failed to count with collation."));
+    assert_eq!(0, coll.count(Some(doc! { "name" => "BOB" }), None).expect("Failed to count."));
+
+    let mut delete_options = DeleteOptions::new();
+    delete_options.collation = Some(collation);
+    let result = coll.delete_one(doc! { "name" => "BOB" }, Some(delete_options))
+        .expect("Failed to delete with collation.");
+    assert_eq!(1, result.deleted_count);
+}
+
+#[test]
+fn unacknowledged_writes() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("unacknowledged_writes");
+
+    coll.drop().expect("Failed to drop collection");
+
+    let mut write_concern = WriteConcern::new();
+    write_concern.w = Acknowledgment::Nodes(0);
+
+    let result = coll.insert_one(doc! { "x" => 1 }, Some(write_concern.clone()))
+        .expect("Failed to send unacknowledged insert.");
+    assert!(!result.acknowledged);
+    assert!(result.inserted_id.is_some());
+
+    let docs = vec![doc! { "x" => 2 }, doc! { "x" => 3 }];
+    let options = InsertManyOptions::new(true, Some(write_concern.clone()));
+    let result = coll.insert_many(docs, Some(options))
+        .expect("Failed to send unacknowledged inserts.");
+    assert!(!result.acknowledged);
+
+    let options = UpdateOptions::new(false, Some(write_concern.clone()));
+    let result = coll.update_many(doc! {}, doc! { "$set" => { "y" => true } }, Some(options))
+        .expect("Failed to send unacknowledged update.");
+    assert!(!result.acknowledged);
+
+    let mut delete_options = DeleteOptions::new();
+    delete_options.write_concern = Some(write_concern.clone());
+    let result = coll.delete_one(doc! { "x" => 1 }, Some(delete_options))
+        .expect("Failed to send unacknowledged delete.");
+    assert!(!result.acknowledged);
+
+    // Nothing waits for the server to apply the writes, so poll until they are visible.
+    let expected = doc! { "y" => true };
+    let mut count = 0;
+    for _ in 0..50 {
+        count = coll.count(Some(expected.clone()), None).expect("Failed to count documents.");
+        if count == 2 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(2, count);
+
+    let mut collation_options = DeleteOptions::new();
+    collation_options.write_concern = Some(write_concern);
+    collation_options.collation = Some(Collation::new("en"));
+    match coll.delete_many(doc! {}, Some(collation_options)) {
+        Err(Error::ArgumentError(_)) => (),
+        _ => panic!("Expected collation with w:0 to be rejected."),
+    }
+}
+
+#[test]
+fn aggregation_pipeline_builder() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("aggregation_pipeline_builder");
+
+    coll.drop().expect("Failed to drop collection");
+
+    let doc1 = doc! { "tags" => ["a", "b", "c"] };
+    let doc2 = doc! { "tags" => ["a", "b", "d"] };
+    let doc3 = doc! { "tags" => ["d", "e", "f"] };
+
+    coll.insert_many(vec![doc1, doc2, doc3], None)
+        .expect("Failed to execute insert_many command.");
+
+    let count = Accumulator::Sum(Bson::I32(1));
+    let pipeline = Pipeline::new()
+        .project(doc! { "tags" => 1 })
+        .unwind("$tags", None)
+        .group(Bson::String(String::from("$tags")), vec![(String::from("n"), count)])
+        .sort(doc! { "n" => (-1), "_id" => 1 })
+        .limit(2)
+        .build()
+        .expect("Failed to build pipeline.");
+
+    assert_eq!(doc! { "$unwind" => "$tags" }, pipeline[1]);
+    assert_eq!(doc! { "$group" => { "_id" => "$tags", "n" => { "$sum" => 1 } } },
+               pipeline[2]);
+
+    let mut cursor = coll.aggregate(pipeline, None).expect("Failed to execute aggregate command.");
+    let results = cursor.next_n(10).expect("Failed to get next 10 from cursor.");
+    assert_eq!(2, results.len());
+    assert_eq!(Some(&Bson::String(String::from("a"))), results[0].get("_id"));
+    assert_eq!(Some(&Bson::I32(2)), results[0].get("n"));
+
+    assert!(Pipeline::new().out("tags").match_(doc! {}).build().is_err());
+    assert!(Pipeline::new().unwind("tags", None).build().is_err());
+    assert!(Pipeline::new().count("$n").build().is_err());
+
+    let max = Accumulator::Max(Bson::String(String::from("$n")));
+    assert!(Pipeline::new()
+        .group(Bson::Null, vec![(String::from("_id"), max)])
+        .build()
+        .is_err());
+
+    let facets = vec![(String::from("all"), Pipeline::new().out("tags"))];
+    assert!(Pipeline::new().facet(facets).build().is_err());
+}
+
+#[test]
+fn aggregate_out() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("aggregate_out");
+    let out_coll = db.collection("aggregate_out_results");
+
+    coll.drop().expect("Failed to drop collection");
+    out_coll.drop().expect("Failed to drop collection");
+
+    coll.insert_many(vec![doc! { "x" => 1 }, doc! { "x" => 2 }, doc! { "x" => 3 }], None)
+        .expect("Failed to execute insert_many command.");
+
+    let pipeline = Pipeline::new()
+        .match_(doc! { "x" => { "$gt" => 1 } })
+        .out("aggregate_out_results")
+        .build()
+        .expect("Failed to build pipeline.");
+
+    let mut options = AggregateOptions::new();
+    options.max_time_ms = Some(10000);
+    options.comment = Some(String::from("nightly rollup"));
+    options.bypass_document_validation = Some(true);
+    options.write_concern = Some(WriteConcern::new());
+
+    let mut cursor = coll.aggregate(pipeline, Some(options))
+        .expect("Failed to execute aggregate with $out.");
+    assert!(cursor.next().is_none());
+
+    assert_eq!(2, out_coll.count(None, None).expect("Failed to count output documents."));
+}
+
+#[test]
+fn map_reduce() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("map_reduce");
+    let out_coll = db.collection("map_reduce_results");
+
+    coll.drop().expect("Failed to drop collection");
+    out_coll.drop().expect("Failed to drop collection");
+
+    let docs = vec![doc! { "tag" => "a", "n" => 1 },
+                    doc! { "tag" => "a", "n" => 2 },
+                    doc! { "tag" => "b", "n" => 3 }];
+    coll.insert_many(docs, None).expect("Failed to insert documents.");
+
+    let map = Bson::JavaScriptCode(String::from("function() { emit(this.tag, this.n); }"));
+    let reduce = Bson::JavaScriptCode(String::from("function(key, values) { \
+                                                    return Array.sum(values); }"));
+
+    let mut options = MapReduceOptions::new();
+    options.sort = Some(doc! { "tag" => 1 });
+    match coll.map_reduce(map.clone(), reduce.clone(), Some(options))
+        .expect("Failed to run inline mapReduce.") {
+        MapReduceResult::Inline(mut cursor, stats) => {
+            assert_eq!(Some(3), stats.input_count);
+            let results = cursor.next_n(10).expect("Failed to get results from cursor.");
+            assert_eq!(2, results.len());
+            assert_eq!(Some(&Bson::FloatingPoint(3.0)), results[0].get("value"));
+        }
+        MapReduceResult::Collection(_) => panic!("Expected inline results."),
+    }
+
+    let mut options = MapReduceOptions::new();
+    options.query = Some(doc! { "tag" => "a" });
+    options.out = MapReduceOutput::Replace {
+        collection: String::from("map_reduce_results"),
+        db: None,
+    };
+    match coll.map_reduce(map.clone(), reduce.clone(), Some(options))
+        .expect("Failed to run mapReduce with an output collection.") {
+        MapReduceResult::Collection(stats) => {
+            assert_eq!(Some(String::from("map_reduce_results")), stats.collection);
+            assert_eq!(Some(1), stats.output_count);
+        }
+        MapReduceResult::Inline(..) => panic!("Expected results in a collection."),
+    }
+    assert_eq!(1, out_coll.count(None, None).expect("Failed to count output documents."));
+
+    let not_code = Bson::String(String::from("function() {}"));
+    assert!(coll.map_reduce(not_code, reduce, None).is_err());
+}
+
+#[test]
+fn collection_administration() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll-collection_administration");
+    db.drop_database().expect("Failed to drop database");
+
+    let coll = db.collection("events");
+    coll.insert_many(vec![doc! { "n" => 1 }, doc! { "n" => 2 }], None)
+        .expect("Failed to insert documents.");
+
+    let mut index_options = IndexOptions::new();
+    index_options.expire_after_seconds = Some(3600);
+    coll.create_index(doc! { "created" => 1 }, Some(index_options))
+        .expect("Failed to create TTL index.");
+
+    let stats = coll.stats().expect("Failed to get collection stats.");
+    assert_eq!("test-client-coll-collection_administration.events", stats.ns);
+    assert_eq!(2, stats.count);
+    assert_eq!(2, stats.nindexes);
+    assert!(stats.index_sizes.contains_key("_id_"));
+    assert!(!stats.capped);
+
+    let result = coll.validate(true).expect("Failed to validate collection.");
+    assert!(result.valid);
+    assert!(result.errors.is_empty());
+
+    let mut index = IndexModification::new();
+    index.keys = Some(doc! { "created" => 1 });
+    index.expire_after_seconds = Some(60);
+
+    let mut options = CollModOptions::new();
+    options.validator = Some(doc! { "n" => { "$exists" => true } });
+    options.validation_level = Some(ValidationLevel::Moderate);
+    options.validation_action = Some(ValidationAction::Error);
+    options.index = Some(index);
+    coll.modify(options).expect("Failed to modify collection.");
+    let result = coll.insert_one(doc! { "m" => 1 }, None).expect("Failed to insert document.");
+    assert!(result.write_exception.is_some());
+
+    let mut options = CollModOptions::new();
+    options.index = Some(IndexModification::new());
+    assert!(coll.modify(options).is_err());
+
+    db.collection("archive").insert_one(doc! { "n" => 0 }, None)
+        .expect("Failed to insert document.");
+    assert!(coll.rename("archive", false).is_err());
+    coll.rename("archive", true).expect("Failed to rename collection.");
+
+    let names = db.collection_names(None).expect("Failed to list collections.");
+    assert!(names.contains(&String::from("archive")));
+    assert!(!names.contains(&String::from("events")));
+}
+
+#[test]
+fn sync_indexes() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("sync_indexes");
+
+    coll.drop().expect("Failed to drop collection");
+    coll.insert_one(doc! { "a" => 1 }, None).expect("Failed to insert document.");
+    coll.create_index(doc! { "legacy" => 1 }, None).expect("Failed to create index.");
+    coll.create_index(doc! { "b" => 1 }, None).expect("Failed to create index.");
+
+    let mut unique = IndexOptions::new();
+    unique.unique = Some(true);
+    let models = vec![IndexModel::new(doc! { "a" => 1 }, None),
+                      IndexModel::new(doc! { "b" => 1 }, Some(unique))];
+
+    // Changed options fail unless recreation is allowed.
+    assert!(coll.sync_indexes(models.clone(), None).is_err());
+
+    // A dry run that only finds changed options reports them as changes.
+    let mut changed_only = SyncOptions::new();
+    changed_only.dry_run = true;
+    let result = coll.sync_indexes(vec![models[1].clone()], Some(changed_only))
+        .expect("Failed to plan index synchronization.");
+    assert_eq!(vec![String::from("b_1")], result.changed);
+    assert!(result.created.is_empty());
+    assert!(result.recreated.is_empty());
+    assert!(result.dropped.is_empty());
+    assert!(result.has_changes());
+
+    // A dry run reports changed options instead of failing.
+    let mut options = SyncOptions::new();
+    options.drop_unmanaged = true;
+    options.dry_run = true;
+    let result = coll.sync_indexes(models.clone(), Some(options.clone()))
+        .expect("Failed to plan index synchronization.");
+    assert_eq!(vec![String::from("b_1")], result.changed);
+    assert!(result.recreated.is_empty());
+
+    options.recreate_changed = true;
+    let result = coll.sync_indexes(models.clone(), Some(options.clone()))
+        .expect("Failed to plan index synchronization.");
+    assert!(result.dry_run);
+    assert!(result.has_changes());
+    assert_eq!(vec![String::from("a_1")], result.created);
+    assert_eq!(vec![String::from("b_1")], result.changed);
+    assert_eq!(vec![String::from("b_1")], result.recreated);
+    assert_eq!(vec![String::from("legacy_1")], result.dropped);
+
+    let names: Vec<_> = coll.list_indexes()
+        .expect("Failed to list indexes.")
+        .filter_map(|index| match index.ok().and_then(|doc| doc.get("name").cloned()) {
+            Some(Bson::String(name)) => Some(name),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(3, names.len());
+
+    options.dry_run = false;
+    let result = coll.sync_indexes(models.clone(), Some(options.clone()))
+        .expect("Failed to synchronize indexes.");
+    assert!(!result.dry_run);
+    assert!(result.has_changes());
+    let result = coll.insert_one(doc! { "b" => 1 }, None).expect("Failed to insert document.");
+    assert!(result.write_exception.is_none());
+    let result = coll.insert_one(doc! { "b" => 1 }, None).expect("Failed to insert document.");
+    assert!(result.write_exception.is_some());
+
+    let result = coll.sync_indexes(models, Some(options))
+        .expect("Failed to synchronize indexes.");
+    assert!(!result.has_changes());
+    assert_eq!(2, result.unchanged.len());
+}
+
+#[test]
+fn partial_index_models() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-coll");
+    let coll = db.collection("partial_index_models");
+    coll.drop().expect("Failed to drop collection");
+
+    let mut options = IndexOptions::new();
+    options.name = Some(String::from("active_email"));
+    options.unique = Some(true);
+    options.partial_filter_expression = Some(doc! { "active" => true });
+    let model = IndexModel::new(doc! { "email" => 1 }, Some(options));
+
+    let mut create_options = CreateIndexesOptions::new();
+    create_options.max_time_ms = Some(10000);
+    let names = coll.create_indexes_with_options(vec![model], Some(create_options))
+        .expect("Failed to create index.");
+    assert_eq!(vec![String::from("active_email")], names);
+
+    // Inactive documents are not indexed, so they may share an email.
+    for active in vec![false, false, true] {
+        let result = coll.insert_one(doc! { "email" => "a@b.c", "active" => active }, None)
+            .expect("Failed to insert document.");
+        assert!(result.write_exception.is_none());
+    }
+    let result = coll.insert_one(doc! { "email" => "a@b.c", "active" => true }, None)
+        .expect("Failed to insert document.");
+    assert!(result.write_exception.is_some());
+
+    let models = coll.list_index_models().expect("Failed to list index models.");
+    assert_eq!(2, models.len());
+
+    let model = models.iter()
+        .find(|model| model.options.name == Some(String::from("active_email")))
+        .expect("Failed to find index model.");
+    assert_eq!(doc! { "email" => 1 }, model.keys);
+    assert_eq!(Some(true), model.options.unique);
+    assert_eq!(Some(doc! { "active" => true }),
+               model.options.partial_filter_expression);
+}
@@ -69,7 +69,7 @@ macro_rules! run_distinct_test {
 
 macro_rules! run_find_one_and_delete_test {
     ( $db:expr, $coll:expr, $filter:expr, $opt:expr, $outcome:expr ) => {{
-        let doc_opt = $coll.find_one_and_delete($filter, $opt).unwrap();
+        let doc_opt = $coll.find_one_and_delete($filter, $opt).unwrap().value;
 
         let bson = match doc_opt {
             Some(ref doc) => Bson::Document(doc.clone()),
@@ -85,7 +85,7 @@ macro_rules! run_find_one_and_replace_test {
     ( $db:expr, $coll:expr, $filter:expr, $replacement:expr, $opt:expr,
       $outcome:expr ) => {{
           let doc_opt = $coll.find_one_and_replace($filter, $replacement,
-                                                   $opt).unwrap();
+                                                   $opt).unwrap().value;
 
           let bson = match doc_opt {
               Some(ref doc) => Bson::Document(doc.clone()),
@@ -100,7 +100,7 @@ macro_rules! run_find_one_and_replace_test {
 macro_rules! run_find_one_and_update_test {
     ( $db:expr, $coll:expr, $filter:expr, $update:expr, $opt:expr,
       $outcome:expr ) => {{
-          let doc_opt = $coll.find_one_and_update($filter, $update, $opt).unwrap();
+          let doc_opt = $coll.find_one_and_update($filter, $update, $opt).unwrap().value;
 
           let bson = match doc_opt {
               Some(ref doc) => Bson::Document(doc.clone()),
@@ -1,11 +1,15 @@
 use bson::{Bson, Document};
 
-use mongodb::{Client, CommandType, ThreadedClient};
-use mongodb::common::{ReadMode, ReadPreference};
+use mongodb::{Client, CommandStarted, CommandType, ThreadedClient};
+use mongodb::coll::options::{CursorType, FindOptions};
+use mongodb::common::{ReadConcern, ReadConcernLevel, ReadMode, ReadPreference};
 use mongodb::db::ThreadedDatabase;
+use mongodb::db::options::CreateCollectionOptions;
 use mongodb::cursor::Cursor;
 use mongodb::wire_protocol::flags::OpQueryFlags;
 
+use std::sync::atomic::{ATOMIC_USIZE_INIT, AtomicUsize, Ordering};
+
 #[test]
 fn cursor_features() {
     let client = Client::connect("localhost", 27017).unwrap();
@@ -77,3 +81,114 @@ fn cursor_features() {
         };
     }
 }
+
+#[test]
+fn tailable_cursor() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-cursor");
+    db.drop_collection("tailable_test").expect("Failed to drop collection.");
+
+    let mut options = CreateCollectionOptions::new();
+    options.capped = true;
+    options.size = Some(100000);
+    db.create_collection("tailable_test", Some(options)).expect("Failed to create collection.");
+
+    let coll = db.collection("tailable_test");
+    coll.insert_one(doc! { "job" => 0 }, None).expect("Failed to insert document.");
+
+    let mut find_options = FindOptions::new();
+    find_options.cursor_type = CursorType::TailableAwait;
+    find_options.max_await_time_ms = Some(100);
+
+    let mut cursor = coll.find(None, Some(find_options)).expect("Failed to execute find.");
+    assert!(cursor.try_next().expect("Failed to read from cursor.").is_some());
+
+    // The cursor stays open after an empty batch.
+    assert!(cursor.try_next().expect("Failed to poll cursor.").is_none());
+    assert!(!cursor.is_closed());
+
+    coll.insert_one(doc! { "job" => 1 }, None).expect("Failed to insert document.");
+    match cursor.next() {
+        Some(Ok(doc)) => assert_eq!(Some(&Bson::I32(1)), doc.get("job")),
+        _ => panic!("Expected the tailable cursor to return the new document."),
+    }
+
+    db.drop_collection("tailable_test").expect("Failed to drop collection.");
+    while let Ok(Some(_)) = cursor.try_next() {}
+    assert!(cursor.is_closed());
+}
+
+#[test]
+fn tailable_cursor_with_read_concern() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-cursor");
+    db.drop_collection("tailable_read_concern").expect("Failed to drop collection.");
+
+    let mut options = CreateCollectionOptions::new();
+    options.capped = true;
+    options.size = Some(100000);
+    db.create_collection("tailable_read_concern", Some(options))
+        .expect("Failed to create collection.");
+
+    let coll = db.collection("tailable_read_concern");
+    coll.insert_one(doc! { "job" => 0 }, None).expect("Failed to insert document.");
+
+    // A read concern sends the query as a find command, which must keep the cursor tailable.
+    let mut find_options = FindOptions::new();
+    find_options.cursor_type = CursorType::TailableAwait;
+    find_options.max_await_time_ms = Some(100);
+    find_options.read_concern = Some(ReadConcern::new(ReadConcernLevel::Local));
+
+    let mut cursor = coll.find(None, Some(find_options)).expect("Failed to execute find.");
+    assert!(cursor.try_next().expect("Failed to read from cursor.").is_some());
+
+    assert!(cursor.try_next().expect("Failed to poll cursor.").is_none());
+    assert!(!cursor.is_closed());
+
+    coll.insert_one(doc! { "job" => 1 }, None).expect("Failed to insert document.");
+    match cursor.next() {
+        Some(Ok(doc)) => assert_eq!(Some(&Bson::I32(1)), doc.get("job")),
+        _ => panic!("Expected the tailable cursor to return the new document."),
+    }
+
+    db.drop_collection("tailable_read_concern").expect("Failed to drop collection.");
+}
+
+static AWAIT_GET_MORES: AtomicUsize = ATOMIC_USIZE_INIT;
+
+fn record_await_get_mores(_client: Client, event: &CommandStarted) {
+    if event.command_name == "get_more" &&
+       event.command.get("maxTimeMS") == Some(&Bson::I64(100)) {
+        AWAIT_GET_MORES.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn await_data_cursor_sends_max_await_time() {
+    let mut client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-cursor");
+    db.drop_collection("await_time_test").expect("Failed to drop collection.");
+
+    let mut options = CreateCollectionOptions::new();
+    options.capped = true;
+    options.size = Some(100000);
+    db.create_collection("await_time_test", Some(options)).expect("Failed to create collection.");
+
+    let coll = db.collection("await_time_test");
+    coll.insert_one(doc! { "job" => 0 }, None).expect("Failed to insert document.");
+    client.add_start_hook(record_await_get_mores).unwrap();
+
+    let mut find_options = FindOptions::new();
+    find_options.cursor_type = CursorType::TailableAwait;
+    find_options.max_await_time_ms = Some(100);
+
+    let mut cursor = coll.find(None, Some(find_options)).expect("Failed to execute find.");
+    assert!(cursor.try_next().expect("Failed to read from cursor.").is_some());
+
+    // The server waits for the await time itself, so a single getMore is sent.
+    assert!(cursor.try_next().expect("Failed to poll cursor.").is_none());
+    assert!(!cursor.is_closed());
+    assert_eq!(1, AWAIT_GET_MORES.load(Ordering::SeqCst));
+
+    db.drop_collection("await_time_test").expect("Failed to drop collection.");
+}
@@ -1,6 +1,6 @@
 use mongodb::common::WriteConcern;
 use mongodb::coll::error::{BulkWriteException, WriteConcernError, WriteError};
-use mongodb::Error;
+use mongodb::{Error, ErrorCode};
 
 #[test]
 fn validate_write_result() {
@@ -123,3 +123,28 @@ fn parse_invalid_write_error() {
     let result = WriteError::parse(doc);
     assert!(result.is_err());
 }
+
+#[test]
+fn classify_state_change_errors() {
+    assert_eq!(Some(ErrorCode::NotMaster), ErrorCode::from_i32(10107));
+    assert_eq!(None, ErrorCode::from_i32(-12345));
+
+    let not_master = ErrorCode::from_i32(13435).unwrap();
+    assert!(not_master.is_not_master());
+    assert!(not_master.is_state_change_error());
+    assert!(!not_master.is_shutdown());
+
+    let recovering = ErrorCode::from_i32(91).unwrap();
+    assert!(recovering.is_node_recovering());
+    assert!(recovering.is_shutdown());
+    assert!(recovering.is_state_change_error());
+
+    assert!(!ErrorCode::DuplicateKey.is_state_change_error());
+}
+
+#[test]
+fn coded_error_keeps_message() {
+    let err = Error::CodedError(ErrorCode::NotMaster, String::from("not master"));
+    assert_eq!("NotMaster: not master", format!("{}", err));
+    assert!(err.is_retryable());
+}
@@ -9,8 +9,19 @@ mod gridfs;
 mod wire_protocol;
 
 use bson;
-use mongodb::{Client, ThreadedClient};
+use mongodb::{Client, ClientOptions, CommandStarted, CommandType, ThreadedClient};
+use mongodb::Error::OperationError;
+use mongodb::common::{Acknowledgment, ReadConcern, ReadConcernLevel, ReadMode, ReadPreference,
+                      WriteConcern};
+use mongodb::coll::options::FindOptions;
+use mongodb::connstring::{self, ConnectionString};
 use mongodb::db::ThreadedDatabase;
+use mongodb::topology::{TopologyDescription, TopologyType};
+use mongodb::topology::monitor::IsMasterResult;
+use mongodb::topology::server::{Server, ServerDescription, ServerType};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{ATOMIC_USIZE_INIT, AtomicUsize, Ordering};
 use std::thread;
 
 #[test]
@@ -96,3 +107,348 @@ fn is_sync() {
     assert!(results.contains(&"test-client-mod-is_sync".to_owned()));
     assert!(results.contains(&"test-client-mod-is_sync_2".to_owned()));
 }
+
+#[test]
+fn shutdown() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let coll = client.db("test-client-mod-shutdown").collection("test");
+    coll.drop().expect("Failed to drop collection");
+
+    let docs = (0..50).map(|i| doc! { "i" => i }).collect();
+    coll.insert_many(docs, None).expect("Failed to insert documents.");
+
+    // Leave a server cursor open across the shutdown.
+    let mut cursor = coll.find(None, None).expect("Failed to execute find.");
+    assert!(cursor.next().is_some());
+
+    client.shutdown().expect("Failed to shut down client.");
+    assert!(client.database_names().is_err());
+
+    // Shutting down an already closed client is a no-op.
+    client.shutdown().expect("Failed to shut down client twice.");
+}
+
+#[test]
+fn topology_snapshot_round_trip_times() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    client.database_names().expect("Failed to execute database_names.");
+
+    let snapshot = client.topology_snapshot().expect("Failed to take topology snapshot.");
+    assert!(!snapshot.servers.is_empty());
+
+    for description in snapshot.servers.values() {
+        if description.server_type != ServerType::Unknown {
+            assert!(description.round_trip_time.is_some());
+            assert!(description.last_round_trip_time.is_some());
+        }
+    }
+}
+
+#[test]
+fn round_trip_time_average_and_latency_window() {
+    let ismaster = || IsMasterResult::new(doc! { "ok" => 1, "ismaster" => true }).unwrap();
+
+    let mut description = ServerDescription::new();
+    description.update(ismaster(), 100);
+    assert_eq!(Some(100), description.round_trip_time);
+    assert_eq!(Some(100), description.last_round_trip_time);
+
+    // A single slow check only moves the average a fifth of the way towards it.
+    description.update(ismaster(), 600);
+    assert_eq!(Some(200), description.round_trip_time);
+    assert_eq!(Some(600), description.last_round_trip_time);
+
+    description.update(ismaster(), 200);
+    assert_eq!(Some(200), description.round_trip_time);
+
+    // Errors reset the average.
+    description.set_err(OperationError(String::from("network error")));
+    assert_eq!(None, description.round_trip_time);
+    assert_eq!(None, description.last_round_trip_time);
+    description.update(ismaster(), 50);
+    assert_eq!(Some(50), description.round_trip_time);
+
+    // Only servers within local_threshold_ms of the fastest server are selectable.
+    let dummy_config = ConnectionString::new("i-dont-exist", 27017);
+    let dummy_client = Client::with_config(dummy_config, None, None).unwrap();
+    let dummy_top_arc = Arc::new(RwLock::new(TopologyDescription::new()));
+
+    let mut topology_description = TopologyDescription::new();
+    topology_description.topology_type = TopologyType::Sharded;
+    topology_description.local_threshold_ms = 15;
+
+    let mut hosts = Vec::new();
+    for &(address, rtt) in &[("a:27017", 10), ("b:27017", 25), ("c:27017", 26)] {
+        let host = connstring::parse_host(address).unwrap();
+        let server = Server::new(dummy_client.clone(), host.clone(), dummy_top_arc.clone(), false);
+        server.description.write().unwrap().round_trip_time = Some(rtt);
+        topology_description.servers.insert(host.clone(), server);
+        hosts.push(host);
+    }
+
+    topology_description.filter_latency_hosts(&mut hosts);
+    assert_eq!(2, hosts.len());
+    assert!(hosts.contains(&connstring::parse_host("a:27017").unwrap()));
+    assert!(hosts.contains(&connstring::parse_host("b:27017").unwrap()));
+}
+
+#[test]
+fn direct_connection_and_load_balanced_uris() {
+    let client = Client::with_uri("mongodb://localhost:27017/?directConnection=true").unwrap();
+    let snapshot = client.topology_snapshot().unwrap();
+    assert_eq!(TopologyType::Single, snapshot.topology_type);
+
+    let client = Client::with_uri("mongodb://localhost:27017/?loadBalanced=true").unwrap();
+    let snapshot = client.topology_snapshot().unwrap();
+    assert_eq!(TopologyType::LoadBalanced, snapshot.topology_type);
+    for description in snapshot.servers.values() {
+        assert_eq!(ServerType::LoadBalancer, description.server_type);
+    }
+
+    assert!(Client::with_uri("mongodb://a:27017,b:27017/?directConnection=true").is_err());
+    assert!(Client::with_uri("mongodb://a:27017,b:27017/?loadBalanced=true").is_err());
+    assert!(Client::with_uri("mongodb://localhost/?loadBalanced=true&replicaSet=rs").is_err());
+    assert!(Client::with_uri("mongodb://localhost/?directConnection=yes").is_err());
+}
+
+#[test]
+fn load_balanced_ignores_tag_sets() {
+    let dummy_config = ConnectionString::new("i-dont-exist", 27017);
+    let dummy_client = Client::with_config(dummy_config, None, None).unwrap();
+    let dummy_top_arc = Arc::new(RwLock::new(TopologyDescription::new()));
+
+    let host = connstring::parse_host("lb:27017").unwrap();
+    let server = Server::new(dummy_client, host.clone(), dummy_top_arc, false);
+    server.description.write().unwrap().server_type = ServerType::LoadBalancer;
+
+    let mut topology_description = TopologyDescription::new();
+    topology_description.topology_type = TopologyType::LoadBalanced;
+    topology_description.servers.insert(host.clone(), server);
+
+    let mut tags = BTreeMap::new();
+    tags.insert(String::from("dc"), String::from("ny"));
+    let read_pref = ReadPreference::new(ReadMode::Nearest, Some(vec![tags]));
+
+    let mut hosts = topology_description.choose_hosts(&read_pref);
+    topology_description.filter_hosts(&mut hosts, &read_pref);
+    assert_eq!(vec![host], hosts);
+}
+
+#[test]
+fn read_concern_inheritance() {
+    let client = Client::with_uri("mongodb://localhost:27017/?readConcernLevel=local").unwrap();
+    assert_eq!(ReadConcern::new(ReadConcernLevel::Local), client.read_concern);
+
+    let db = client.db("test-client-read-concern");
+    assert_eq!(client.read_concern, db.read_concern);
+
+    let coll = db.collection("read_concern_inheritance");
+    assert_eq!(&db.read_concern, coll.read_concern());
+
+    coll.drop().unwrap();
+    coll.insert_one(doc! { "x" => 1 }, None).unwrap();
+    assert!(coll.find_one(None, None).unwrap().is_some());
+    assert_eq!(1, coll.count(None, None).unwrap());
+
+    let coll = db.with_read_concern(ReadConcern::default()).collection("read_concern_inheritance");
+    assert_eq!(&ReadConcern::default(), coll.read_concern());
+
+    let mut options = FindOptions::new();
+    options.read_concern = Some(ReadConcern::new(ReadConcernLevel::Local));
+    assert!(coll.find_one(None, Some(options)).unwrap().is_some());
+
+    assert!(Client::with_uri("mongodb://localhost:27017/?readConcernLevel=strong").is_err());
+}
+
+#[test]
+fn write_concern_options() {
+    let uri = "mongodb://localhost:27017/?w=majority&wtimeoutMS=500&journal=true";
+    let client = Client::with_uri(uri).unwrap();
+    assert_eq!(Acknowledgment::Majority, client.write_concern.w);
+    assert_eq!(500, client.write_concern.w_timeout);
+    assert!(client.write_concern.j);
+    assert_eq!(doc! { "w" => "majority", "wtimeout" => 500, "j" => true },
+               client.write_concern.to_bson());
+
+    let client = Client::with_uri("mongodb://localhost:27017/?w=dataCenters").unwrap();
+    assert_eq!(Acknowledgment::Tag(String::from("dataCenters")), client.write_concern.w);
+
+    let mut write_concern = WriteConcern::new();
+    write_concern.fsync = true;
+    assert_eq!(doc! { "w" => 1, "fsync" => true }, write_concern.to_bson());
+
+    write_concern.w = Acknowledgment::Nodes(0);
+    assert!(write_concern.is_acknowledged());
+    assert!(write_concern.validate().is_err());
+
+    write_concern.fsync = false;
+    assert!(!write_concern.is_acknowledged());
+    assert!(write_concern.validate().is_ok());
+
+    write_concern.j = true;
+    assert!(write_concern.validate().is_err());
+    assert!(Client::with_uri("mongodb://localhost:27017/?w=0&journal=true").is_err());
+}
+
+#[test]
+fn retry_writes_options() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    assert!(client.retry_writes);
+
+    let client = Client::with_uri("mongodb://localhost:27017/?retryWrites=false").unwrap();
+    assert!(!client.retry_writes);
+
+    let mut options = ClientOptions::new();
+    options.retry_writes = Some(true);
+    let client = Client::with_uri_and_options("mongodb://localhost:27017/?retryWrites=false",
+                                              options)
+        .unwrap();
+    assert!(client.retry_writes);
+
+    assert!(Client::with_uri("mongodb://localhost:27017/?retryWrites=yes").is_err());
+
+    // Retryable writes behave like any other write, whether or not the server supports them.
+    let coll = client.db("test-client-mod").collection("retry_writes_options");
+    coll.drop().unwrap();
+    coll.insert_one(doc! { "_id" => 1 }, None).unwrap();
+    coll.update_one(doc! { "_id" => 1 }, doc! { "$set" => { "x" => 1 } }, None).unwrap();
+    coll.find_one_and_delete(doc! { "_id" => 1 }, None).unwrap();
+    assert_eq!(0, coll.count(None, None).unwrap());
+}
+
+#[test]
+fn retry_reads_options() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    assert!(client.retry_reads);
+
+    let client = Client::with_uri("mongodb://localhost:27017/?retryReads=false").unwrap();
+    assert!(!client.retry_reads);
+
+    let mut options = ClientOptions::new();
+    options.retry_reads = Some(true);
+    let client = Client::with_uri_and_options("mongodb://localhost:27017/?retryReads=false",
+                                              options)
+        .unwrap();
+    assert!(client.retry_reads);
+
+    assert!(Client::with_uri("mongodb://localhost:27017/?retryReads=yes").is_err());
+
+    // Retryable reads behave like any other read when the first attempt succeeds.
+    let coll = client.db("test-client-mod").collection("retry_reads_options");
+    coll.drop().unwrap();
+    coll.insert_many(vec![doc! { "x" => 1 }, doc! { "x" => 2 }], None).unwrap();
+
+    assert_eq!(2, coll.find(None, None).unwrap().count());
+    assert_eq!(2, coll.count(None, None).unwrap());
+    assert_eq!(2, coll.distinct("x", None, None).unwrap().len());
+    assert_eq!(1,
+               coll.aggregate(vec![doc! { "$match" => { "x" => 1 } }], None).unwrap().count());
+    assert_eq!(1, coll.list_indexes().unwrap().count());
+}
+
+static COUNT_ATTEMPTS: AtomicUsize = ATOMIC_USIZE_INIT;
+static COUNT_RETRIES: AtomicUsize = ATOMIC_USIZE_INIT;
+
+fn record_count_attempts(_client: Client, event: &CommandStarted) {
+    if event.command_name == "count" {
+        COUNT_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        if event.retry {
+            COUNT_RETRIES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn retry_reads_after_network_error() {
+    let mut client = Client::connect("localhost", 27017).unwrap();
+    let coll = client.db("test-client-mod").collection("retry_reads_after_network_error");
+    coll.drop().unwrap();
+    coll.insert_one(doc! { "x" => 1 }, None).unwrap();
+    client.add_start_hook(record_count_attempts).unwrap();
+
+    // The server closes the connection on the first count, which is retried once.
+    let admin = client.db("admin");
+    admin.command(doc! {
+                      "configureFailPoint" => "failCommand",
+                      "mode" => { "times" => 1 },
+                      "data" => { "failCommands" => ["count"], "closeConnection" => true }
+                  },
+                  CommandType::Suppressed,
+                  None)
+        .unwrap();
+
+    let count = coll.count(None, None);
+    admin.command(doc! { "configureFailPoint" => "failCommand", "mode" => "off" },
+                  CommandType::Suppressed,
+                  None)
+        .unwrap();
+
+    assert_eq!(1, count.unwrap());
+    assert_eq!(2, COUNT_ATTEMPTS.load(Ordering::SeqCst));
+    assert_eq!(1, COUNT_RETRIES.load(Ordering::SeqCst));
+}
+
+static INSERT_ATTEMPTS: AtomicUsize = ATOMIC_USIZE_INIT;
+static INSERT_RETRIES: AtomicUsize = ATOMIC_USIZE_INIT;
+
+fn record_insert_attempts(_client: Client, event: &CommandStarted) {
+    if event.command_name == "insert_one" {
+        INSERT_ATTEMPTS.fetch_add(1, Ordering::SeqCst);
+        if event.retry {
+            INSERT_RETRIES.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn retry_writes_after_network_and_failover_errors() {
+    let mut client = Client::connect("localhost", 27017).unwrap();
+    let admin = client.db("admin");
+
+    // Retryable writes are only supported by replica sets and sharded clusters.
+    let is_master = admin.command(doc! { "isMaster" => 1 }, CommandType::IsMaster, None).unwrap();
+    if !is_master.contains_key("setName") {
+        return;
+    }
+
+    let coll = client.db("test-client-mod").collection("retry_writes_after_errors");
+    coll.drop().unwrap();
+    client.add_start_hook(record_insert_attempts).unwrap();
+
+    // The server closes the connection on the first insert, which is retried once.
+    admin.command(doc! {
+                      "configureFailPoint" => "failCommand",
+                      "mode" => { "times" => 1 },
+                      "data" => { "failCommands" => ["insert"], "closeConnection" => true }
+                  },
+                  CommandType::Suppressed,
+                  None)
+        .unwrap();
+
+    let result = coll.insert_one(doc! { "x" => 1 }, None);
+    assert!(result.is_ok());
+    assert_eq!(1, coll.count(None, None).unwrap());
+    assert_eq!(2, INSERT_ATTEMPTS.load(Ordering::SeqCst));
+    assert_eq!(1, INSERT_RETRIES.load(Ordering::SeqCst));
+
+    // The server reports that it is no longer primary, so the insert is retried once.
+    admin.command(doc! {
+                      "configureFailPoint" => "failCommand",
+                      "mode" => { "times" => 1 },
+                      "data" => { "failCommands" => ["insert"], "errorCode" => 10107 }
+                  },
+                  CommandType::Suppressed,
+                  None)
+        .unwrap();
+
+    let result = coll.insert_one(doc! { "x" => 2 }, None);
+    admin.command(doc! { "configureFailPoint" => "failCommand", "mode" => "off" },
+                  CommandType::Suppressed,
+                  None)
+        .unwrap();
+
+    assert!(result.is_ok());
+    assert_eq!(2, coll.count(None, None).unwrap());
+    assert_eq!(4, INSERT_ATTEMPTS.load(Ordering::SeqCst));
+    assert_eq!(2, INSERT_RETRIES.load(Ordering::SeqCst));
+}
@@ -1,7 +1,7 @@
 use bson::{Bson, Document};
 use mongodb::{Client, ThreadedClient};
 use mongodb::db::ThreadedDatabase;
-use mongodb::wire_protocol::flags::{OpInsertFlags, OpQueryFlags, OpUpdateFlags};
+use mongodb::wire_protocol::flags::{OpDeleteFlags, OpInsertFlags, OpQueryFlags, OpUpdateFlags};
 use mongodb::wire_protocol::operations::Message;
 use std::net::TcpStream;
 
@@ -293,3 +293,74 @@ fn insert_update_then_query() {
         Err(_) => panic!("Could not connect to server"),
     }
 }
+
+#[test]
+fn insert_delete_then_query() {
+    let client = Client::connect("localhost", 27017).unwrap();
+    let db = client.db("test-client-wire_protocol-insert_delete_then_query");
+    db.drop_database().unwrap();
+
+    match TcpStream::connect("localhost:27017") {
+        Ok(mut stream) => {
+            let docs = vec![doc! { "foo" => 1 }, doc! { "foo" => 2 }, doc! { "foo" => 2 }];
+            let flags = OpInsertFlags::no_flags();
+            let name = "test-client-wire_protocol-insert_delete_then_query.delete".to_owned();
+            let res = Message::new_insert(1, flags, name, docs);
+
+            let cm = match res {
+                Ok(message) => message,
+                Err(_) => panic!("Could not create insert message!"),
+            };
+
+            match cm.write(&mut stream) {
+                Ok(_) => (),
+                Err(s) => panic!("{}", s),
+            };
+
+            let selector = doc! { "foo" => 2 };
+
+            let mut flags = OpDeleteFlags::no_flags();
+            flags.single_remove = true;
+            let name = "test-client-wire_protocol-insert_delete_then_query.delete".to_owned();
+            let res = Message::new_delete(2, name, flags, selector);
+
+            let cm = match res {
+                Ok(message) => message,
+                Err(_) => panic!("Could not create delete message!"),
+            };
+
+            match cm.write(&mut stream) {
+                Ok(_) => (),
+                Err(s) => panic!("{}", s),
+            };
+
+            let doc = Document::new();
+            let flags = OpQueryFlags::no_flags();
+            let name = "test-client-wire_protocol-insert_delete_then_query.delete".to_owned();
+            let res = Message::new_query(3, flags, name, 0, 0, doc, None);
+
+            let cm = match res {
+                Ok(message) => message,
+                Err(_) => panic!("Could not create query message!"),
+            };
+
+            match cm.write(&mut stream) {
+                Ok(_) => (),
+                Err(s) => panic!("{}", s),
+            };
+
+            let reply = match Message::read(&mut stream) {
+                Ok(m) => m,
+                Err(s) => panic!("Could not read response: {}", s),
+            };
+
+            let docs = match reply {
+                Message::OpReply { documents: d, .. } => d,
+                _ => panic!("Invalid response read from server"),
+            };
+
+            assert_eq!(docs.len() as i32, 2);
+        }
+        Err(_) => panic!("Could not connect to server"),
+    }
+}
@@ -1,5 +1,6 @@
 #[macro_use(bson, doc)]
 extern crate bson;
+extern crate chrono;
 extern crate mongodb;
 extern crate rand;
 extern crate rustc_serialize;